@@ -0,0 +1,195 @@
+use crate::{nasr, util};
+use eframe::{egui, emath};
+
+pub struct AirportDlg {
+  info: nasr::AirportInfo,
+  location: String,
+  remarks: Option<Vec<String>>,
+  remarks_requested: bool,
+}
+
+#[derive(Eq, PartialEq)]
+pub enum Response {
+  None,
+  Close,
+  GoTo,
+  Measure,
+  BearingLine,
+  Bookmark,
+  CopyId,
+  ExportSummary,
+}
+
+impl AirportDlg {
+  pub fn open(info: nasr::AirportInfo, coord_format: util::CoordFormat) -> Self {
+    let location = coord_format.format(info.coord).unwrap_or_default();
+    Self {
+      info,
+      location,
+      remarks: None,
+      remarks_requested: false,
+    }
+  }
+
+  /// Change the format of the location.
+  /// - `coord_format`: coordinate format
+  pub fn set_coord_format(&mut self, coord_format: util::CoordFormat) {
+    self.location = coord_format.format(self.info.coord).unwrap_or_default();
+  }
+
+  pub fn info(&self) -> &nasr::AirportInfo {
+    &self.info
+  }
+
+  /// Get a markdown summary of the airport for briefing notes.
+  pub fn summary(&self) -> String {
+    let info = &self.info;
+    let mut text = format!("# {} ({})\n\n", info.name, info.id);
+    if let Some(icao_id) = &info.icao_id {
+      text += &format!("- ICAO ID: {icao_id}\n");
+    }
+
+    let (airport_type, airport_use) = (info.airport_type.abv(), info.airport_use.abv());
+    text += &format!("- Type: {airport_type}, {airport_use}\n");
+    text += &format!("- Location: {}\n", self.location);
+    if let Some(mag_var) = info.mag_var {
+      let dir = if mag_var < 0.0 { 'W' } else { 'E' };
+      text += &format!("- Magnetic Variation: {:.0}°{dir}\n", mag_var.abs());
+    }
+
+    text += &format!("- Status: {}\n", info.status.text());
+    if info.towered {
+      text += "- Control Tower: Yes\n";
+    }
+
+    if let Some(remarks) = self.remarks.as_ref().filter(|remarks| !remarks.is_empty()) {
+      text += "\n## Remarks\n\n";
+      for remark in remarks {
+        text += &format!("- {remark}\n");
+      }
+    }
+    text
+  }
+
+  /// Returns the airport ID the first time it's called so that the remarks can be requested.
+  pub fn remarks_request(&mut self) -> Option<String> {
+    if self.remarks_requested {
+      return None;
+    }
+    self.remarks_requested = true;
+    Some(self.info.id.clone())
+  }
+
+  /// Set the airport remarks.
+  /// - `id`: airport ID the remarks are for
+  /// - `remarks`: remark text
+  pub fn set_remarks(&mut self, id: &str, remarks: Vec<String>) {
+    if id == self.info.id {
+      self.remarks = Some(remarks);
+    }
+  }
+
+  pub fn show(&mut self, ctx: &egui::Context) -> Response {
+    let key = util::dlg_key(ctx);
+    let mut response = Response::None;
+    let mut open = key != util::DlgKey::Cancel;
+
+    egui::Window::new(egui::RichText::from("✈  Airport").strong())
+      .open(&mut open)
+      .collapsible(false)
+      .resizable(false)
+      .anchor(emath::Align2::CENTER_CENTER, [0.0, 0.0])
+      .show(ctx, |ui| {
+        ui.add_space(8.0);
+        egui::Grid::new("airport_dlg_grid")
+          .num_columns(2)
+          .show(ui, |ui| {
+            let info = &self.info;
+            ui.label("ID:");
+            ui.label(&info.id);
+            ui.end_row();
+
+            if let Some(icao_id) = &info.icao_id {
+              ui.label("ICAO ID:");
+              ui.label(icao_id);
+              ui.end_row();
+            }
+
+            ui.label("Name:");
+            ui.label(&info.name);
+            ui.end_row();
+
+            ui.label("Type:");
+            ui.label(format!(
+              "{}, {}",
+              info.airport_type.abv(),
+              info.airport_use.abv()
+            ));
+            ui.end_row();
+
+            ui.label("Location:");
+            ui.label(&self.location);
+            ui.end_row();
+
+            if info.is_closed() {
+              ui.label("Status:");
+              ui.label(egui::RichText::new(info.status.text()).color(ui.visuals().warn_fg_color));
+              ui.end_row();
+            }
+          });
+
+        if let Some(remarks) = self.remarks.as_ref().filter(|remarks| !remarks.is_empty()) {
+          ui.add_space(8.0);
+          ui.label("Remarks:");
+          egui::ScrollArea::vertical()
+            .max_height(160.0)
+            .show(ui, |ui| {
+              for remark in remarks {
+                ui.add(egui::Label::new(remark).wrap(true));
+                ui.add_space(4.0);
+              }
+            });
+        }
+        ui.add_space(8.0);
+        ui.separator();
+        ui.horizontal(|ui| {
+          if ui.button("Go To").clicked() {
+            response = Response::GoTo;
+          }
+
+          if ui.button("Measure From Here").clicked() {
+            response = Response::Measure;
+          }
+
+          if ui.button("Bearing Line").clicked() {
+            response = Response::BearingLine;
+          }
+
+          if ui.button("Bookmark").clicked() {
+            response = Response::Bookmark;
+          }
+
+          if ui.button("Copy ID").clicked() {
+            response = Response::CopyId;
+          }
+
+          if ui.button("Export Summary").clicked() {
+            response = Response::ExportSummary;
+          }
+
+          if ui.button("Close").clicked() {
+            response = Response::Close;
+          }
+        });
+      });
+
+    if !open {
+      response = Response::Close;
+    } else if key == util::DlgKey::Confirm {
+      // Go to the airport by default.
+      response = Response::GoTo;
+    }
+
+    response
+  }
+}
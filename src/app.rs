@@ -1,28 +1,108 @@
-use crate::{chart, config, error_dlg, find_dlg, nasr, select_dlg, select_menu, touch, util};
+use crate::{
+  airport_dlg, bearing_dlg, chart, config, confirm_dlg, error_dlg, find_dlg, nasr, progress_dlg,
+  select_dlg, select_menu, toast, touch, util,
+};
 use eframe::{egui, emath, epaint, glow};
 use egui::scroll_area;
-use std::{ffi::OsStr, path, rc};
+use std::{cmp, collections, env, ffi::OsStr, fs, mem, path, rc, time};
 
 pub struct App {
   config: config::Storage,
   win_info: util::WinInfo,
+  restored_win: Option<util::WinInfo>,
   default_theme: egui::Visuals,
   asset_path: Option<path::PathBuf>,
+  charts_path: Option<path::PathBuf>,
   file_dlg: Option<egui_file::FileDialog>,
+  folder_dlg: Option<egui_file::FileDialog>,
+  airport_folder_dlg: Option<egui_file::FileDialog>,
+  summary_dlg: Option<(egui_file::FileDialog, String)>,
+  diagnostics_dlg: Option<(egui_file::FileDialog, Vec<u8>)>,
   find_dlg: Option<find_dlg::FindDlg>,
+  find_chart_only: bool,
+  bearing_dlg: Option<bearing_dlg::BearingDlg>,
   error_dlg: Option<error_dlg::ErrorDlg>,
+  airport_dlg: Option<airport_dlg::AirportDlg>,
+  reset_dlg: Option<confirm_dlg::ConfirmDlg>,
+  retry_dlg: Option<(confirm_dlg::ConfirmDlg, path::PathBuf, Vec<path::PathBuf>)>,
   select_dlg: select_dlg::SelectDlg,
   select_menu: select_menu::SelectMenu,
+  toast: Option<toast::Toast>,
   airport_reader: Option<nasr::AirportReader>,
   chart: Chart,
+  charts: Vec<Box<ChartInfo>>,
+  active_tab: usize,
+  split: Option<Box<ChartInfo>>,
+  split_sync: bool,
+  split_link: bool,
+  link_view: Option<(util::Rect, f32)>,
   airport_infos: AirportInfos,
+  markers: AirportMarkers,
+  nearby: NearbyAirports,
+  mag_var: MagVarLookup,
+  adjacent: AdjacentCharts,
+  dem_reader: Option<(path::PathBuf, chart::DemReader)>,
+  reloader: Option<(path::PathBuf, chart::RasterOpener)>,
+  bookmarks: Vec<util::Bookmark>,
+  home: Option<util::Coord>,
+  initial_view: bool,
+  measure: Option<MeasurePath>,
+  bearing_line: Option<(util::Coord, f64)>,
   long_press: touch::LongPressTracker,
+  feedback: touch::FeedbackPlayer,
   top_panel_height: u32,
   side_panel_width: u32,
   night_mode: bool,
+  ui_theme: util::UiTheme,
+  grayscale: bool,
+  bg_color: Option<[u8; 3]>,
+  night_bg_color: Option<[u8; 3]>,
+  sharpen: f32,
+  zoom_lock: bool,
+  scroll_snap: util::ScrollSnap,
+  image_limit: u32,
+  image_cache: u32,
+  dim_timeout: u32,
+  dim_level: u32,
+  last_input: time::Instant,
+  dimmed: bool,
+  chart_thumbnails: bool,
+  thumbnails: collections::HashMap<path::PathBuf, egui::TextureHandle>,
+  thumbnail_reader: Option<chart::ThumbnailReader>,
+  color_map: util::ColorMap,
+  color_vision: util::ColorVision,
+  emphasis: util::Emphasis,
+  emphasis_indices: Vec<u8>,
+  emphasis_text: String,
+  palette_index: u8,
+  units: util::Units,
+  coord_format: util::CoordFormat,
   side_panel: bool,
   ui_enabled: bool,
   include_nph: bool,
+  auto_load_aero: bool,
+  auto_hide_panel: bool,
+  open_fit: bool,
+  show_closed: bool,
+  show_private: bool,
+  towered_only: bool,
+  show_center: bool,
+  highlight_found: bool,
+  highlight: Option<(util::Coord, String)>,
+  last_click: Option<util::Coord>,
+  low_power: bool,
+  cache_overview: bool,
+  progressive: bool,
+  range_rings: u32,
+  screen_dpi: u32,
+  marker_size: util::MarkerSize,
+  zoom_step: util::ZoomStep,
+  goto_anchor: util::GotoAnchor,
+  marker_labels: bool,
+  long_press_feedback: bool,
+  show_magnetic: bool,
+  long_press_action: util::LongPressAction,
+  escape_action: util::EscapeAction,
 }
 
 impl App {
@@ -33,6 +113,19 @@ impl App {
     config: config::Storage,
   ) -> Self {
     let ctx = &cc.egui_ctx;
+
+    // Make sure GDAL can read the FAA data and log its version for bug reports.
+    let error_dlg = match util::gdal_self_test() {
+      Ok(version) => {
+        println!("GDAL {version}");
+        None
+      }
+      Err(err) => {
+        println!("{err}");
+        Some(error_dlg::ErrorDlg::open(err))
+      }
+    };
+
     if let Some(theme) = theme {
       ctx.set_visuals(theme);
     }
@@ -55,11 +148,38 @@ impl App {
     let default_theme = style.visuals.clone();
     ctx.set_style(style);
 
-    // If starting in night mode then set the dark theme.
+    // The UI theme can follow night mode or be set independently of it.
     let night_mode = config.get_night_mode().unwrap_or(false);
-    if night_mode {
-      ctx.set_visuals(dark_theme());
-    }
+    let ui_theme = config.get_ui_theme().unwrap_or_default();
+    ctx.set_visuals(theme_visuals(ui_theme, night_mode, &default_theme));
+
+    // Display preferences.
+    let grayscale = config.get_grayscale().unwrap_or(false);
+    let bg_color = config.get_bg_color();
+    let night_bg_color = config.get_night_bg_color();
+    let sharpen = config.get_sharpen().unwrap_or(0.0);
+    let scroll_snap = config.get_scroll_snap().unwrap_or_default();
+    let zoom_lock = config.get_zoom_lock().unwrap_or(false);
+    let image_limit = config.get_image_limit().unwrap_or(0);
+
+    // Memory budget for recently read chart images, in megabytes. Zero means no cache.
+    let image_cache = config.get_image_cache().unwrap_or(0);
+
+    // Dimming the display after a period of inactivity is off by default.
+    let dim_timeout = config.get_dim_timeout().unwrap_or(0);
+    let dim_level = config.get_dim_level().unwrap_or(DEFAULT_DIM_LEVEL);
+
+    // Show chart thumbnails when choosing from a zip file with more than one chart.
+    let chart_thumbnails = config.get_chart_thumbnails().unwrap_or(true);
+    let color_map = config.get_color_map().unwrap_or_default();
+    let color_vision = config.get_color_vision().unwrap_or_default();
+
+    // Emphasizing water or terrain is off by default. The palette indices are picked by color
+    // unless they've been set.
+    let emphasis = config.get_emphasis().unwrap_or_default();
+    let emphasis_indices = config.get_emphasis_indices(emphasis).unwrap_or_default();
+    let units = config.get_units().unwrap_or_default();
+    let coord_format = config.get_coord_format().unwrap_or_default();
 
     let asset_path = if let Some(asset_path) = config.get_asset_path() {
       Some(asset_path.into())
@@ -67,43 +187,208 @@ impl App {
       dirs::download_dir()
     };
 
+    // Folder that the user has chosen for charts.
+    let charts_path = config.get_charts_path().map(path::PathBuf::from);
+
+    // Opt-in to loading airport data found next to a chart.
+    let auto_load_aero = config.get_auto_load_aero().unwrap_or(false);
+
+    // Hide the side panel after opening a chart.
+    let auto_hide_panel = config.get_auto_hide_panel().unwrap_or(false);
+
+    // Closed airports are shown (and flagged) by default.
+    let show_closed = config.get_show_closed().unwrap_or(true);
+
+    // Private airports are also shown (and flagged) by default.
+    let show_private = config.get_show_private().unwrap_or(true);
+    let towered_only = config.get_towered_only().unwrap_or(false);
+
+    // Show the coordinate at the center of the view in the top panel.
+    let show_center = config.get_show_center().unwrap_or(false);
+
+    // Keep the airport found by a search highlighted.
+    let highlight_found = config.get_highlight_found().unwrap_or(true);
+
+    // Open charts zoomed out to fit the view instead of at full size.
+    let open_fit = config.get_open_fit().unwrap_or(false);
+
+    // Avoid animations that continuously repaint.
+    let low_power = config.get_low_power().unwrap_or(false);
+
+    // Keep the whole chart image in memory so that zooming out to fit is instant.
+    let cache_overview = config.get_cache_overview().unwrap_or(true);
+
+    // Show a low resolution image first when reading large parts of the chart.
+    let progressive = config.get_progressive().unwrap_or(false);
+
+    // Range ring interval, in the selected units. Zero means no rings.
+    let range_rings = config.get_range_rings().unwrap_or(0);
+
+    // Points per inch on the display, for showing the chart scale.
+    let screen_dpi = config.get_screen_dpi().unwrap_or(DEFAULT_SCREEN_DPI);
+
+    // Zoom factor for the zoom buttons and keys.
+    let zoom_step = config.get_zoom_step().unwrap_or_default();
+
+    // Where the find/goto result is placed on the screen.
+    let goto_anchor = config.get_goto_anchor().unwrap_or_default();
+
+    // Airport markers are off by default.
+    let marker_size = config.get_marker_size().unwrap_or_default();
+    let marker_labels = config.get_marker_labels().unwrap_or(true);
+
+    // Feedback for long-presses is on by default for mobile.
+    let long_press_feedback = config.get_long_press_feedback().unwrap_or(MOBILE);
+
+    // Show magnetic bearings along with true bearings.
+    let show_magnetic = config.get_show_magnetic().unwrap_or(true);
+    let long_press_action = config.get_long_press_action().unwrap_or_default();
+
+    // Escape closes the side panel once there's nothing else to clear.
+    let escape_action = config.get_escape_action().unwrap_or_default();
+
+    // Locations that the user has bookmarked.
+    let bookmarks = config.get_bookmarks().unwrap_or_default();
+
+    // Location that newly opened charts are centered on.
+    let home = config.get_home();
+
+    // Window info that was restored from the last session.
+    let restored_win = config.win_stored().then(|| config.get_win_info());
+
+    // Reopen the most recently loaded airport data.
+    let airport_reader = config
+      .get_nasr_path()
+      .and_then(|path| reopen_airport_data(path::Path::new(&path), ctx));
+
     Self {
       config,
       win_info: util::WinInfo::default(),
+      restored_win,
       default_theme,
       asset_path,
+      charts_path,
       file_dlg: None,
+      folder_dlg: None,
+      airport_folder_dlg: None,
+      summary_dlg: None,
+      diagnostics_dlg: None,
       find_dlg: None,
-      error_dlg: None,
+      find_chart_only: true,
+      bearing_dlg: None,
+      error_dlg,
+      airport_dlg: None,
+      reset_dlg: None,
+      retry_dlg: None,
       select_dlg: select_dlg::SelectDlg::new(),
       select_menu: select_menu::SelectMenu::default(),
-      airport_reader: None,
+      toast: None,
+      airport_reader,
       chart: Chart::None,
+      charts: Vec::new(),
+      active_tab: 0,
+      split: None,
+      split_sync: false,
+      split_link: false,
+      link_view: None,
       airport_infos: AirportInfos::None,
+      markers: AirportMarkers::default(),
+      nearby: NearbyAirports::default(),
+      mag_var: MagVarLookup::default(),
+      adjacent: AdjacentCharts::default(),
+      dem_reader: None,
+      reloader: None,
+      bookmarks,
+      home,
+      initial_view: false,
+      measure: None,
+      bearing_line: None,
       long_press: touch::LongPressTracker::new(ctx),
+      feedback: touch::FeedbackPlayer::new(),
       top_panel_height: 0,
       side_panel_width: 0,
       night_mode,
+      ui_theme,
+      grayscale,
+      bg_color,
+      night_bg_color,
+      sharpen,
+      zoom_lock,
+      scroll_snap,
+      image_limit,
+      image_cache,
+      dim_timeout,
+      dim_level,
+      last_input: time::Instant::now(),
+      dimmed: false,
+      chart_thumbnails,
+      thumbnails: collections::HashMap::new(),
+      thumbnail_reader: None,
+      color_map,
+      color_vision,
+      emphasis,
+      emphasis_text: util::format_indices(&emphasis_indices),
+      emphasis_indices,
+      palette_index: 0,
+      units,
+      coord_format,
       side_panel: true,
       ui_enabled: true,
       include_nph: false,
+      auto_load_aero,
+      auto_hide_panel,
+      open_fit,
+      show_closed,
+      show_private,
+      towered_only,
+      show_center,
+      highlight_found,
+      highlight: None,
+      last_click: None,
+      low_power,
+      cache_overview,
+      progressive,
+      range_rings,
+      screen_dpi,
+      marker_size,
+      zoom_step,
+      goto_anchor,
+      marker_labels,
+      long_press_feedback,
+      show_magnetic,
+      long_press_action,
+      escape_action,
     }
   }
 
-  fn select_zip_file(&mut self) {
+  fn select_zip_file(&mut self, zip_filter: ZipFilter) {
     let filter = Box::new({
       let zip_ext = Some(OsStr::new("zip"));
       move |path: &path::Path| {
         // Only show valid zip files.
         if path.extension() == zip_ext {
-          return util::get_zip_info(path).is_ok();
+          if let Ok(info) = util::get_zip_info(path) {
+            return match zip_filter {
+              ZipFilter::Any => true,
+              ZipFilter::Chart => matches!(info, util::ZipInfo::Chart(_)),
+              ZipFilter::Aero => matches!(info, util::ZipInfo::Aero { .. }),
+            };
+          }
         }
         false
       }
     });
 
-    let mut file_dlg = egui_file::FileDialog::open_file(self.asset_path.clone())
-      .title("Open ZIP File")
+    let title = match zip_filter {
+      ZipFilter::Any => "Open ZIP File",
+      ZipFilter::Chart => "Open Chart ZIP File",
+      ZipFilter::Aero => "Open Airport Data ZIP File",
+    };
+
+    // Start in the charts folder if one has been set.
+    let path = self.charts_path.clone().or_else(|| self.asset_path.clone());
+    let mut file_dlg = egui_file::FileDialog::open_file(path)
+      .title(title)
       .anchor(emath::Align2::CENTER_CENTER, [0.0, 0.0])
       .default_size([525.0, 320.0])
       .show_files_filter(filter)
@@ -114,19 +399,159 @@ impl App {
     self.file_dlg = Some(file_dlg);
   }
 
+  fn select_charts_folder(&mut self) {
+    let path = self.charts_path.clone().or_else(|| self.asset_path.clone());
+    let mut folder_dlg = egui_file::FileDialog::select_folder(path)
+      .title("Select Charts Folder")
+      .anchor(emath::Align2::CENTER_CENTER, [0.0, 0.0])
+      .default_size([525.0, 320.0])
+      .show_new_folder(false)
+      .show_rename(false)
+      .resizable(false);
+    folder_dlg.open();
+    self.folder_dlg = Some(folder_dlg);
+  }
+
+  fn select_airport_folder(&mut self) {
+    let path = self.asset_path.clone().or_else(|| self.charts_path.clone());
+    let mut folder_dlg = egui_file::FileDialog::select_folder(path)
+      .title("Select Extracted Airport Data Folder")
+      .anchor(emath::Align2::CENTER_CENTER, [0.0, 0.0])
+      .default_size([525.0, 320.0])
+      .show_new_folder(false)
+      .show_rename(false)
+      .resizable(false);
+    folder_dlg.open();
+    self.airport_folder_dlg = Some(folder_dlg);
+  }
+
+  /// Ask where to save an airport summary.
+  /// - `id`: airport ID, used for the default file name
+  /// - `summary`: summary text
+  fn export_airport_summary(&mut self, id: &str, summary: String) {
+    let path = self.asset_path.clone().or_else(|| self.charts_path.clone());
+    let mut summary_dlg = egui_file::FileDialog::save_file(path)
+      .title("Export Airport Summary")
+      .anchor(emath::Align2::CENTER_CENTER, [0.0, 0.0])
+      .default_size([525.0, 320.0])
+      .default_filename(format!("{id}.md"))
+      .show_rename(false)
+      .resizable(false);
+    summary_dlg.open();
+    self.summary_dlg = Some((summary_dlg, summary));
+  }
+
   fn open_chart_data(&mut self, ctx: &egui::Context, path: &path::Path, file: &path::Path) {
-    self.chart = Chart::None;
+    let chart_path = util::chart_path(path, file);
+
+    // Switch to the chart instead of opening it again if it's already open.
+    if self
+      .get_chart()
+      .is_some_and(|chart| chart.path == chart_path)
+    {
+      return;
+    }
 
-    // Concatenate the VSI prefix and the file path.
-    let path = ["/vsizip/", path.to_str().unwrap()].concat();
-    let path = path::Path::new(path.as_str()).join(file);
+    if let Some(index) = self
+      .charts
+      .iter()
+      .position(|chart| chart.path == chart_path)
+    {
+      let active = self.get_chart().is_some();
+      self.activate_chart(inactive_tab(index, self.active_tab, active));
+      return;
+    }
+
+    // Keep the current chart open in its tab.
+    self.stash_chart();
+
+    // Airport data may be in the same folder.
+    let folder = path.parent().map(|folder| folder.to_owned());
+
+    // Open the chart on a separate thread so that it can be canceled.
+    let name = util::stem_string(file).unwrap();
+    let text = format!("Opening {name}…");
+    self.chart = Chart::Open(Box::new(ChartOpen {
+      opener: chart::RasterOpener::new(&chart_path, ctx),
+      progress_dlg: progress_dlg::ProgressDlg::open("Open Chart".into(), text),
+      name,
+      path: chart_path,
+      folder,
+      choices: None,
+    }));
+  }
+
+  /// Let the user choose which chart to open from a zip file with more than one.
+  /// - `path`: zip file path
+  /// - `files`: chart files within the zip file
+  fn choose_chart(&mut self, path: path::PathBuf, files: Vec<path::PathBuf>) {
+    self.stash_chart();
+    self.chart = Chart::Load(path, files);
+
+    // Remove the chart spatial reference from the airport reader.
+    if let Some(airport_reader) = &self.airport_reader {
+      airport_reader.clear_spatial_ref();
+    }
+  }
+
+  /// Finish opening a chart.
+  /// - `open`: chart open info
+  /// - `result`: the chart reader or an error
+  fn chart_opened(
+    &mut self,
+    ctx: &egui::Context,
+    open: ChartOpen,
+    result: Result<chart::RasterReader, util::Error>,
+  ) {
+    let ChartOpen {
+      name,
+      path,
+      folder,
+      choices,
+      ..
+    } = open;
 
-    match chart::RasterReader::new(path, ctx) {
+    match result {
       Ok(chart_reader) => {
+        if self.custom_colors() {
+          self.send_colors(&chart_reader);
+        }
+
+        // Hide the side panel before the chart is set so that the new chart starts at its origin.
+        if self.auto_hide_panel {
+          self.toggle_side_panel(false);
+        }
+
         let proj4 = chart_reader.transform().get_proj4();
         let bounds = chart_reader.transform().bounds().clone();
+        self.active_tab = self.charts.len();
+        self.airport_infos = AirportInfos::None;
+        self.markers = AirportMarkers::default();
+        self.nearby = NearbyAirports::default();
+        self.mag_var = MagVarLookup::default();
+        self.highlight = None;
+        self.measure = None;
+
+        // Set up the view once the display size is known.
+        self.initial_view = true;
+
+        // If this is a heliport chart then include non-public heliports in searches.
+        let series = util::ChartSeries::from_stem(&name);
+        self.include_nph = series == Some(util::ChartSeries::Helicopter);
+
+        // Prefer the title from the chart's metadata.
+        let title = chart_reader
+          .title()
+          .map_or_else(|| name.clone(), str::to_owned);
+
+        // Use elevation data from the chart's folder if there is any.
+        let dem_path = folder.as_deref().and_then(util::find_dem);
+
         self.chart = Chart::Ready(Box::new(ChartInfo {
-          name: util::stem_string(file).unwrap(),
+          name,
+          title,
+          path,
+          dem_path,
           reader: rc::Rc::new(chart_reader),
           texture: None,
           disp_rect: util::Rect::default(),
@@ -134,27 +559,58 @@ impl App {
           zoom: 1.0,
         }));
 
+        // The image cache is shared by all the chart readers.
+        self.update_read_options();
+
         if let Some(nasr_reader) = &mut self.airport_reader {
           nasr_reader.set_spatial_ref(proj4, bounds);
         }
 
-        // If this is a heliport chart then include non-public heliports in searches.
-        self.include_nph = util::stem_str(file).unwrap().ends_with(" HEL");
+        // Find the coverage of the other charts in the chart's folder.
+        if let Some(folder) = &folder {
+          self.adjacent.scan(folder, ctx);
+        }
+
+        // Load airport data from the chart's folder if it's not already loaded.
+        if self.auto_load_aero && self.airport_reader.is_none() {
+          if let Some((path, csv)) = folder.as_deref().and_then(util::find_nasr_zip) {
+            self.open_airport_data(ctx, &path, &csv);
+          }
+        }
       }
       Err(err) => {
-        self.error_dlg = Some(error_dlg::ErrorDlg::open(err));
+        // Offer the other charts in the zip file if this one was chosen from them.
+        let others = choices.map(|(zip, mut files)| {
+          files.retain(|file| util::chart_path(&zip, file) != path);
+          (zip, files)
+        });
+
+        match others.filter(|(_, files)| !files.is_empty()) {
+          Some((zip, files)) => {
+            let title = "Unable to Open Chart".into();
+            let text = format!("{err}\n\nChoose a different chart from the zip file?");
+            let retry_dlg = confirm_dlg::ConfirmDlg::open(title, text);
+            self.retry_dlg = Some((retry_dlg, zip, files));
+          }
+          None => self.error_dlg = Some(error_dlg::ErrorDlg::open(err)),
+        }
+        self.restore_chart();
       }
     }
   }
 
   fn open_airport_data(&mut self, ctx: &egui::Context, path: &path::Path, zip: &path::Path) {
-    // Concatenate the VSI prefix and the file path.
-    let path = ["/vsizip//vsizip/", path.to_str().unwrap()].concat();
-    let path = path::Path::new(path.as_str());
-    let path = path.join(zip).join("APT_BASE.csv");
+    // A lookup pending on the old reader will never be answered.
+    self.mag_var = MagVarLookup::default();
 
-    self.airport_reader = match nasr::AirportReader::new(path, ctx) {
+    let csv_path = airport_csv_path(path, zip);
+    self.airport_reader = match nasr::AirportReader::new(csv_path, ctx) {
       Ok(nasr_reader) => {
+        // Remember it for the next session.
+        if let Some(path) = path.to_str() {
+          self.config.set_nasr_path(path.into());
+        }
+
         if let Some(chart_reader) = self.get_chart_reader() {
           let proj4 = chart_reader.transform().get_proj4();
           let bounds = chart_reader.transform().bounds().clone();
@@ -169,11 +625,381 @@ impl App {
     }
   }
 
+  fn close_chart(&mut self) {
+    // Dropping the chart reader closes the connection to its thread, which then exits and releases
+    // the raster data. Dropping the texture handle frees the image.
+    self.chart = Chart::None;
+    self.airport_infos = AirportInfos::None;
+    self.markers = AirportMarkers::default();
+    self.nearby = NearbyAirports::default();
+    self.mag_var = MagVarLookup::default();
+    self.highlight = None;
+    self.last_click = None;
+    self.measure = None;
+    self.include_nph = false;
+
+    // Remove the chart spatial reference from the airport reader.
+    if let Some(airport_reader) = &self.airport_reader {
+      airport_reader.clear_spatial_ref();
+    }
+
+    // Switch to one of the other open charts.
+    self.restore_chart();
+
+    // Close the split view if that was the last chart.
+    if matches!(self.chart, Chart::None) {
+      self.split = None;
+    }
+
+    // The remaining readers share the closed chart's part of the image cache.
+    self.update_read_options();
+  }
+
+  /// Move the active chart into the inactive charts, keeping its tab position.
+  fn stash_chart(&mut self) {
+    if let Chart::Ready(chart) = mem::replace(&mut self.chart, Chart::None) {
+      let index = self.active_tab.min(self.charts.len());
+      self.charts.insert(index, chart);
+    }
+  }
+
+  /// Activate the chart in the active tab position (or the last tab) if there's no active chart.
+  fn restore_chart(&mut self) {
+    if matches!(self.chart, Chart::None) && !self.charts.is_empty() {
+      self.activate_chart(self.active_tab.min(self.charts.len() - 1));
+    }
+  }
+
+  /// Switch to the chart in a tab.
+  /// - `tab`: tab index
+  fn activate_chart(&mut self, tab: usize) {
+    self.stash_chart();
+
+    let mut chart = self.charts.remove(tab);
+    self.active_tab = tab;
+
+    // Restore the scroll position.
+    chart.scroll = Some(chart.disp_rect.pos.into());
+
+    // Point the airport reader at this chart.
+    if let Some(nasr_reader) = &self.airport_reader {
+      let proj4 = chart.reader.transform().get_proj4();
+      let bounds = chart.reader.transform().bounds().clone();
+      nasr_reader.set_spatial_ref(proj4, bounds);
+    }
+
+    self.include_nph =
+      util::ChartSeries::from_stem(&chart.name) == Some(util::ChartSeries::Helicopter);
+    self.airport_infos = AirportInfos::None;
+    self.markers = AirportMarkers::default();
+    self.nearby = NearbyAirports::default();
+    self.mag_var = MagVarLookup::default();
+    self.highlight = None;
+    self.measure = None;
+    self.chart = Chart::Ready(chart);
+  }
+
+  /// Close the chart in an inactive tab.
+  /// - `tab`: tab index
+  fn close_tab(&mut self, tab: usize) {
+    if tab < self.active_tab {
+      self.charts.remove(tab);
+      self.active_tab -= 1;
+    } else if tab > self.active_tab {
+      self.charts.remove(tab - 1);
+    }
+
+    // The remaining readers share the closed chart's part of the image cache.
+    self.update_read_options();
+  }
+
+  /// Get the chart in a tab.
+  /// - `tab`: tab index
+  fn tab_chart(&self, tab: usize) -> Option<&ChartInfo> {
+    match tab.cmp(&self.active_tab) {
+      cmp::Ordering::Less => self.charts.get(tab).map(|chart| chart.as_ref()),
+      cmp::Ordering::Equal => self.get_chart(),
+      cmp::Ordering::Greater => self.charts.get(tab - 1).map(|chart| chart.as_ref()),
+    }
+  }
+
+  /// Open a chart in the split view. The split view has its own reader so that it can be panned and
+  /// zoomed independently of the chart's tab.
+  /// - `tab`: tab index of the chart to show
+  fn open_split(&mut self, ctx: &egui::Context, tab: usize) {
+    let Some(chart) = self.tab_chart(tab) else {
+      return;
+    };
+
+    let (name, title, path) = (chart.name.clone(), chart.title.clone(), chart.path.clone());
+    let dem_path = chart.dem_path.clone();
+    match chart::RasterReader::new(&path, ctx) {
+      Ok(chart_reader) => {
+        if self.custom_colors() {
+          self.send_colors(&chart_reader);
+        }
+
+        self.split = Some(Box::new(ChartInfo {
+          name,
+          title,
+          path,
+          dem_path,
+          reader: rc::Rc::new(chart_reader),
+          texture: None,
+          disp_rect: util::Rect::default(),
+          scroll: Some(emath::pos2(0.0, 0.0)),
+          zoom: 1.0,
+        }));
+
+        // The image cache is shared by all the chart readers.
+        self.update_read_options();
+
+        // Link the new chart to the main chart's view.
+        self.link_view = None;
+      }
+      Err(err) => {
+        self.error_dlg = Some(error_dlg::ErrorDlg::open(err));
+      }
+    }
+  }
+
+  /// Open the split view, initially showing the next tab's chart (or the active chart if there
+  /// are no other tabs).
+  fn toggle_split(&mut self, ctx: &egui::Context) {
+    if self.split.take().is_some() {
+      // The remaining readers share the split view's part of the image cache.
+      self.update_read_options();
+    } else {
+      let tab = if self.charts.is_empty() {
+        self.active_tab
+      } else if self.active_tab < self.charts.len() {
+        self.active_tab + 1
+      } else {
+        0
+      };
+      self.open_split(ctx, tab);
+    }
+  }
+
+  /// Show the split view chart in a panel to the right of the active chart.
+  /// - `events`: input events for this frame
+  fn show_split_view(&mut self, ctx: &egui::Context, events: &InputEvents) {
+    let Some(mut split) = self.split.take() else {
+      return;
+    };
+
+    let tab_count = self.charts.len() + 1;
+    let names: Vec<(usize, String)> = (0..tab_count)
+      .filter_map(|tab| self.tab_chart(tab).map(|chart| (tab, chart.title.clone())))
+      .collect();
+
+    let main_zoom = self.get_chart_zoom();
+    let mut sync = self.split_sync;
+    let mut link = self.split_link;
+    let mut select = None;
+    let mut close = false;
+    let mut stale = false;
+    let fill = ctx.style().visuals.panel_fill;
+    egui::SidePanel::right(format!("{}_split_panel", util::APP_NAME))
+      .frame(egui::Frame {
+        inner_margin: egui::Margin {
+          top: 4.0,
+          ..Default::default()
+        },
+        fill,
+        ..Default::default()
+      })
+      .default_width(ctx.available_rect().width() * 0.5)
+      .show(ctx, |ui| {
+        ui.set_enabled(self.ui_enabled);
+        ui.horizontal(|ui| {
+          ui.add_space(8.0);
+          egui::ComboBox::from_id_source("split_chart")
+            .selected_text(&split.title)
+            .show_ui(ui, |ui| {
+              for (tab, name) in &names {
+                if ui.selectable_label(*name == split.title, name).clicked() {
+                  select = Some(*tab);
+                }
+              }
+            });
+
+          ui.add_enabled(!link, egui::Checkbox::new(&mut sync, "Sync Zoom"))
+            .on_hover_text("Keep the zoom the same as the main chart");
+
+          ui.checkbox(&mut link, "Link")
+            .on_hover_text("Keep both charts centered on the same location and scale");
+
+          ui.with_layout(egui::Layout::right_to_left(emath::Align::Center), |ui| {
+            ui.add_space(8.0);
+            if ui.button("✖").clicked() {
+              close = true;
+            }
+          });
+        });
+
+        let scroll = split.scroll.take();
+        let response = chart_scroll_area(ui, "split_scroll", &split, scroll);
+
+        // Set a new display rectangle.
+        let pos = response.state.offset;
+        let display_rect = util::Rect {
+          pos: pos.into(),
+          size: response.inner_rect.size().into(),
+        };
+        split.disp_rect = display_rect;
+
+        // Make sure the image position lands on an even pixel.
+        if self.should_snap(ctx, response.state.velocity()) {
+          let floored = pos.floor();
+          if floored != pos {
+            split.scroll = Some(floored.to_pos2());
+          }
+        }
+
+        let min_zoom = split.get_min_zoom();
+        let mut zoom = split.zoom.max(min_zoom);
+        if let Some(main_zoom) = main_zoom.filter(|_| sync && !link) {
+          // Follow the main chart's zoom.
+          zoom = main_zoom.clamp(min_zoom, 1.0);
+        } else if let Some(zoom_pos) = events.zoom_pos.filter(|_| !link) {
+          if response.inner_rect.contains(zoom_pos) {
+            let new_zoom = (zoom * events.zoom_mod).clamp(min_zoom, 1.0);
+            if new_zoom != zoom {
+              // Attempt to keep the point under the mouse cursor the same.
+              let zoom_pos = zoom_pos - response.inner_rect.min;
+              let pos = (pos + zoom_pos) * new_zoom / zoom - zoom_pos;
+              split.scroll = Some(pos.to_pos2().round());
+              split.zoom = new_zoom;
+              zoom = new_zoom;
+            }
+          }
+        }
+
+        if zoom != split.zoom {
+          split.scroll = Some(split.get_zoom_pos(zoom).round());
+          split.zoom = zoom;
+          ctx.request_repaint();
+        }
+
+        // Request a new image if needed.
+        if display_rect.size.w > 0 && display_rect.size.h > 0 {
+          let part = self.image_part(display_rect, zoom);
+          stale = split
+            .texture
+            .as_ref()
+            .map_or(true, |(current, _)| *current != part);
+          if stale && split.scroll.is_none() {
+            split.reader.read_image(part);
+          }
+        }
+
+        if stale && split.reader.is_loading() {
+          draw_loading(ui, response.inner_rect, self.low_power);
+        }
+      });
+
+    if link && !self.split_link {
+      // Link to the main chart's current view.
+      self.link_view = None;
+    }
+
+    self.split_sync = sync;
+    self.split_link = link;
+    if close {
+      return;
+    }
+
+    self.split = Some(split);
+    if let Some(tab) = select {
+      self.open_split(ctx, tab);
+    }
+  }
+
+  /// Center the split view chart on the same NAD83 coordinate as the main chart when the main
+  /// chart's view changes. The zoom is adjusted so that both show the same ground distance per
+  /// screen pixel, which accounts for charts with different projections and scales.
+  fn link_split(&mut self, ctx: &egui::Context) {
+    if !self.split_link {
+      return;
+    }
+
+    let Some(chart) = self.get_chart() else {
+      return;
+    };
+
+    let view = (chart.disp_rect, chart.zoom);
+    if self.link_view == Some(view) {
+      return;
+    }
+
+    let Some(split) = self.split.as_deref() else {
+      return;
+    };
+
+    if split.disp_rect.size.w == 0 || split.disp_rect.size.h == 0 {
+      // Wait for the split view to be laid out.
+      return;
+    }
+
+    let linked = split.get_link_view(chart);
+    self.link_view = Some(view);
+    if let (Some((scroll, zoom)), Some(split)) = (linked, &mut self.split) {
+      split.scroll = Some(scroll);
+      split.zoom = zoom;
+      ctx.request_repaint();
+    }
+  }
+
+  /// Check if the scroll position should be snapped to a whole pixel.
+  /// - `velocity`: scroll velocity
+  fn should_snap(&self, ctx: &egui::Context, velocity: emath::Vec2) -> bool {
+    let dragging = ctx.input(|state| state.pointer.any_down());
+    self.scroll_snap.should_snap(velocity.length(), dragging)
+  }
+
+  /// Get the image part needed to display a rectangle at a zoom level. If the image limit is set
+  /// and the rectangle would exceed it then the part is read at a lower zoom and stretched for
+  /// display. This keeps memory use in check at the cost of a softer image.
+  fn image_part(&self, rect: util::Rect, zoom: f32) -> chart::ImagePart {
+    let limit = self.image_limit as f32 * 1_000_000.0;
+    let pixels = rect.size.w as f32 * rect.size.h as f32;
+    if limit > 0.0 && pixels > limit {
+      let scale = (limit / pixels).sqrt();
+      let rect = rect.scaled(scale);
+      let (dark, gray) = (self.night_mode, self.grayscale);
+      return chart::ImagePart::new(rect, zoom * scale, dark, gray, self.sharpen);
+    }
+    chart::ImagePart::new(rect, zoom, self.night_mode, self.grayscale, self.sharpen)
+  }
+
   fn request_image(&mut self, rect: util::Rect, zoom: f32) {
     if let Some(reader) = self.get_chart_reader() {
-      let dark = self.night_mode;
-      let part = chart::ImagePart::new(rect, zoom, dark);
-      reader.read_image(part);
+      reader.read_image(self.image_part(rect, zoom));
+    }
+  }
+
+  /// Open the elevation data found with the active chart, or close it if there isn't any.
+  fn update_dem(&mut self, ctx: &egui::Context) {
+    let dem_path = self.get_chart().and_then(|chart| chart.dem_path.clone());
+    let current = self.dem_reader.as_ref().map(|(path, _)| path);
+    if current == dem_path.as_ref() {
+      return;
+    }
+
+    self.dem_reader = None;
+    if let Some(path) = dem_path {
+      match chart::DemReader::new(&path, ctx) {
+        Ok(dem_reader) => self.dem_reader = Some((path, dem_reader)),
+        Err(err) => {
+          println!("{err}");
+
+          // Don't try to open it again.
+          if let Chart::Ready(chart) = &mut self.chart {
+            chart.dem_path = None;
+          }
+        }
+      }
     }
   }
 
@@ -259,7 +1085,9 @@ impl App {
 
   fn set_chart_scroll(&mut self, pos: emath::Pos2) {
     if let Chart::Ready(chart) = &mut self.chart {
-      chart.scroll = Some(pos.floor());
+      // Keep the sub-pixel position if snapping is off.
+      let snap = self.scroll_snap != util::ScrollSnap::Off;
+      chart.scroll = Some(if snap { pos.floor() } else { pos });
     }
   }
 
@@ -271,19 +1099,369 @@ impl App {
     false
   }
 
-  /// Pan the map to a NAD83 coordinate.
-  fn goto_coord(&mut self, coord: util::Coord) {
-    if let Some(chart) = self.get_chart() {
-      if let Ok(px) = chart.reader.transform().nad83_to_px(coord) {
-        let chart_size = chart.reader.transform().px_size();
-        if chart_size.contains(px) {
-          let x = px.x as f32 - 0.5 * chart.disp_rect.size.w as f32;
-          let y = px.y as f32 - 0.5 * chart.disp_rect.size.h as f32;
-          self.set_chart_zoom(1.0);
+  /// Find the charts that continue past the edges of the active chart when the view is at an edge.
+  /// Returns where to place each button along with its text and the chart's zip and file paths.
+  /// - `pos`: scroll offset
+  /// - `view`: size of the chart area
+  fn adjacent_charts(
+    &self,
+    pos: emath::Vec2,
+    view: emath::Vec2,
+  ) -> Vec<(emath::Align2, String, path::PathBuf, path::PathBuf)> {
+    let mut adjacent = Vec::new();
+    let Some(chart) = self.get_chart() else {
+      return adjacent;
+    };
+
+    let transform = chart.reader.transform();
+    let px_size: emath::Vec2 = transform.px_size().into();
+    let size = px_size * chart.zoom;
+    let center = (pos + view * 0.5) / chart.zoom;
+    let edges = [
+      (
+        emath::Align2::LEFT_CENTER,
+        pos.x <= ADJACENT_EDGE_DIST,
+        emath::pos2(-ADJACENT_PROBE_DIST, center.y),
+        "◀",
+      ),
+      (
+        emath::Align2::RIGHT_CENTER,
+        pos.x + view.x >= size.x - ADJACENT_EDGE_DIST,
+        emath::pos2(px_size.x + ADJACENT_PROBE_DIST, center.y),
+        "▶",
+      ),
+      (
+        emath::Align2::CENTER_TOP,
+        pos.y <= ADJACENT_EDGE_DIST,
+        emath::pos2(center.x, -ADJACENT_PROBE_DIST),
+        "▲",
+      ),
+      (
+        emath::Align2::CENTER_BOTTOM,
+        pos.y + view.y >= size.y - ADJACENT_EDGE_DIST,
+        emath::pos2(center.x, px_size.y + ADJACENT_PROBE_DIST),
+        "▼",
+      ),
+    ];
+
+    for (align, at_edge, px, arrow) in edges {
+      if !at_edge {
+        continue;
+      }
+
+      let Ok(coord) = transform.px_to_nad83(px.into()) else {
+        continue;
+      };
+
+      if let Some((zip, file, _)) = self.adjacent.find(coord, &chart.path) {
+        let name = util::stem_str(file).unwrap();
+        let text = format!("{arrow} {name}");
+        adjacent.push((align, text, zip.clone(), file.clone()));
+      }
+    }
+    adjacent
+  }
+
+  /// Handle the Escape key. Each press clears only the first of these that applies:
+  /// 1. Stop measuring
+  /// 2. Remove the bearing line
+  /// 3. Remove the found airport highlight
+  /// 4. Close the airport menu
+  /// 5. Remove the last click marker
+  /// 6. Close the side panel
+  /// 7. Close the chart, if the escape action is [`util::EscapeAction::Chart`]
+  fn escape(&mut self) {
+    if self.measure.take().is_some()
+      || self.bearing_line.take().is_some()
+      || self.highlight.take().is_some()
+      || self.reset_airport_menu()
+      || self.last_click.take().is_some()
+    {
+      return;
+    }
+
+    if self.side_panel {
+      self.toggle_side_panel(false);
+    } else if self.escape_action == util::EscapeAction::Chart {
+      self.close_chart();
+    }
+  }
+
+  /// Center the chart on the most recently clicked coordinate.
+  fn goto_last_click(&mut self) {
+    if let Some(coord) = self.last_click {
+      if !self.goto_coord(coord) {
+        // The coordinate isn't on this chart.
+        self.last_click = None;
+      }
+    }
+  }
+
+  /// Scroll the chart so that a NAD83 coordinate is at the configured goto position. Returns false
+  /// if the coordinate is not on the chart.
+  fn goto_coord(&mut self, coord: util::Coord) -> bool {
+    if let Some(chart) = self.get_chart() {
+      if let Ok(px) = chart.reader.transform().nad83_to_px(coord) {
+        let chart_size = chart.reader.transform().px_size();
+        if chart_size.contains(px) {
+          let (w, h) = (chart.disp_rect.size.w as f32, chart.disp_rect.size.h as f32);
+          let fy = self.goto_anchor.fraction();
+          let (x, y) = if self.scroll_snap == util::ScrollSnap::Off {
+            (px.x as f32 - 0.5 * w, px.y as f32 - fy * h)
+          } else {
+            // Put the center of the target's pixel on the center of the anchor display pixel so
+            // that it's drawn sharply.
+            let x = px.x.floor() as f32 - (0.5 * w).floor();
+            let y = px.y.floor() as f32 - (fy * h).floor();
+            (x, y)
+          };
+          self.set_chart_zoom(1.0);
           self.set_chart_scroll(emath::pos2(x, y));
+          return true;
         }
       }
     }
+    false
+  }
+
+  /// Request the airports for the markers if the view has moved outside of the previous request.
+  fn request_markers(&mut self) {
+    if self.marker_size == util::MarkerSize::Off {
+      return;
+    }
+
+    let (Some(chart), Some(nasr_reader)) = (self.get_chart(), &self.airport_reader) else {
+      return;
+    };
+
+    if !nasr_reader.airport_spatial_idx() {
+      return;
+    }
+
+    // Get the view bounds in chart coordinates.
+    let pos: emath::Pos2 = chart.disp_rect.pos.into();
+    let size: emath::Vec2 = chart.disp_rect.size.into();
+    let transform = chart.reader.transform();
+    let a = transform.px_to_chart((pos.to_vec2() / chart.zoom).into());
+    let b = transform.px_to_chart(((pos.to_vec2() + size) / chart.zoom).into());
+    let view = util::Bounds {
+      min: util::Coord {
+        x: a.x.min(b.x),
+        y: a.y.min(b.y),
+      },
+      max: util::Coord {
+        x: a.x.max(b.x),
+        y: a.y.max(b.y),
+      },
+    };
+
+    if let Some(request) = &self.markers.request {
+      if request.contains(view.min) && request.contains(view.max) {
+        return;
+      }
+    }
+
+    // Add a margin so that small pans don't need a new request.
+    let dx = (view.max.x - view.min.x) * 0.5;
+    let dy = (view.max.y - view.min.y) * 0.5;
+    let request = util::Bounds {
+      min: util::Coord {
+        x: view.min.x - dx,
+        y: view.min.y - dy,
+      },
+      max: util::Coord {
+        x: view.max.x + dx,
+        y: view.max.y + dy,
+      },
+    };
+
+    nasr_reader.in_bounds(request.clone(), self.include_nph);
+    self.markers.request = Some(request);
+  }
+
+  /// Request the airports near the center of the view if it has moved far enough since the last
+  /// request.
+  fn request_nearby(&mut self, ctx: &egui::Context) {
+    let (Some(chart), Some(nasr_reader)) = (self.get_chart(), &self.airport_reader) else {
+      return;
+    };
+
+    if !nasr_reader.airport_spatial_idx() {
+      return;
+    }
+
+    // Wait for the view to settle.
+    if self.nearby.momentum || ctx.input(|state| state.pointer.any_down()) {
+      return;
+    }
+
+    // Get the view center and the search distance in chart coordinates.
+    let pos: emath::Pos2 = chart.disp_rect.pos.into();
+    let size: emath::Vec2 = chart.disp_rect.size.into();
+    let transform = chart.reader.transform();
+    let a = transform.px_to_chart((pos.to_vec2() / chart.zoom).into());
+    let b = transform.px_to_chart(((pos.to_vec2() + size) / chart.zoom).into());
+    let center = util::Coord {
+      x: (a.x + b.x) * 0.5,
+      y: (a.y + b.y) * 0.5,
+    };
+    let dist = ((b.x - a.x).hypot(b.y - a.y) * 0.5).min(NEARBY_MAX_DIST);
+
+    if let Some((request, request_dist)) = self.nearby.request {
+      // Ignore small pans and zooms.
+      let moved = (center.x - request.x).hypot(center.y - request.y);
+      if moved < request_dist * 0.1 && (dist - request_dist).abs() < request_dist * 0.25 {
+        return;
+      }
+    }
+
+    // Don't search for every step of a zoom or keyboard pan.
+    let wait = self.nearby.settle_time(center, dist);
+    if !wait.is_zero() {
+      ctx.request_repaint_after(wait);
+      return;
+    }
+
+    nasr_reader.nearby_live(center, dist, self.include_nph);
+    self.nearby.request = Some((center, dist));
+    self.nearby.pending.push(center);
+  }
+
+  /// Set up the view of a newly opened chart. It's centered on the home location if that's on the
+  /// chart. Otherwise it's zoomed out to fit if that option is set, or zoomed to suit the chart
+  /// series.
+  /// - `min_zoom`: minimum zoom for the display size
+  fn set_initial_view(&mut self, min_zoom: f32) {
+    if let Some(home) = self.home {
+      if self.goto_coord(home) {
+        return;
+      }
+    }
+
+    let Some(chart) = self.get_chart() else {
+      return;
+    };
+
+    if self.open_fit && min_zoom < 1.0 {
+      // Zoom out and center the chart.
+      let chart_size: emath::Vec2 = chart.reader.transform().px_size().into();
+      let disp_size: emath::Vec2 = chart.disp_rect.size.into();
+      let offset = ((chart_size * min_zoom - disp_size) * 0.5).max(emath::Vec2::ZERO);
+      self.set_chart_zoom(min_zoom);
+      self.set_chart_scroll(offset.to_pos2().round());
+    } else {
+      // Stay at the chart's origin.
+      let series = util::ChartSeries::from_stem(&chart.name);
+      let zoom = series.map_or(1.0, |series| series.open_zoom().max(min_zoom));
+      self.set_chart_zoom(zoom);
+      self.set_chart_scroll(emath::Pos2::ZERO);
+    }
+  }
+
+  /// Use the center of the view as the location that newly opened charts are centered on.
+  fn set_home_to_view(&mut self) {
+    let Some(coord) = self.get_chart().and_then(|chart| chart.get_center_coord()) else {
+      return;
+    };
+
+    self.home = Some(coord);
+    self.config.set_home(Some(coord));
+    self.toast = Some(toast::Toast::new("Home set to the view center".into()));
+  }
+
+  /// Copy the NAD83 bounds of the view to the clipboard.
+  fn copy_view_bounds(&mut self, ctx: &egui::Context) {
+    let Some(bounds) = self.get_chart().and_then(|chart| chart.get_view_bounds()) else {
+      return;
+    };
+
+    let min = self.coord_format.format(bounds.min);
+    let max = self.coord_format.format(bounds.max);
+    if let (Some(min), Some(max)) = (min, max) {
+      ctx.output_mut(|state| state.copied_text = format!("{min}\n{max}"));
+      self.toast = Some(toast::Toast::new("Copied view bounds".into()));
+    }
+  }
+
+  /// Copy the chart's spatial reference to the clipboard.
+  /// - `wkt`: copy well known text instead of proj4
+  fn copy_spatial_ref(&mut self, ctx: &egui::Context, wkt: bool) {
+    let Some(reader) = self.get_chart_reader() else {
+      return;
+    };
+
+    let (text, name) = if wkt {
+      (reader.transform().get_wkt(), "WKT")
+    } else {
+      (reader.transform().get_proj4(), "proj4")
+    };
+    ctx.output_mut(|state| state.copied_text = text);
+    self.toast = Some(toast::Toast::new(format!("Copied {name} projection")));
+  }
+
+  /// Get the magnetic variation at a location. Returns `None` while the variation is being looked up,
+  /// if it's not available or if magnetic bearings are turned off.
+  /// - `origin`: NAD83 coordinate
+  fn magnetic_variation(&mut self, origin: util::Coord) -> Option<f64> {
+    if !self.show_magnetic {
+      return None;
+    }
+
+    if self.mag_var.origin == Some(origin) {
+      return self.mag_var.value;
+    }
+
+    let (Some(chart), Some(nasr_reader)) = (self.get_chart(), &self.airport_reader) else {
+      return None;
+    };
+
+    if !nasr_reader.airport_spatial_idx() {
+      return None;
+    }
+
+    let lcc = chart.reader.transform().nad83_to_chart(origin).ok()?;
+    nasr_reader.mag_var(lcc, MAG_VAR_SEARCH_DIST);
+    self.mag_var = MagVarLookup {
+      origin: Some(origin),
+      pending: Some(lcc),
+      value: None,
+    };
+    None
+  }
+
+  /// Text to show when the nearby airports list is empty.
+  fn nearby_hint(&self) -> &'static str {
+    let Some(nasr_reader) = &self.airport_reader else {
+      return "Load NASR airport data to enable nearby search";
+    };
+
+    if self.get_chart().is_none() {
+      return "Load a chart to enable nearby search";
+    }
+
+    if !nasr_reader.airport_spatial_idx() {
+      return "Indexing airports…";
+    }
+
+    "No airports near the view center"
+  }
+
+  /// Check if an airport passes the closed, private and towered filters.
+  fn airport_visible(&self, info: &nasr::AirportInfo) -> bool {
+    (self.show_closed || !info.is_closed())
+      && (self.show_private || !info.is_private())
+      && (!self.towered_only || info.towered)
+  }
+
+  /// Go to an airport found by a search or show its information if it's not on the chart.
+  fn show_airport(&mut self, info: nasr::AirportInfo) {
+    if self.goto_coord(info.coord) {
+      if self.highlight_found {
+        self.highlight = Some((info.coord, info.id));
+      }
+    } else {
+      self.airport_dlg = Some(airport_dlg::AirportDlg::open(info, self.coord_format));
+    }
   }
 
   fn toggle_side_panel(&mut self, visible: bool) {
@@ -328,11 +1506,11 @@ impl App {
     self.night_mode = night_mode;
 
     // Set the theme.
-    ctx.set_visuals(if night_mode {
-      dark_theme()
-    } else {
-      self.default_theme.clone()
-    });
+    ctx.set_visuals(theme_visuals(
+      self.ui_theme,
+      night_mode,
+      &self.default_theme,
+    ));
 
     // Store the night mode flag.
     self.config.set_night_mode(night_mode);
@@ -343,352 +1521,2056 @@ impl App {
     }
   }
 
-  fn process_input(&mut self, ctx: &egui::Context) -> InputEvents {
-    let mut events = InputEvents::new(ctx);
-    events.secondary_click = self.long_press.check();
+  fn set_ui_theme(&mut self, ctx: &egui::Context, theme: util::UiTheme) {
+    if self.ui_theme == theme {
+      return;
+    }
 
-    ctx.input(|state| {
-      // Get the window size info.
-      self.win_info = util::WinInfo::new(state.viewport());
+    self.ui_theme = theme;
+    self.config.set_ui_theme(theme);
+    ctx.set_visuals(theme_visuals(theme, self.night_mode, &self.default_theme));
+  }
 
-      // Process events.
-      for event in &state.events {
-        match event {
-          egui::Event::Key {
-            key,
-            physical_key: _,
-            pressed,
-            repeat,
-            modifiers,
-          } if *pressed && !*repeat && self.ui_enabled => {
-            match key {
-              egui::Key::Escape => {
-                // Remove the airport infos.
-                if !self.reset_airport_menu() {
-                  // No airport menu. Close the side panel.
-                  self.toggle_side_panel(false);
-                }
-              }
-              egui::Key::F if modifiers.command_only() => {
-                if let Some(nasr_reader) = &self.airport_reader {
-                  if nasr_reader.airport_basic_idx() && matches!(self.chart, Chart::Ready(_)) {
-                    self.find_dlg = Some(find_dlg::FindDlg::open());
-                    self.reset_airport_menu();
-                  }
-                }
-              }
-              egui::Key::Q if modifiers.command_only() => {
-                events.quit = true;
-                self.reset_airport_menu();
-              }
-              _ => (),
-            }
-          }
-          egui::Event::Touch {
-            device_id: _,
-            id,
-            phase,
-            pos,
-            force: _,
-          } => self.long_press.initiate(*id, *phase, *pos),
-          egui::Event::PointerButton {
-            pos,
-            button,
-            pressed,
-            modifiers,
-          } if *button == egui::PointerButton::Secondary && !pressed && modifiers.is_none() => {
-            events.secondary_click = Some(*pos);
-          }
-          egui::Event::Zoom(val) => {
-            events.zoom_pos = state.pointer.hover_pos();
-            events.zoom_mod *= val;
-          }
-          _ => (),
-        }
+  fn set_grayscale(&mut self, gray: bool) {
+    if self.grayscale == gray {
+      return;
+    }
+
+    self.grayscale = gray;
+    self.config.set_grayscale(gray);
+
+    // Request a new image.
+    if let Some((part, _)) = self.get_chart_texture() {
+      self.request_image(part.rect, part.zoom.into());
+    }
+  }
+
+  fn set_sharpen(&mut self, sharpen: f32) {
+    if self.sharpen == sharpen {
+      return;
+    }
+
+    self.sharpen = sharpen;
+
+    // Request a new image.
+    if let Some((part, _)) = self.get_chart_texture() {
+      self.request_image(part.rect, part.zoom.into());
+    }
+  }
+
+  fn set_color_map(&mut self, color_map: util::ColorMap) {
+    if self.color_map == color_map {
+      return;
+    }
+
+    self.color_map = color_map;
+    self.config.set_color_map(self.color_map.clone());
+    self.update_colors();
+  }
+
+  fn set_color_vision(&mut self, vision: util::ColorVision) {
+    if self.color_vision == vision {
+      return;
+    }
+
+    self.color_vision = vision;
+    self.config.set_color_vision(vision);
+    self.update_colors();
+  }
+
+  fn set_emphasis(&mut self, emphasis: util::Emphasis) {
+    if self.emphasis == emphasis {
+      return;
+    }
+
+    self.emphasis = emphasis;
+    self.emphasis_indices = self
+      .config
+      .get_emphasis_indices(emphasis)
+      .unwrap_or_default();
+    self.emphasis_text = util::format_indices(&self.emphasis_indices);
+    self.config.set_emphasis(emphasis);
+    self.update_colors();
+  }
+
+  /// Set the palette indices of the emphasized features.
+  /// - `indices`: palette indices, or empty to pick them by color
+  fn set_emphasis_indices(&mut self, indices: Vec<u8>) {
+    self.emphasis_text = util::format_indices(&indices);
+    if self.emphasis_indices == indices {
+      return;
+    }
+
+    self.emphasis_indices = indices;
+    let indices = self.emphasis_indices.clone();
+    self.config.set_emphasis_indices(self.emphasis, indices);
+    self.update_colors();
+  }
+
+  fn set_coord_format(&mut self, coord_format: util::CoordFormat) {
+    if self.coord_format == coord_format {
+      return;
+    }
+
+    self.coord_format = coord_format;
+    self.config.set_coord_format(coord_format);
+    if let Some(airport_dlg) = &mut self.airport_dlg {
+      airport_dlg.set_coord_format(coord_format);
+    }
+  }
+
+  /// Send the image read settings to a chart reader. The image cache budget is split evenly between
+  /// the open chart readers.
+  /// - `reader`: chart reader
+  fn set_read_options(&self, reader: &chart::RasterReader) {
+    let budget = self.image_cache as usize * BYTES_PER_MB / self.chart_readers().count().max(1);
+    reader.set_cache_overview(self.cache_overview);
+    reader.set_progressive(self.progressive);
+    reader.set_cache_budget(budget);
+  }
+
+  /// Dim the display if there hasn't been any input for the dim timeout.
+  /// - `wake`: true if there was input while the display was dimmed
+  fn dim_display(&mut self, ctx: &egui::Context, wake: bool) {
+    if self.dim_timeout == 0 {
+      self.dimmed = false;
+      return;
+    }
+
+    let timeout = time::Duration::from_secs(self.dim_timeout as u64 * 60);
+    let elapsed = self.last_input.elapsed();
+    self.dimmed = elapsed >= timeout;
+    if !self.dimmed {
+      // Repaint when it's time to dim.
+      ctx.request_repaint_after(timeout - elapsed);
+      if !wake {
+        return;
       }
-    });
-    events
+    }
+
+    // Cover the display. The cover stays for the frame that wakes the display so that the input
+    // that wakes it doesn't reach anything underneath.
+    let alpha = if self.dimmed {
+      (self.dim_level.min(100) * 255 / 100) as u8
+    } else {
+      0
+    };
+    let rect = ctx.screen_rect();
+    egui::Area::new(egui::Id::new("dim_display"))
+      .order(egui::Order::Foreground)
+      .fixed_pos(rect.min)
+      .show(ctx, |ui| {
+        ui.allocate_rect(rect, egui::Sense::click_and_drag());
+        let color = epaint::Color32::from_black_alpha(alpha);
+        ui.painter().rect_filled(rect, 0.0, color);
+      });
   }
-}
 
-impl eframe::App for App {
-  fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-    // Process input.
-    let events = self.process_input(ctx);
+  /// Reopen the active chart's file, keeping the current view.
+  fn reload_chart(&mut self, ctx: &egui::Context) {
+    if self.reloader.is_some() {
+      return;
+    }
+
+    let Some(path) = self.get_chart().map(|chart| chart.path.clone()) else {
+      return;
+    };
+
+    // Reopen the chart on a separate thread. The current reader stays in use until it's done.
+    let opener = chart::RasterOpener::new(&path, ctx);
+    self.reloader = Some((path, opener));
+  }
+
+  /// Swap in the reopened chart once a reload has finished.
+  fn check_reload(&mut self) {
+    let Some(result) = self.reloader.as_ref().and_then(|(_, opener)| opener.get()) else {
+      return;
+    };
+
+    let (path, _) = self.reloader.take().unwrap();
+    let reader = match result {
+      Ok(reader) => reader,
+      Err(err) => {
+        self.error_dlg = Some(error_dlg::ErrorDlg::open(err));
+        return;
+      }
+    };
+
+    // Drop the result if a different chart was activated in the meantime.
+    if !self.get_chart().is_some_and(|chart| chart.path == path) {
+      return;
+    }
+
+    if self.custom_colors() {
+      self.send_colors(&reader);
+    }
+    self.set_read_options(&reader);
+
+    // The new edition may have a different transformation.
+    if let Some(airport_reader) = &self.airport_reader {
+      let proj4 = reader.transform().get_proj4();
+      let bounds = reader.transform().bounds().clone();
+      airport_reader.set_spatial_ref(proj4, bounds);
+    }
+    self.markers = AirportMarkers::default();
+    self.thumbnails.remove(&path);
+
+    if let Chart::Ready(chart) = &mut self.chart {
+      chart.title = reader
+        .title()
+        .map_or_else(|| chart.name.clone(), str::to_owned);
+      chart.reader = rc::Rc::new(reader);
+      chart.texture = None;
+    }
+
+    // Request an image for the current view.
+    if let Some((rect, zoom)) = self.get_chart().map(|chart| (chart.disp_rect, chart.zoom)) {
+      self.request_image(rect, zoom);
+    }
+  }
+
+  /// Get all the open chart readers, including those of inactive tabs and the split view.
+  fn chart_readers(&self) -> impl Iterator<Item = &chart::RasterReader> {
+    let charts = self.charts.iter().chain(self.split.iter()).map(Box::as_ref);
+    let charts = charts.chain(self.get_chart());
+    charts.map(|chart| chart.reader.as_ref())
+  }
+
+  /// Send the image read settings to all the chart readers.
+  fn update_read_options(&self) {
+    for reader in self.chart_readers() {
+      self.set_read_options(reader);
+    }
+  }
+
+  /// True if the chart colors differ from the chart's own palette.
+  fn custom_colors(&self) -> bool {
+    self.color_vision != util::ColorVision::Normal
+      || !self.color_map.is_empty()
+      || self.emphasis != util::Emphasis::Off
+  }
+
+  /// Send the chart colors to a chart reader.
+  /// - `reader`: chart reader
+  fn send_colors(&self, reader: &chart::RasterReader) {
+    let (vision, color_map) = (self.color_vision, self.color_map.clone());
+    reader.set_colors(
+      vision,
+      color_map,
+      self.emphasis,
+      self.emphasis_indices.clone(),
+    );
+  }
+
+  /// Send the chart colors to all the chart readers and request new images.
+  fn update_colors(&mut self) {
+    if let Some(split) = &self.split {
+      self.send_colors(&split.reader);
+      if let Some((part, _)) = &split.texture {
+        split
+          .reader
+          .read_image(self.image_part(part.rect, part.zoom.into()));
+      }
+    }
+
+    if let Some(reader) = self.get_chart_reader() {
+      self.send_colors(&reader);
+      if let Some((part, _)) = self.get_chart_texture() {
+        self.request_image(part.rect, part.zoom.into());
+      }
+    }
+
+    // Charts in the other tabs are read again when they're activated.
+    for chart in &self.charts {
+      self.send_colors(&chart.reader);
+    }
+
+    for chart in &mut self.charts {
+      chart.texture = None;
+    }
+  }
+
+  fn open_settings_folder(&mut self) {
+    let Some(folder) = config::Storage::folder() else {
+      let text = "Unable to find the settings folder";
+      self.error_dlg = Some(error_dlg::ErrorDlg::open(text.into()));
+      return;
+    };
+
+    if let Err(err) = util::open_folder(&folder) {
+      self.error_dlg = Some(error_dlg::ErrorDlg::open(err));
+    }
+  }
+
+  /// Ask where to save a zip file with the app and GDAL versions plus the sanitized settings.
+  fn export_diagnostics(&mut self) {
+    let gdal = match util::gdal_self_test() {
+      Ok(version) => version,
+      Err(err) => err.into(),
+    };
+    let summary = [
+      format!("{} {}", util::APP_NAME, env!("CARGO_PKG_VERSION")),
+      format!("GDAL {gdal}"),
+      format!("{} {}", env::consts::OS, env::consts::ARCH),
+      String::new(),
+    ]
+    .join("\n");
+    let settings = self.config.sanitized_json();
+    let files = [
+      ("summary.txt", summary.as_bytes()),
+      ("settings.json", settings.as_bytes()),
+    ];
+    let zip = util::zip_files(&files);
+
+    let path = dirs::download_dir().or_else(dirs::home_dir);
+    let mut diagnostics_dlg = egui_file::FileDialog::save_file(path)
+      .title("Save Diagnostics")
+      .anchor(emath::Align2::CENTER_CENTER, [0.0, 0.0])
+      .default_size([525.0, 320.0])
+      .default_filename(format!("{}_diagnostics.zip", util::APP_NAME))
+      .show_rename(false)
+      .resizable(false);
+    diagnostics_dlg.open();
+    self.diagnostics_dlg = Some((diagnostics_dlg, zip));
+  }
+
+  fn reset_settings(&mut self, ctx: &egui::Context) {
+    // Put the displayed settings back to their defaults.
+    self.set_night_mode(ctx, false);
+    self.set_ui_theme(ctx, util::UiTheme::default());
+    self.set_grayscale(false);
+    self.bg_color = None;
+    self.night_bg_color = None;
+    self.set_sharpen(0.0);
+    self.set_color_map(util::ColorMap::new());
+    self.set_color_vision(util::ColorVision::default());
+    self.set_emphasis(util::Emphasis::default());
+    self.image_limit = 0;
+    self.image_cache = 0;
+    self.dim_timeout = 0;
+    self.dim_level = DEFAULT_DIM_LEVEL;
+    self.chart_thumbnails = true;
+    self.scroll_snap = util::ScrollSnap::default();
+    self.zoom_lock = false;
+    self.units = util::Units::default();
+    self.coord_format = util::CoordFormat::default();
+    self.asset_path = dirs::download_dir();
+    self.charts_path = None;
+    self.auto_load_aero = false;
+    self.auto_hide_panel = false;
+    self.open_fit = false;
+    self.show_closed = true;
+    self.show_private = true;
+    self.towered_only = false;
+    self.show_center = false;
+    self.highlight_found = true;
+    self.low_power = false;
+    self.cache_overview = true;
+    self.progressive = false;
+    self.update_read_options();
+    self.range_rings = 0;
+    self.screen_dpi = DEFAULT_SCREEN_DPI;
+    self.marker_size = util::MarkerSize::default();
+    self.zoom_step = util::ZoomStep::default();
+    self.goto_anchor = util::GotoAnchor::default();
+    self.marker_labels = true;
+    self.markers = AirportMarkers::default();
+    self.long_press_feedback = MOBILE;
+    self.show_magnetic = true;
+    self.long_press_action = util::LongPressAction::default();
+    self.escape_action = util::EscapeAction::default();
+
+    // Clear the stored settings.
+    self.config.reset();
+  }
+
+  /// Make sure that a restored window fits on its monitor. egui can't list the monitors and only
+  /// reports the size of the current one, so only the primary monitor at the desktop origin is
+  /// known. A window whose upper-left corner is at a negative or far offset may be on another
+  /// monitor and is left where it is.
+  fn check_win_pos(&mut self, ctx: &egui::Context) {
+    let Some(monitor) = self.win_info.monitor else {
+      return;
+    };
+
+    let Some(restored) = self.restored_win.take() else {
+      return;
+    };
+
+    if restored.maxed || restored.pos.is_none() {
+      return;
+    }
+
+    if let Some(rect) = ctx.input(|state| state.viewport().outer_rect) {
+      let pos = rect.min.into();
+      if !util::on_monitor(pos, util::Pos::default(), monitor) {
+        return;
+      }
+
+      let clamped = util::clamp_win_pos(pos, rect.size().into(), util::Pos::default(), monitor);
+      if clamped != pos {
+        ctx.send_viewport_cmd(egui::ViewportCommand::OuterPosition(clamped.into()));
+      }
+    }
+  }
+
+  fn process_input(&mut self, ctx: &egui::Context) -> InputEvents {
+    let mut events = InputEvents::new(ctx);
+    if let Some(pos) = self.long_press.check() {
+      if self.long_press_feedback {
+        self.feedback.play();
+      }
+
+      match self.long_press_action {
+        util::LongPressAction::Menu => events.secondary_click = Some(pos),
+        util::LongPressAction::Recenter => events.recenter = Some(pos),
+      }
+    }
+
+    // Keyboard shortcuts shouldn't be handled while a text field has focus.
+    let text_focus = ctx.wants_keyboard_input();
+
+    ctx.input(|state| {
+      // Get the window size info.
+      self.win_info = util::WinInfo::new(state.viewport());
+
+      // Any input wakes a dimmed display.
+      if !state.events.is_empty() {
+        events.wake = self.dimmed;
+        self.last_input = time::Instant::now();
+      }
+
+      // Process events.
+      for event in &state.events {
+        match event {
+          egui::Event::Key {
+            key,
+            physical_key: _,
+            pressed,
+            repeat,
+            modifiers,
+          } if *pressed && !*repeat && self.ui_enabled => {
+            match key {
+              egui::Key::Plus | egui::Key::Equals | egui::Key::Minus
+                if !text_focus && !self.zoom_lock && !modifiers.command =>
+              {
+                let factor = self.zoom_step.factor();
+                let factor = if *key == egui::Key::Minus {
+                  factor.recip()
+                } else {
+                  factor
+                };
+                if let Chart::Ready(chart) = &mut self.chart {
+                  chart.zoom_by(factor);
+                }
+              }
+              egui::Key::Escape => {
+                // Clear whatever has priority.
+                self.escape();
+              }
+              egui::Key::F if modifiers.command_only() => {
+                if let Some(nasr_reader) = &self.airport_reader {
+                  if nasr_reader.airport_basic_idx() && matches!(self.chart, Chart::Ready(_)) {
+                    self.find_dlg = Some(find_dlg::FindDlg::open(self.find_chart_only));
+                    self.reset_airport_menu();
+                  }
+                }
+              }
+              egui::Key::O if modifiers.command_only() && !text_focus => {
+                self.select_zip_file(ZipFilter::Any);
+                self.reset_airport_menu();
+              }
+              egui::Key::J if modifiers.command_only() && !text_focus => {
+                self.goto_last_click();
+                self.reset_airport_menu();
+              }
+              egui::Key::L if modifiers.command_only() && !text_focus => {
+                self.set_coord_format(self.coord_format.next());
+                let text = format!("Coordinates: {}", self.coord_format.text());
+                self.toast = Some(toast::Toast::new(text));
+              }
+              egui::Key::R if modifiers.command_only() && !text_focus => {
+                events.reload = true;
+                self.reset_airport_menu();
+              }
+              egui::Key::F11 => {
+                let fullscreen = state.viewport().fullscreen.unwrap_or(false);
+                events.fullscreen = Some(!fullscreen);
+              }
+              egui::Key::Q if modifiers.command_only() => {
+                events.quit = true;
+                self.reset_airport_menu();
+              }
+              _ => (),
+            }
+          }
+          egui::Event::Touch {
+            device_id,
+            id,
+            phase,
+            pos,
+            force: _,
+          } => self.long_press.initiate(*device_id, *id, *phase, *pos),
+          egui::Event::PointerButton {
+            pos,
+            button,
+            pressed,
+            modifiers,
+          } if *button == egui::PointerButton::Secondary && !pressed && modifiers.is_none() => {
+            events.secondary_click = Some(*pos);
+          }
+          egui::Event::Zoom(val) => {
+            events.zoom_pos = state.pointer.hover_pos();
+            events.zoom_mod *= val;
+          }
+          _ => (),
+        }
+      }
+    });
+
+    if self.zoom_lock {
+      // Ignore zoom gestures so that only panning is possible.
+      events.zoom_mod = 1.0;
+      events.zoom_pos = None;
+    }
+
+    events
+  }
+}
+
+impl eframe::App for App {
+  fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+    // Process input.
+    let events = self.process_input(ctx);
+    self.check_win_pos(ctx);
+    self.update_dem(ctx);
+    self.check_reload();
+
+    // Process chart raster replies.
+    for reply in self.get_chart_replies() {
+      match reply {
+        chart::RasterReply::Image(part, image) => {
+          self.set_chart_image(ctx, part, image);
+        }
+        chart::RasterReply::Error(_, err) => {
+          println!("{err}");
+        }
+      }
+    }
+
+    // Process split view raster replies.
+    if let Some(split) = &mut self.split {
+      for reply in split.reader.get_replies() {
+        match reply {
+          chart::RasterReply::Image(part, image) => {
+            let texture = ctx.load_texture("split_image", image, Default::default());
+            split.texture = Some((part, texture));
+          }
+          chart::RasterReply::Error(_, err) => {
+            println!("{err}");
+          }
+        }
+      }
+    }
+
+    // Close the chart if its reader has stopped working.
+    if self
+      .get_chart_reader()
+      .is_some_and(|reader| !reader.is_available())
+    {
+      let text = "The chart reader stopped unexpectedly";
+      self.error_dlg = Some(error_dlg::ErrorDlg::open(text.into()));
+      self.close_chart();
+    }
+
+    // Unload the airport data if its reader has stopped working.
+    if self
+      .airport_reader
+      .as_ref()
+      .is_some_and(|reader| !reader.is_available())
+    {
+      let text = "The airport data reader stopped unexpectedly";
+      self.error_dlg = Some(error_dlg::ErrorDlg::open(text.into()));
+      self.airport_reader = None;
+    }
+
+    // Process NASR airport replies.
+    for reply in self.get_airport_replies() {
+      match reply {
+        nasr::AirportReply::Airport(info) => {
+          self.show_airport(info);
+        }
+        nasr::AirportReply::MagVar(coord, infos) => {
+          // Replies for an earlier origin are stale.
+          if self.mag_var.pending == Some(coord) {
+            self.mag_var.pending = None;
+            self.mag_var.set_value(&infos);
+          }
+        }
+        nasr::AirportReply::Nearby(coord, mut infos) => {
+          if let Some(index) = self.nearby.pending.iter().position(|c| *c == coord) {
+            // Reply for the nearby airports list. Only the most recent request is of interest.
+            self.nearby.pending.remove(index);
+            if self.nearby.request.map(|(request, _)| request) == Some(coord) {
+              if let Some(reader) = self.get_chart_reader() {
+                if let Ok(center) = reader.transform().chart_to_nad83(coord) {
+                  self.nearby.set_items(center, infos);
+                }
+              }
+            }
+            continue;
+          }
+
+          infos.retain(|info| self.airport_visible(info));
+
+          if !infos.is_empty() {
+            if let AirportInfos::Menu(_, airport_list) = &mut self.airport_infos {
+              *airport_list = Some(infos);
+            }
+          }
+        }
+        nasr::AirportReply::InBounds(bounds, infos) => {
+          if self.markers.request.as_ref() == Some(&bounds) {
+            if let Some(reader) = self.get_chart_reader() {
+              // Convert the airport coordinates to chart pixels.
+              let transform = reader.transform();
+              let items = infos.into_iter().filter_map(|info| {
+                let px: emath::Pos2 = transform.nad83_to_px(info.coord).ok()?.into();
+                Some((px, info))
+              });
+              self.markers.items = items.collect();
+            }
+          }
+        }
+        nasr::AirportReply::Remarks(id, remarks) => {
+          if let Some(airport_dlg) = &mut self.airport_dlg {
+            airport_dlg.set_remarks(&id, remarks);
+          }
+        }
+        nasr::AirportReply::Search(mut infos, truncated) => {
+          infos.retain(|(_, info)| self.airport_visible(info));
+          if infos.is_empty() {
+            let err = if self.towered_only || !self.show_private {
+              "Only filtered out airports match the search"
+            } else {
+              "Only closed airports match the search"
+            };
+            self.error_dlg = Some(error_dlg::ErrorDlg::open(err.into()));
+            continue;
+          }
+
+          if truncated {
+            let text = format!(
+              "Showing the first {} matches, refine the search",
+              infos.len()
+            );
+            self.toast = Some(toast::Toast::new(text));
+          }
+
+          match infos.len() {
+            0 => unreachable!(),
+            1 => self.show_airport(infos.into_iter().next().unwrap().1),
+            _ => self.airport_infos = AirportInfos::Dialog(infos),
+          }
+        }
+        nasr::AirportReply::Error(err) => {
+          self.error_dlg = Some(error_dlg::ErrorDlg::open(err));
+        }
+      }
+    }
+
+    // Show the file dialog if set.
+    if let Some(file_dlg) = &mut self.file_dlg {
+      if file_dlg.show(ctx).visible() {
+        self.ui_enabled = false;
+      } else {
+        if file_dlg.selected() {
+          if let Some(path) = file_dlg.path() {
+            // Save the folder path.
+            if let Some(path) = path.parent().and_then(|p| p.to_str()) {
+              self.config.set_asset_path(path.into());
+              self.asset_path = Some(path.into());
+            }
+
+            let path = path.to_owned();
+            match util::get_zip_info(&path) {
+              Ok(info) => match info {
+                util::ZipInfo::Chart(files) => {
+                  if files.len() > 1 {
+                    self.choose_chart(path, files);
+                  } else {
+                    self.open_chart_data(ctx, &path, files.first().unwrap());
+                  }
+                }
+                util::ZipInfo::Aero { csv, shp: _ } => {
+                  self.open_airport_data(ctx, &path, &csv);
+                }
+              },
+              Err(err) => {
+                self.error_dlg = Some(error_dlg::ErrorDlg::open(err));
+              }
+            }
+          }
+        }
+        self.file_dlg = None;
+        self.ui_enabled = true;
+      }
+    }
+
+    // Show the folder dialog if set.
+    if let Some(folder_dlg) = &mut self.folder_dlg {
+      if folder_dlg.show(ctx).visible() {
+        self.ui_enabled = false;
+      } else {
+        if folder_dlg.selected() {
+          if let Some(path) = folder_dlg.path().and_then(|p| p.to_str()) {
+            self.config.set_charts_path(path.into());
+            self.charts_path = Some(path.into());
+          }
+        }
+        self.folder_dlg = None;
+        self.ui_enabled = true;
+      }
+    }
+
+    // Show the airport data folder dialog if set.
+    if let Some(folder_dlg) = &mut self.airport_folder_dlg {
+      if folder_dlg.show(ctx).visible() {
+        self.ui_enabled = false;
+      } else {
+        if folder_dlg.selected() {
+          if let Some(path) = folder_dlg.path().map(path::Path::to_owned) {
+            match util::get_zip_info(&path) {
+              Ok(util::ZipInfo::Aero { csv, shp: _ }) => {
+                self.open_airport_data(ctx, &path, &csv);
+              }
+              Ok(_) => (),
+              Err(err) => {
+                self.error_dlg = Some(error_dlg::ErrorDlg::open(err));
+              }
+            }
+          }
+        }
+        self.airport_folder_dlg = None;
+        self.ui_enabled = true;
+      }
+    }
+
+    // Show the airport summary save dialog if set.
+    if let Some((summary_dlg, summary)) = &mut self.summary_dlg {
+      if summary_dlg.show(ctx).visible() {
+        self.ui_enabled = false;
+      } else {
+        if summary_dlg.selected() {
+          if let Some(path) = summary_dlg.path() {
+            match fs::write(path, summary.as_bytes()) {
+              Ok(()) => {
+                let text = format!("Saved {}", path.display());
+                self.toast = Some(toast::Toast::new(text));
+              }
+              Err(err) => {
+                let text = format!("Unable to save the airport summary: {err}");
+                self.error_dlg = Some(error_dlg::ErrorDlg::open(text.into()));
+              }
+            }
+          }
+        }
+        self.summary_dlg = None;
+        self.ui_enabled = true;
+      }
+    }
+
+    // Show the diagnostics save dialog if set.
+    if let Some((diagnostics_dlg, zip)) = &mut self.diagnostics_dlg {
+      if diagnostics_dlg.show(ctx).visible() {
+        self.ui_enabled = false;
+      } else {
+        if diagnostics_dlg.selected() {
+          if let Some(path) = diagnostics_dlg.path() {
+            match fs::write(path, zip.as_slice()) {
+              Ok(()) => {
+                let text = format!("Saved {}", path.display());
+                self.toast = Some(toast::Toast::new(text));
+              }
+              Err(err) => {
+                let text = format!("Unable to save the diagnostics: {err}");
+                self.error_dlg = Some(error_dlg::ErrorDlg::open(text.into()));
+              }
+            }
+          }
+        }
+        self.diagnostics_dlg = None;
+        self.ui_enabled = true;
+      }
+    }
+
+    // Show the progress dialog while a chart is being opened.
+    if let Chart::Open(open) = &mut self.chart {
+      self.ui_enabled = false;
+      let result = open.opener.get();
+      if result.is_some() || !open.progress_dlg.show(ctx) {
+        self.ui_enabled = true;
+        if let Chart::Open(open) = mem::replace(&mut self.chart, Chart::None) {
+          match result {
+            Some(result) => self.chart_opened(ctx, *open, result),
+            None => self.restore_chart(),
+          }
+        }
+      }
+    }
+
+    // Show the selection dialog if there's a chart choice to be made.
+    if let Chart::Load(path, files) = &self.chart {
+      self.ui_enabled = false;
+      let response = if self.chart_thumbnails {
+        // Read the thumbnails that haven't been read yet.
+        let paths: Vec<path::PathBuf> = files
+          .iter()
+          .map(|file| util::chart_path(path, file))
+          .collect();
+        if self.thumbnail_reader.is_none() {
+          let missing = paths.iter().filter(|p| !self.thumbnails.contains_key(*p));
+          let missing: Vec<path::PathBuf> = missing.cloned().collect();
+          if !missing.is_empty() {
+            let size = select_dlg::THUMBNAIL_SIZE as u32;
+            self.thumbnail_reader = Some(chart::ThumbnailReader::new(missing, size, ctx));
+          }
+        }
+
+        if let Some(reader) = &self.thumbnail_reader {
+          for (path, image) in reader.get_replies() {
+            let texture = ctx.load_texture("thumbnail", image, Default::default());
+            self.thumbnails.insert(path, texture);
+          }
+        }
+
+        let choices = files
+          .iter()
+          .zip(&paths)
+          .map(|(file, path)| (util::stem_str(file).unwrap(), self.thumbnails.get(path)));
+        self.select_dlg.show_images(ctx, choices)
+      } else {
+        let choices = files.iter().map(|f| util::stem_str(f).unwrap());
+        self.select_dlg.show(ctx, choices)
+      };
+
+      if let Some(response) = response {
+        self.ui_enabled = true;
+        self.thumbnail_reader = None;
+        if let select_dlg::Response::Index(index) = response {
+          // Clone the parameters in order to avoid simultaneously borrowing self as immutable and mutable.
+          let (path, files) = (path.clone(), files.clone());
+          self.open_chart_data(ctx, &path, &files[index]);
+
+          // Keep the choices in case the chart can't be opened.
+          if let Chart::Open(open) = &mut self.chart {
+            open.choices = Some((path, files));
+          }
+        } else {
+          self.chart = Chart::None;
+          self.restore_chart();
+        }
+      }
+    }
+
+    // Show the selection dialog if there's an airport choice to be made.
+    if let AirportInfos::Dialog(infos) = &self.airport_infos {
+      self.ui_enabled = false;
+      let iter = infos
+        .iter()
+        .map(|(group, info)| (group.text(), info.desc.as_str()));
+      if let Some(response) = self.select_dlg.show_grouped(ctx, iter) {
+        self.ui_enabled = true;
+        let airport_infos = mem::replace(&mut self.airport_infos, AirportInfos::None);
+        if let (select_dlg::Response::Index(index), AirportInfos::Dialog(mut infos)) =
+          (response, airport_infos)
+        {
+          self.show_airport(infos.swap_remove(index).1);
+        }
+      }
+    }
+
+    // Show the find dialog.
+    if let Some(find_dialog) = &mut self.find_dlg {
+      self.ui_enabled = false;
+      match find_dialog.show(ctx) {
+        find_dlg::Response::None => (),
+        find_dlg::Response::Cancel => {
+          self.ui_enabled = true;
+          self.find_dlg = None;
+        }
+        find_dlg::Response::Term(term, chart_only) => {
+          self.ui_enabled = true;
+          self.find_dlg = None;
+          self.find_chart_only = chart_only;
+          if let Some(nasr_reader) = &self.airport_reader {
+            // Limit the search to the chart bounds.
+            let bounds = self
+              .get_chart()
+              .filter(|_| chart_only)
+              .map(|chart| chart.reader.transform().bounds().clone());
+            nasr_reader.search(term, bounds, self.include_nph);
+          }
+        }
+      }
+    }
+
+    // Show the bearing dialog.
+    if let Some(bearing_dlg) = &mut self.bearing_dlg {
+      self.ui_enabled = false;
+      match bearing_dlg.show(ctx) {
+        bearing_dlg::Response::None => (),
+        bearing_dlg::Response::Cancel => {
+          self.ui_enabled = true;
+          self.bearing_dlg = None;
+        }
+        bearing_dlg::Response::Bearing(bearing) => {
+          self.bearing_line = Some((bearing_dlg.origin(), bearing));
+          self.ui_enabled = true;
+          self.bearing_dlg = None;
+        }
+      }
+    }
+
+    // Offer to choose a different chart after one couldn't be opened.
+    if let Some((retry_dlg, _, _)) = &mut self.retry_dlg {
+      self.ui_enabled = false;
+      let response = retry_dlg.show(ctx);
+      if response != confirm_dlg::Response::None {
+        self.ui_enabled = true;
+        if let Some((_, path, files)) = self.retry_dlg.take() {
+          if response == confirm_dlg::Response::Confirm {
+            self.choose_chart(path, files);
+          }
+        }
+      }
+    }
+
+    // Show the reset confirmation dialog.
+    if let Some(reset_dlg) = &mut self.reset_dlg {
+      self.ui_enabled = false;
+      let response = reset_dlg.show(ctx);
+      if response != confirm_dlg::Response::None {
+        self.reset_dlg = None;
+        self.ui_enabled = true;
+        if response == confirm_dlg::Response::Confirm {
+          self.reset_settings(ctx);
+        }
+      }
+    }
+
+    // Show the error dialog if there's an error.
+    if let Some(error_dlg) = &mut self.error_dlg {
+      self.ui_enabled = false;
+      if !error_dlg.show(ctx) {
+        self.error_dlg = None;
+        self.ui_enabled = true;
+      }
+    }
+
+    // Show the airport dialog.
+    if let Some(airport_dlg) = &mut self.airport_dlg {
+      self.ui_enabled = false;
+      if let (Some(id), Some(nasr_reader)) = (airport_dlg.remarks_request(), &self.airport_reader) {
+        nasr_reader.remarks(id);
+      }
+
+      let response = airport_dlg.show(ctx);
+      match response {
+        airport_dlg::Response::None => (),
+        airport_dlg::Response::Bookmark => {
+          let info = airport_dlg.info();
+          let name = format!("{} ({})", info.short_name(), info.id);
+          self.toast = Some(toast::Toast::new(format!("Bookmarked {}", info.id)));
+          self.bookmarks.push(util::Bookmark::new(name, info.coord));
+          self.config.set_bookmarks(self.bookmarks.clone());
+        }
+        airport_dlg::Response::CopyId => {
+          let id = airport_dlg.info().id.clone();
+          self.toast = Some(toast::Toast::new(format!("Copied {id}")));
+          ctx.output_mut(|state| state.copied_text = id);
+        }
+        airport_dlg::Response::ExportSummary => {
+          let id = airport_dlg.info().id.clone();
+          let summary = airport_dlg.summary();
+          self.airport_dlg = None;
+          self.ui_enabled = true;
+          self.export_airport_summary(&id, summary);
+        }
+        _ => {
+          let coord = airport_dlg.info().coord;
+          self.airport_dlg = None;
+          self.ui_enabled = true;
+          match response {
+            airport_dlg::Response::GoTo => {
+              self.goto_coord(coord);
+            }
+            airport_dlg::Response::Measure => self.measure = Some(MeasurePath::new(coord)),
+            airport_dlg::Response::BearingLine => {
+              self.bearing_dlg = Some(bearing_dlg::BearingDlg::open(coord));
+            }
+            _ => (),
+          }
+        }
+      }
+    }
+
+    // Show the toast.
+    if let Some(toast) = &self.toast {
+      if !toast.show(ctx) {
+        self.toast = None;
+      }
+    }
+
+    // Show airport choices in a popup.
+    if let AirportInfos::Menu(lat_lon, infos) = &mut self.airport_infos {
+      let iter = infos
+        .as_ref()
+        .map(|v| v.iter().map(|info| info.desc.as_str()));
+      if let Some(response) = self.select_menu.show(ctx, lat_lon, iter) {
+        if let select_menu::Response::Index(index) = response {
+          // Show the selected airport in a dialog.
+          if let Some(mut infos) = infos.take() {
+            let info = infos.swap_remove(index);
+            let airport_dlg = airport_dlg::AirportDlg::open(info, self.coord_format);
+            self.airport_dlg = Some(airport_dlg);
+          }
+        }
+        self.airport_infos = AirportInfos::None;
+      }
+    }
+
+    let mut tab_action = None;
+    let mut toggle_split = false;
+    let mut toggle_zoom_lock = false;
+    let split = self.split.is_some();
+    let zoom_step = self.zoom_step;
+    self.top_panel_height = top_panel(self.top_panel_height, ctx, |ui| {
+      ui.set_enabled(self.ui_enabled);
+      ui.horizontal_centered(|ui| {
+        let widget = egui::SelectableLabel::new(self.side_panel, " ⚙ ");
+        if ui.add_sized([0.0, 21.0], widget).clicked() {
+          self.toggle_side_panel(!self.side_panel);
+        }
+
+        if let Some(nasr_reader) = &self.airport_reader {
+          if nasr_reader.airport_basic_idx() {
+            let text = 'text: {
+              const APT: &str = "APT";
+              if nasr_reader.request_count() > 0 {
+                ctx.output_mut(|state| state.cursor_icon = egui::CursorIcon::Progress);
+                break 'text egui::RichText::new(APT).strong();
+              }
+              egui::RichText::new(APT)
+            };
+
+            ui.separator();
+            ui.label(text);
+          }
+        }
+
+        if let Chart::Ready(chart) = &mut self.chart {
+          if let Some(nasr_reader) = &self.airport_reader {
+            if nasr_reader.airport_spatial_idx() && ui.button("🔎").clicked() {
+              self.find_dlg = Some(find_dlg::FindDlg::open(self.find_chart_only));
+            }
+          }
+
+          ui.separator();
+          if self.charts.is_empty() {
+            ui.label(&chart.title)
+              .on_hover_text(util::chart_label(&chart.name));
+          } else {
+            // Show the open charts as tabs.
+            let mut others = self.charts.iter();
+            for tab in 0..=self.charts.len() {
+              if tab == self.active_tab {
+                ui.selectable_label(true, &chart.title)
+                  .on_hover_text(util::chart_label(&chart.name));
+              } else if let Some(other) = others.next() {
+                let response = ui.selectable_label(false, &other.title);
+                if response.clicked() {
+                  tab_action = Some(TabAction::Select(tab));
+                } else if response.middle_clicked() {
+                  tab_action = Some(TabAction::Close(tab));
+                }
+                let label = util::chart_label(&other.name);
+                response.on_hover_text(format!("{label}\nMiddle-click to close"));
+
+                // Middle-clicking isn't possible on touch screens.
+                if ui.small_button("×").on_hover_text("Close").clicked() {
+                  tab_action = Some(TabAction::Close(tab));
+                }
+              }
+            }
+          }
+
+          // Coordinate at the center of the view, along with how precise it is.
+          let center = self.show_center.then(|| chart.get_center_coord()).flatten();
+          let accuracy = chart.get_accuracy();
+
+          ui.with_layout(egui::Layout::right_to_left(emath::Align::Center), |ui| {
+            // Split view toggle.
+            let widget = egui::SelectableLabel::new(split, " ◫ ");
+            let response = ui.add_sized([0.0, 21.0], widget);
+            if response.on_hover_text("Split View").clicked() {
+              toggle_split = true;
+            }
+
+            ui.separator();
+
+            // Zoom lock toggle.
+            let widget = egui::SelectableLabel::new(self.zoom_lock, " 🔒 ");
+            let response = ui.add_sized([0.0, 21.0], widget);
+            if response.on_hover_text("Lock Zoom").clicked() {
+              toggle_zoom_lock = true;
+            }
+
+            // The zoom buttons are hidden while the zoom is locked.
+            if !self.zoom_lock {
+              // Zoom-in button.
+              ui.add_enabled_ui(chart.zoom < 1.0, |ui| {
+                if let Some(font_id) = ui.style().text_styles.get(&egui::TextStyle::Monospace) {
+                  let text = egui::RichText::new("+").font(font_id.clone());
+                  let widget = egui::Button::new(text);
+                  if ui.add_sized([21.0, 21.0], widget).clicked() {
+                    chart.zoom_by(zoom_step.factor());
+                  }
+                }
+              });
+
+              // Zoom-out button.
+              let min_zoom = chart.get_min_zoom();
+              ui.add_enabled_ui(chart.zoom > min_zoom, |ui| {
+                if let Some(font_id) = ui.style().text_styles.get(&egui::TextStyle::Monospace) {
+                  let text = egui::RichText::new("-").font(font_id.clone());
+                  let widget = egui::Button::new(text);
+                  if ui.add_sized([21.0, 21.0], widget).clicked() {
+                    chart.zoom_by(zoom_step.factor().recip());
+                  }
+                }
+              });
+            }
+
+            // Display scale, which can be set to one of the standard chart scales.
+            let dpi = self.screen_dpi as f64;
+            if let Some(scale) = chart.get_scale(dpi) {
+              ui.separator();
+              ui.add_enabled_ui(!self.zoom_lock, |ui| {
+                let response = ui.menu_button(util::format_scale(scale), |ui| {
+                  for target in CHART_SCALES {
+                    if ui.button(util::format_scale(target)).clicked() {
+                      if let Some(zoom) = chart.get_scale_zoom(target, dpi) {
+                        chart.zoom_by(zoom / chart.zoom);
+                      }
+                      ui.close_menu();
+                    }
+                  }
+                });
+                response.response.on_hover_text("Display Scale");
+              });
+            }
+
+            if let Some(text) = center.and_then(|coord| self.coord_format.format(coord)) {
+              ui.separator();
+              if let Some(meters) = accuracy {
+                let accuracy = egui::RichText::new(accuracy_text(self.units, meters)).weak();
+                ui.label(accuracy)
+                  .on_hover_text("Ground distance covered by one screen pixel");
+              }
+              ui.label(text).on_hover_text("View Center");
+            }
+          });
+        }
+      });
+    });
+
+    // Switch or close chart tabs.
+    match tab_action {
+      Some(TabAction::Select(tab)) => self.activate_chart(tab),
+      Some(TabAction::Close(tab)) => self.close_tab(tab),
+      None => (),
+    }
+
+    if toggle_split {
+      self.toggle_split(ctx);
+    }
+
+    if toggle_zoom_lock {
+      self.zoom_lock = !self.zoom_lock;
+      self.config.set_zoom_lock(self.zoom_lock);
+    }
+
+    if self.side_panel {
+      self.side_panel_width = side_panel(self.side_panel_width, ctx, |ui| {
+        ui.set_enabled(self.ui_enabled);
+
+        ui.horizontal(|ui| {
+          let button = egui::Button::new("Open Zip File");
+          if ui.add_sized(ui.available_size(), button).clicked() {
+            self.select_zip_file(ZipFilter::Any);
+          }
+        });
+
+        ui.add_space(ui.spacing().item_spacing.y);
+        ui.separator();
+
+        // Chart status.
+        let status = match &self.chart {
+          Chart::None => egui::RichText::new("Not loaded").weak(),
+          Chart::Load(_, _) => egui::RichText::new("Selecting…").weak(),
+          Chart::Open(_) => egui::RichText::new("Opening…").weak(),
+          Chart::Ready(chart) => egui::RichText::new(&chart.title),
+        };
+
+        if data_status(ui, "Chart:", status, false).is_some() {
+          self.select_zip_file(ZipFilter::Chart);
+        }
+
+        // Airport data status.
+        let status = match &self.airport_reader {
+          None => egui::RichText::new("Not loaded").weak(),
+          Some(reader) if !reader.airport_basic_idx() => egui::RichText::new("Loading…").weak(),
+          Some(reader) => match reader.parse_stats() {
+            // Show how many airports were loaded to confirm the size of the data set.
+            Some(stats) => {
+              let count = util::format_count(stats.loaded_count());
+              egui::RichText::new(format!("{count} airports"))
+            }
+            None => egui::RichText::new("Loaded"),
+          },
+        };
+
+        match data_status(ui, "Airports:", status, true) {
+          Some(DataButton::File) => self.select_zip_file(ZipFilter::Aero),
+          Some(DataButton::Folder) => self.select_airport_folder(),
+          None => (),
+        }
+
+        // Records that couldn't be parsed are skipped, so let the user know about them.
+        let stats = self
+          .airport_reader
+          .as_ref()
+          .and_then(|reader| reader.parse_stats());
+        if let Some(stats) = stats.filter(|stats| stats.failed_count() > 0) {
+          let text = format!("{} records skipped", stats.failed_count());
+          ui.label(egui::RichText::new(text).weak())
+            .on_hover_text(stats.summary());
+        }
+
+        // Charts folder.
+        let status = match self.charts_path.as_ref().and_then(|p| p.file_name()) {
+          Some(name) => egui::RichText::new(name.to_string_lossy()),
+          None => egui::RichText::new("Not set").weak(),
+        };
+
+        if data_status(ui, "Folder:", status, false).is_some() {
+          self.select_charts_folder();
+        }
+
+        ui.add_enabled_ui(matches!(self.chart, Chart::Ready(_)), |ui| {
+          ui.horizontal(|ui| {
+            let button = egui::Button::new("Close Chart");
+            if ui.add_sized(ui.available_size(), button).clicked() {
+              self.close_chart();
+            }
+          });
+
+          ui.horizontal(|ui| {
+            // Show that a reload is in progress.
+            let reloading = self.reloader.is_some();
+            let text = if reloading {
+              "Reloading…"
+            } else {
+              "Reload Chart"
+            };
+            let button = egui::Button::new(text);
+            let size = ui.available_size();
+            let response = ui
+              .add_enabled_ui(!reloading, |ui| ui.add_sized(size, button))
+              .inner;
+            let response = response.on_hover_text("Reopen the chart file to pick up a new edition");
+            if response.clicked() {
+              self.reload_chart(ctx);
+            }
+          });
+
+          ui.horizontal(|ui| {
+            let button = egui::Button::new("Copy View Bounds");
+            let response = ui.add_sized(ui.available_size(), button);
+            let response = response.on_hover_text("Copy the southwest and northeast corners");
+            if response.clicked() {
+              self.copy_view_bounds(ctx);
+            }
+          });
+
+          ui.horizontal(|ui| {
+            let button = egui::Button::new("Copy Proj4");
+            let response = ui.add_sized(ui.available_size(), button);
+            let response = response.on_hover_text("Copy the chart's projection as a proj4 string");
+            if response.clicked() {
+              self.copy_spatial_ref(ctx, false);
+            }
+          });
+
+          ui.horizontal(|ui| {
+            let button = egui::Button::new("Copy WKT");
+            let response = ui.add_sized(ui.available_size(), button);
+            let response = response.on_hover_text("Copy the chart's projection as well known text");
+            if response.clicked() {
+              self.copy_spatial_ref(ctx, true);
+            }
+          });
+
+          ui.horizontal(|ui| {
+            let button = egui::Button::new("Set View as Home");
+            let response = ui.add_sized(ui.available_size(), button);
+            let response = response.on_hover_text("Center newly opened charts on this location");
+            if response.clicked() {
+              self.set_home_to_view();
+            }
+          });
+        });
+
+        ui.add_enabled_ui(self.home.is_some(), |ui| {
+          ui.horizontal(|ui| {
+            let button = egui::Button::new("Clear Home");
+            let response = ui.add_sized(ui.available_size(), button);
+            let response = response.on_hover_text("Open charts at their top left corner");
+            if response.clicked() {
+              self.home = None;
+              self.config.set_home(None);
+            }
+          });
+        });
+
+        ui.add_enabled_ui(self.last_click.is_some(), |ui| {
+          ui.horizontal(|ui| {
+            let button = egui::Button::new("Go to Last Click");
+            let response = ui.add_sized(ui.available_size(), button);
+            let response = response
+              .on_hover_text("Center the chart on the last right-clicked position (Ctrl+J)");
+            if response.clicked() {
+              self.goto_last_click();
+            }
+          });
+        });
+
+        ui.add_space(ui.spacing().item_spacing.y);
+        ui.separator();
+
+        ui.horizontal(|ui| {
+          let mut night_mode = self.night_mode;
+          if ui.checkbox(&mut night_mode, "Night Mode").clicked() {
+            self.set_night_mode(ctx, night_mode);
+          }
+        });
+
+        ui.horizontal(|ui| {
+          let mut grayscale = self.grayscale;
+          if ui.checkbox(&mut grayscale, "Grayscale").clicked() {
+            self.set_grayscale(grayscale);
+          }
+        });
+
+        ui.horizontal(|ui| {
+          let mut auto_load_aero = self.auto_load_aero;
+          let text = "Auto-load Airport Data";
+          if ui.checkbox(&mut auto_load_aero, text).clicked() {
+            self.auto_load_aero = auto_load_aero;
+            self.config.set_auto_load_aero(auto_load_aero);
+          }
+        })
+        .response
+        .on_hover_text("Load airport data from the chart's folder when opening a chart");
+
+        ui.horizontal(|ui| {
+          let mut auto_hide_panel = self.auto_hide_panel;
+          if ui
+            .checkbox(&mut auto_hide_panel, "Auto-hide Panel")
+            .clicked()
+          {
+            self.auto_hide_panel = auto_hide_panel;
+            self.config.set_auto_hide_panel(auto_hide_panel);
+          }
+        })
+        .response
+        .on_hover_text("Hide this panel after opening a chart");
+
+        ui.horizontal(|ui| {
+          let mut open_fit = self.open_fit;
+          if ui.checkbox(&mut open_fit, "Open Zoomed to Fit").clicked() {
+            self.open_fit = open_fit;
+            self.config.set_open_fit(open_fit);
+          }
+        })
+        .response
+        .on_hover_text("Open charts at the minimum zoom, which is also quicker to read");
+
+        ui.horizontal(|ui| {
+          let mut show_closed = self.show_closed;
+          if ui
+            .checkbox(&mut show_closed, "Show Closed Airports")
+            .clicked()
+          {
+            self.show_closed = show_closed;
+            self.config.set_show_closed(show_closed);
+          }
+        });
+
+        ui.horizontal(|ui| {
+          let mut show_private = self.show_private;
+          if ui
+            .checkbox(&mut show_private, "Show Private Airports")
+            .clicked()
+          {
+            self.show_private = show_private;
+            self.config.set_show_private(show_private);
+          }
+        })
+        .response
+        .on_hover_text("Private airports are marked with an \"R\" when shown");
+
+        ui.horizontal(|ui| {
+          let mut towered_only = self.towered_only;
+          let text = "Towered Airports Only";
+          if ui.checkbox(&mut towered_only, text).clicked() {
+            self.towered_only = towered_only;
+            self.config.set_towered_only(towered_only);
+          }
+        });
+
+        ui.horizontal(|ui| {
+          let mut show_center = self.show_center;
+          if ui.checkbox(&mut show_center, "Show View Center").clicked() {
+            self.show_center = show_center;
+            self.config.set_show_center(show_center);
+          }
+        });
+
+        ui.horizontal(|ui| {
+          let mut highlight_found = self.highlight_found;
+          let text = "Highlight Found Airport";
+          if ui.checkbox(&mut highlight_found, text).clicked() {
+            self.highlight_found = highlight_found;
+            self.config.set_highlight_found(highlight_found);
+            if !highlight_found {
+              self.highlight = None;
+            }
+          }
+        })
+        .response
+        .on_hover_text("Mark the airport that a search went to until it's scrolled away");
+
+        ui.horizontal(|ui| {
+          let mut low_power = self.low_power;
+          if ui.checkbox(&mut low_power, "Low Power Mode").clicked() {
+            self.low_power = low_power;
+            self.config.set_low_power(low_power);
+          }
+        })
+        .response
+        .on_hover_text("Show a static loading indicator instead of an animated one");
+
+        ui.horizontal(|ui| {
+          let mut cache_overview = self.cache_overview;
+          if ui
+            .checkbox(&mut cache_overview, "Cache Chart Overview")
+            .clicked()
+          {
+            self.cache_overview = cache_overview;
+            self.config.set_cache_overview(cache_overview);
+            self.update_read_options();
+          }
+        })
+        .response
+        .on_hover_text("Keep the zoomed to fit image in memory so that it shows instantly");
+
+        ui.horizontal(|ui| {
+          let mut chart_thumbnails = self.chart_thumbnails;
+          if ui
+            .checkbox(&mut chart_thumbnails, "Chart Thumbnails")
+            .clicked()
+          {
+            self.chart_thumbnails = chart_thumbnails;
+            self.config.set_chart_thumbnails(chart_thumbnails);
+          }
+        })
+        .response
+        .on_hover_text("Show small images when choosing a chart from a zip file");
+
+        ui.horizontal(|ui| {
+          let mut progressive = self.progressive;
+          if ui
+            .checkbox(&mut progressive, "Progressive Loading")
+            .clicked()
+          {
+            self.progressive = progressive;
+            self.config.set_progressive(progressive);
+            self.update_read_options();
+          }
+        })
+        .response
+        .on_hover_text("Show a low resolution image while the full image is read");
+
+        ui.horizontal(|ui| {
+          let mut show_magnetic = self.show_magnetic;
+          if ui
+            .checkbox(&mut show_magnetic, "Show Magnetic Bearings")
+            .clicked()
+          {
+            self.show_magnetic = show_magnetic;
+            self.config.set_show_magnetic(show_magnetic);
+          }
+        })
+        .response
+        .on_hover_text("Show magnetic bearings along with true bearings when measuring");
+
+        ui.horizontal(|ui| {
+          let mut long_press_feedback = self.long_press_feedback;
+          let text = "Long-press Feedback";
+          if ui.checkbox(&mut long_press_feedback, text).clicked() {
+            self.long_press_feedback = long_press_feedback;
+            self.config.set_long_press_feedback(long_press_feedback);
+          }
+        })
+        .response
+        .on_hover_text("Play a tick or vibration when a long-press is recognized");
+
+        ui.horizontal(|ui| {
+          ui.label("Sharpen");
+          let mut sharpen = self.sharpen;
+          let widget = egui::Slider::new(&mut sharpen, 0.0..=1.0).show_value(false);
+          let response = ui.add(widget);
+          if response.changed() {
+            self.set_sharpen(sharpen);
+          }
+
+          // Store the value once the user is done adjusting it.
+          if response.drag_stopped() || (response.changed() && !response.dragged()) {
+            self.config.set_sharpen(self.sharpen);
+          }
+        });
+
+        ui.add_space(ui.spacing().item_spacing.y);
+        ui.separator();
+
+        egui::Grid::new("display_prefs_grid")
+          .num_columns(2)
+          .show(ui, |ui| {
+            ui.label("Units");
+            let mut units = self.units;
+            egui::ComboBox::from_id_source("units_combo")
+              .selected_text(units.text())
+              .show_ui(ui, |ui| {
+                for item in util::Units::ALL {
+                  ui.selectable_value(&mut units, item, item.text());
+                }
+              });
+            if units != self.units {
+              self.units = units;
+              self.config.set_units(units);
+            }
+            ui.end_row();
+
+            ui.label("Coordinates");
+            let mut coord_format = self.coord_format;
+            egui::ComboBox::from_id_source("coord_format_combo")
+              .selected_text(coord_format.text())
+              .show_ui(ui, |ui| {
+                for item in util::CoordFormat::ALL {
+                  ui.selectable_value(&mut coord_format, item, item.text());
+                }
+              });
+            self.set_coord_format(coord_format);
+            ui.end_row();
+
+            ui.label("Image Limit")
+              .on_hover_text("Lower limits use less memory but make the chart less sharp");
+            let mut image_limit = self.image_limit;
+            egui::ComboBox::from_id_source("image_limit_combo")
+              .selected_text(image_limit_text(image_limit))
+              .show_ui(ui, |ui| {
+                for item in IMAGE_LIMITS {
+                  ui.selectable_value(&mut image_limit, item, image_limit_text(item));
+                }
+              });
+            if image_limit != self.image_limit {
+              self.image_limit = image_limit;
+              self.config.set_image_limit(image_limit);
+            }
+            ui.end_row();
+
+            let used: usize = self.chart_readers().map(|reader| reader.cache_used()).sum();
+            let text = format!(
+              "Recently viewed chart images are kept in memory up to this limit\nUsing {:.1} MB",
+              used as f64 / BYTES_PER_MB as f64
+            );
+            ui.label("Image Cache").on_hover_text(text);
+            let mut image_cache = self.image_cache;
+            egui::ComboBox::from_id_source("image_cache_combo")
+              .selected_text(image_cache_text(image_cache))
+              .show_ui(ui, |ui| {
+                for item in IMAGE_CACHE_SIZES {
+                  ui.selectable_value(&mut image_cache, item, image_cache_text(item));
+                }
+              });
+            if image_cache != self.image_cache {
+              self.image_cache = image_cache;
+              self.config.set_image_cache(image_cache);
+              self.update_read_options();
+            }
+            ui.end_row();
+
+            ui.label("Dim After")
+              .on_hover_text("Dim the display when there's no input for this long");
+            let mut dim_timeout = self.dim_timeout;
+            egui::ComboBox::from_id_source("dim_timeout_combo")
+              .selected_text(dim_timeout_text(dim_timeout))
+              .show_ui(ui, |ui| {
+                for item in DIM_TIMEOUTS {
+                  ui.selectable_value(&mut dim_timeout, item, dim_timeout_text(item));
+                }
+              });
+            if dim_timeout != self.dim_timeout {
+              self.dim_timeout = dim_timeout;
+              self.config.set_dim_timeout(dim_timeout);
+            }
+            ui.end_row();
+
+            ui.label("Dim Level");
+            let mut dim_level = self.dim_level;
+            ui.add_enabled_ui(self.dim_timeout > 0, |ui| {
+              egui::ComboBox::from_id_source("dim_level_combo")
+                .selected_text(format!("{dim_level}%"))
+                .show_ui(ui, |ui| {
+                  for item in DIM_LEVELS {
+                    ui.selectable_value(&mut dim_level, item, format!("{item}%"));
+                  }
+                });
+            });
+            if dim_level != self.dim_level {
+              self.dim_level = dim_level;
+              self.config.set_dim_level(dim_level);
+            }
+            ui.end_row();
+
+            ui.label("UI Theme")
+              .on_hover_text("Night mode only changes the chart colors unless this follows it");
+            let mut ui_theme = self.ui_theme;
+            egui::ComboBox::from_id_source("ui_theme_combo")
+              .selected_text(ui_theme.text())
+              .show_ui(ui, |ui| {
+                for item in util::UiTheme::ALL {
+                  ui.selectable_value(&mut ui_theme, item, item.text());
+                }
+              });
+            self.set_ui_theme(ctx, ui_theme);
+            ui.end_row();
+
+            ui.label("Pixel Snap")
+              .on_hover_text("When to snap the chart to whole pixels while scrolling");
+            let mut scroll_snap = self.scroll_snap;
+            egui::ComboBox::from_id_source("scroll_snap_combo")
+              .selected_text(scroll_snap.text())
+              .show_ui(ui, |ui| {
+                for item in util::ScrollSnap::ALL {
+                  ui.selectable_value(&mut scroll_snap, item, item.text());
+                }
+              });
+            if scroll_snap != self.scroll_snap {
+              self.scroll_snap = scroll_snap;
+              self.config.set_scroll_snap(scroll_snap);
+            }
+            ui.end_row();
+
+            ui.label("Background");
+            ui.horizontal(|ui| {
+              let mut color = self.bg_color.unwrap_or(DEFAULT_BG_COLOR);
+              if ui.color_edit_button_srgb(&mut color).changed() {
+                self.bg_color = Some(color);
+                self.config.set_bg_color(color);
+              }
+
+              ui.label("Dark");
+              let default = rgb(dark_theme().extreme_bg_color);
+              let mut color = self.night_bg_color.unwrap_or(default);
+              if ui.color_edit_button_srgb(&mut color).changed() {
+                self.night_bg_color = Some(color);
+                self.config.set_night_bg_color(color);
+              }
+            });
+            ui.end_row();
+
+            ui.label("Color Vision");
+            let mut color_vision = self.color_vision;
+            egui::ComboBox::from_id_source("color_vision_combo")
+              .selected_text(color_vision.text())
+              .show_ui(ui, |ui| {
+                for item in util::ColorVision::ALL {
+                  ui.selectable_value(&mut color_vision, item, item.text());
+                }
+              });
+            self.set_color_vision(color_vision);
+            ui.end_row();
+
+            ui.label("Emphasis")
+              .on_hover_text("Boost the colors of water or terrain for quick orientation");
+            let mut emphasis = self.emphasis;
+            egui::ComboBox::from_id_source("emphasis_combo")
+              .selected_text(emphasis.text())
+              .show_ui(ui, |ui| {
+                for item in util::Emphasis::ALL {
+                  ui.selectable_value(&mut emphasis, item, item.text());
+                }
+              });
+            self.set_emphasis(emphasis);
+            ui.end_row();
 
-    // Process chart raster replies.
-    for reply in self.get_chart_replies() {
-      match reply {
-        chart::RasterReply::Image(part, image) => {
-          self.set_chart_image(ctx, part, image);
-        }
-        chart::RasterReply::Error(_, err) => {
-          println!("{err}");
-        }
-      }
-    }
+            if self.emphasis != util::Emphasis::Off {
+              ui.label("Emphasis Indices")
+                .on_hover_text("Comma separated palette indices, or empty to pick them by color");
+              let edit = egui::TextEdit::singleline(&mut self.emphasis_text)
+                .hint_text("Automatic")
+                .desired_width(f32::INFINITY);
+              if ui.add(edit).lost_focus() {
+                match util::parse_indices(&self.emphasis_text) {
+                  Some(indices) => self.set_emphasis_indices(indices),
+                  None => self.emphasis_text = util::format_indices(&self.emphasis_indices),
+                }
+              }
+              ui.end_row();
+            }
 
-    // Process NASR airport replies.
-    for reply in self.get_airport_replies() {
-      match reply {
-        nasr::AirportReply::Airport(info) => {
-          self.goto_coord(info.coord);
-        }
-        nasr::AirportReply::Nearby(infos) => {
-          if !infos.is_empty() {
-            if let AirportInfos::Menu(_, airport_list) = &mut self.airport_infos {
-              *airport_list = Some(infos);
+            ui.label("Range Rings");
+            let mut range_rings = self.range_rings;
+            egui::ComboBox::from_id_source("range_rings_combo")
+              .selected_text(range_rings_text(range_rings, self.units))
+              .show_ui(ui, |ui| {
+                for item in RANGE_RINGS {
+                  let text = range_rings_text(item, self.units);
+                  ui.selectable_value(&mut range_rings, item, text);
+                }
+              });
+            if range_rings != self.range_rings {
+              self.range_rings = range_rings;
+              self.config.set_range_rings(range_rings);
             }
-          }
-        }
-        nasr::AirportReply::Search(infos) => match infos.len() {
-          0 => unreachable!(),
-          1 => self.goto_coord(infos[0].coord),
-          _ => self.airport_infos = AirportInfos::Dialog(infos),
-        },
-        nasr::AirportReply::Error(err) => {
-          self.error_dlg = Some(error_dlg::ErrorDlg::open(err));
-        }
-      }
-    }
+            ui.end_row();
 
-    // Show the file dialog if set.
-    if let Some(file_dlg) = &mut self.file_dlg {
-      if file_dlg.show(ctx).visible() {
-        self.ui_enabled = false;
-      } else {
-        if file_dlg.selected() {
-          if let Some(path) = file_dlg.path() {
-            // Save the folder path.
-            if let Some(path) = path.parent().and_then(|p| p.to_str()) {
-              self.config.set_asset_path(path.into());
-              self.asset_path = Some(path.into());
+            ui.label("Screen DPI")
+              .on_hover_text("Points per inch on this display, used for the chart scale");
+            let mut screen_dpi = self.screen_dpi;
+            ui.add(egui::DragValue::new(&mut screen_dpi).clamp_range(50..=600));
+            if screen_dpi != self.screen_dpi {
+              self.screen_dpi = screen_dpi;
+              self.config.set_screen_dpi(screen_dpi);
             }
+            ui.end_row();
 
-            let path = path.to_owned();
-            match util::get_zip_info(&path) {
-              Ok(info) => match info {
-                util::ZipInfo::Chart(files) => {
-                  if files.len() > 1 {
-                    self.chart = Chart::Load(path, files);
+            ui.label("Zoom Step");
+            let mut zoom_step = self.zoom_step;
+            egui::ComboBox::from_id_source("zoom_step_combo")
+              .selected_text(zoom_step.text())
+              .show_ui(ui, |ui| {
+                for item in util::ZoomStep::ALL {
+                  ui.selectable_value(&mut zoom_step, item, item.text());
+                }
+              });
+            if zoom_step != self.zoom_step {
+              self.zoom_step = zoom_step;
+              self.config.set_zoom_step(zoom_step);
+            }
+            ui.end_row();
 
-                    // Remove the chart spatial reference from the airport reader.
-                    if let Some(airport_reader) = &self.airport_reader {
-                      airport_reader.clear_spatial_ref();
-                    }
-                  } else {
-                    self.open_chart_data(ctx, &path, files.first().unwrap());
+            ui.label("Goto Position")
+              .on_hover_text("Where found locations are placed on the screen");
+            let mut goto_anchor = self.goto_anchor;
+            egui::ComboBox::from_id_source("goto_anchor_combo")
+              .selected_text(goto_anchor.text())
+              .show_ui(ui, |ui| {
+                for item in util::GotoAnchor::ALL {
+                  ui.selectable_value(&mut goto_anchor, item, item.text());
+                }
+              });
+            if goto_anchor != self.goto_anchor {
+              self.goto_anchor = goto_anchor;
+              self.config.set_goto_anchor(goto_anchor);
+            }
+            ui.end_row();
+
+            ui.label("Markers");
+            ui.horizontal(|ui| {
+              let mut marker_size = self.marker_size;
+              egui::ComboBox::from_id_source("marker_size_combo")
+                .selected_text(marker_size.text())
+                .show_ui(ui, |ui| {
+                  for item in util::MarkerSize::ALL {
+                    ui.selectable_value(&mut marker_size, item, item.text());
                   }
+                });
+              if marker_size != self.marker_size {
+                self.marker_size = marker_size;
+                self.config.set_marker_size(marker_size);
+              }
+
+              let mut marker_labels = self.marker_labels;
+              if ui.checkbox(&mut marker_labels, "Labels").clicked() {
+                self.marker_labels = marker_labels;
+                self.config.set_marker_labels(marker_labels);
+              }
+            });
+            ui.end_row();
+
+            ui.label("Long-press");
+            let mut long_press_action = self.long_press_action;
+            egui::ComboBox::from_id_source("long_press_combo")
+              .selected_text(long_press_action.text())
+              .show_ui(ui, |ui| {
+                for item in util::LongPressAction::ALL {
+                  ui.selectable_value(&mut long_press_action, item, item.text());
                 }
-                util::ZipInfo::Aero { csv, shp: _ } => {
-                  self.open_airport_data(ctx, &path, &csv);
+              });
+            if long_press_action != self.long_press_action {
+              self.long_press_action = long_press_action;
+              self.config.set_long_press_action(long_press_action);
+            }
+            ui.end_row();
+
+            ui.label("Escape")
+              .on_hover_text("What Escape does once there's nothing else to clear");
+            let mut escape_action = self.escape_action;
+            egui::ComboBox::from_id_source("escape_combo")
+              .selected_text(escape_action.text())
+              .show_ui(ui, |ui| {
+                for item in util::EscapeAction::ALL {
+                  ui.selectable_value(&mut escape_action, item, item.text());
                 }
-              },
-              Err(err) => {
-                self.error_dlg = Some(error_dlg::ErrorDlg::open(err));
-              }
+              });
+            if escape_action != self.escape_action {
+              self.escape_action = escape_action;
+              self.config.set_escape_action(escape_action);
             }
-          }
-        }
-        self.file_dlg = None;
-        self.ui_enabled = true;
-      }
-    }
+            ui.end_row();
+          });
 
-    // Show the selection dialog if there's a chart choice to be made.
-    if let Chart::Load(path, files) = &self.chart {
-      self.ui_enabled = false;
-      let choices = files.iter().map(|f| util::stem_str(f).unwrap());
-      if let Some(response) = self.select_dlg.show(ctx, choices) {
-        self.ui_enabled = true;
-        if let select_dlg::Response::Index(index) = response {
-          // Clone the parameters in order to avoid simultaneously borrowing self as immutable and mutable.
-          self.open_chart_data(ctx, &path.clone(), &files[index].clone());
-        } else {
-          self.chart = Chart::None;
-        }
-      }
-    }
+        ui.add_space(ui.spacing().item_spacing.y);
+        ui.separator();
 
-    // Show the selection dialog if there's an airport choice to be made.
-    if let AirportInfos::Dialog(infos) = &self.airport_infos {
-      self.ui_enabled = false;
-      let iter = infos.iter().map(|info| info.desc.as_str());
-      if let Some(response) = self.select_dlg.show(ctx, iter) {
-        self.ui_enabled = true;
-        if let select_dlg::Response::Index(index) = response {
-          self.goto_coord(infos[index].coord);
-        }
-        self.airport_infos = AirportInfos::None;
-      }
-    }
+        egui::CollapsingHeader::new("Nearby Airports").show(ui, |ui| {
+          self.request_nearby(ctx);
 
-    // Show the find dialog.
-    if let Some(find_dialog) = &mut self.find_dlg {
-      self.ui_enabled = false;
-      match find_dialog.show(ctx) {
-        find_dlg::Response::None => (),
-        find_dlg::Response::Cancel => {
-          self.ui_enabled = true;
-          self.find_dlg = None;
-        }
-        find_dlg::Response::Term(term) => {
-          self.ui_enabled = true;
-          self.find_dlg = None;
-          if let Some(nasr_reader) = &self.airport_reader {
-            nasr_reader.search(term, self.include_nph);
+          let mut goto = None;
+          let items = self.nearby.items.iter();
+          let mut items = items
+            .filter(|(_, _, info)| self.airport_visible(info))
+            .peekable();
+          if items.peek().is_none() {
+            let text = self.nearby_hint();
+            ui.label(egui::RichText::new(text).weak());
           }
-        }
-      }
-    }
 
-    // Show the error dialog if there's an error.
-    if let Some(error_dlg) = &mut self.error_dlg {
-      self.ui_enabled = false;
-      if !error_dlg.show(ctx) {
-        self.error_dlg = None;
-        self.ui_enabled = true;
-      }
-    }
+          egui::Grid::new("nearby_grid").striped(true).show(ui, |ui| {
+            for (dist, bearing, info) in items.take(NEARBY_LIMIT) {
+              if ui.button(&info.id).on_hover_text(&info.desc).clicked() {
+                goto = Some(info.coord);
+              }
 
-    // Show airport choices in a popup.
-    if let AirportInfos::Menu(lat_lon, infos) = &self.airport_infos {
-      let infos = infos.as_ref();
-      let iter = infos.map(|v| v.iter().map(|info| info.desc.as_str()));
-      if let Some(_response) = self.select_menu.show(ctx, lat_lon, iter) {
-        self.airport_infos = AirportInfos::None;
-      }
-    }
+              let dist = self.units.from_meters(*dist);
+              ui.label(format!("{dist:.1} {}", self.units.abv()));
+              ui.label(format!("{:03}°", bearing.round() as u32 % 360));
+              ui.end_row();
+            }
+          });
 
-    self.top_panel_height = top_panel(self.top_panel_height, ctx, |ui| {
-      ui.set_enabled(self.ui_enabled);
-      ui.horizontal_centered(|ui| {
-        let widget = egui::SelectableLabel::new(self.side_panel, " ⚙ ");
-        if ui.add_sized([0.0, 21.0], widget).clicked() {
-          self.toggle_side_panel(!self.side_panel);
-        }
+          if let Some(coord) = goto {
+            self.goto_coord(coord);
+          }
+        });
 
-        if let Some(nasr_reader) = &self.airport_reader {
-          if nasr_reader.airport_basic_idx() {
-            let text = 'text: {
-              const APT: &str = "APT";
-              if nasr_reader.request_count() > 0 {
-                ctx.output_mut(|state| state.cursor_icon = egui::CursorIcon::Progress);
-                break 'text egui::RichText::new(APT).strong();
+        egui::CollapsingHeader::new("Bookmarks").show(ui, |ui| {
+          let mut action = None;
+          let count = self.bookmarks.len();
+          for (index, bookmark) in self.bookmarks.iter().enumerate() {
+            ui.horizontal(|ui| {
+              if ui.button(&bookmark.name).clicked() {
+                action = Some(BookmarkAction::GoTo(index));
               }
-              egui::RichText::new(APT)
-            };
 
-            ui.separator();
-            ui.label(text);
+              ui.with_layout(egui::Layout::right_to_left(emath::Align::Center), |ui| {
+                if ui.button("🗑").clicked() {
+                  action = Some(BookmarkAction::Remove(index));
+                }
+
+                ui.add_enabled_ui(index + 1 < count, |ui| {
+                  if ui.button("⏷").clicked() {
+                    action = Some(BookmarkAction::MoveDown(index));
+                  }
+                });
+
+                ui.add_enabled_ui(index > 0, |ui| {
+                  if ui.button("⏶").clicked() {
+                    action = Some(BookmarkAction::MoveUp(index));
+                  }
+                });
+              });
+            });
           }
-        }
 
-        if let Chart::Ready(chart) = &mut self.chart {
-          if let Some(nasr_reader) = &self.airport_reader {
-            if nasr_reader.airport_spatial_idx() && ui.button("🔎").clicked() {
-              self.find_dlg = Some(find_dlg::FindDlg::open());
+          if count == 0 {
+            ui.label(egui::RichText::new("Bookmark airports from their details").weak());
+          }
+
+          let center = self.get_chart().and_then(|chart| chart.get_center_coord());
+          ui.add_enabled_ui(center.is_some(), |ui| {
+            if ui.button("Bookmark View Center").clicked() {
+              action = center.map(BookmarkAction::Add);
             }
+          });
+
+          let Some(action) = action else {
+            return;
+          };
+
+          match action {
+            BookmarkAction::GoTo(index) => {
+              let bookmark = &self.bookmarks[index];
+              let (name, coord) = (bookmark.name.clone(), bookmark.coord());
+              if !self.goto_coord(coord) {
+                let text = format!("{name} is not on this chart");
+                self.toast = Some(toast::Toast::new(text));
+              }
+              return;
+            }
+            BookmarkAction::Add(coord) => {
+              let name = self.coord_format.format(coord).unwrap_or_default();
+              self.bookmarks.push(util::Bookmark::new(name, coord));
+            }
+            BookmarkAction::Remove(index) => {
+              self.bookmarks.remove(index);
+            }
+            BookmarkAction::MoveUp(index) => self.bookmarks.swap(index, index - 1),
+            BookmarkAction::MoveDown(index) => self.bookmarks.swap(index, index + 1),
           }
 
-          ui.separator();
-          ui.label(&chart.name);
+          self.config.set_bookmarks(self.bookmarks.clone());
+        });
 
-          ui.with_layout(egui::Layout::right_to_left(emath::Align::Center), |ui| {
-            // Zoom-in button.
-            ui.add_enabled_ui(chart.zoom < 1.0, |ui| {
-              if let Some(font_id) = ui.style().text_styles.get(&egui::TextStyle::Monospace) {
-                let text = egui::RichText::new("+").font(font_id.clone());
-                let widget = egui::Button::new(text);
-                if ui.add_sized([21.0, 21.0], widget).clicked() {
-                  let new_zoom = (chart.zoom * 2.0).min(1.0);
-                  if new_zoom != chart.zoom {
-                    chart.scroll = Some(chart.get_zoom_pos(new_zoom).round());
-                    chart.zoom = new_zoom;
-                  }
-                }
+        egui::CollapsingHeader::new("Keyboard Shortcuts").show(ui, |ui| {
+          let escape = match self.escape_action {
+            util::EscapeAction::Panel => {
+              "Stop measuring, then remove the bearing line, highlight, airport menu and click \
+               marker, then close the side panel"
+            }
+            util::EscapeAction::Chart => {
+              "Stop measuring, then remove the bearing line, highlight, airport menu and click \
+               marker, then close the side panel, then close the chart"
+            }
+          };
+          let long_press = match self.long_press_action {
+            util::LongPressAction::Menu => "Open the context menu, with a finger or stylus",
+            util::LongPressAction::Recenter => "Center the chart there, with a finger or stylus",
+          };
+          let shortcuts = [
+            ("Ctrl+O", "Open a zip file"),
+            ("Ctrl+F", "Find an airport"),
+            ("Ctrl+J", "Go to the last clicked position"),
+            ("Ctrl+L", "Cycle the coordinate format"),
+            ("Ctrl+R", "Reload the chart"),
+            ("+ / -", "Zoom in or out"),
+            ("F11", "Toggle fullscreen"),
+            ("Ctrl+Q", "Quit"),
+            ("Escape", escape),
+            ("Long-press", long_press),
+          ];
+          egui::Grid::new("shortcuts_grid")
+            .striped(true)
+            .show(ui, |ui| {
+              for (keys, text) in shortcuts {
+                ui.label(keys);
+                ui.add(egui::Label::new(text).wrap(true));
+                ui.end_row();
               }
             });
+        });
 
-            // Zoom-out button.
-            let min_zoom = chart.get_min_zoom();
-            ui.add_enabled_ui(chart.zoom > min_zoom, |ui| {
-              if let Some(font_id) = ui.style().text_styles.get(&egui::TextStyle::Monospace) {
-                let text = egui::RichText::new("-").font(font_id.clone());
-                let widget = egui::Button::new(text);
-                if ui.add_sized([21.0, 21.0], widget).clicked() {
-                  let new_zoom = (chart.zoom * 0.5).max(min_zoom);
-                  if new_zoom != chart.zoom {
-                    chart.scroll = Some(chart.get_zoom_pos(new_zoom).round());
-                    chart.zoom = new_zoom;
-                  }
+        egui::CollapsingHeader::new("Palette Overrides").show(ui, |ui| {
+          let mut color_map = self.color_map.clone();
+          let mut remove = None;
+          for (index, color) in &mut color_map {
+            ui.horizontal(|ui| {
+              ui.label(format!("Index {index}"));
+              ui.with_layout(egui::Layout::right_to_left(emath::Align::Center), |ui| {
+                if ui.button("🗑").clicked() {
+                  remove = Some(*index);
                 }
-              }
+                ui.color_edit_button_srgba_unmultiplied(color);
+              });
             });
-          });
-        }
-      });
-    });
+          }
 
-    if self.side_panel {
-      self.side_panel_width = side_panel(self.side_panel_width, ctx, |ui| {
-        ui.set_enabled(self.ui_enabled);
+          if let Some(index) = remove {
+            color_map.remove(&index);
+          }
 
-        ui.horizontal(|ui| {
-          let button = egui::Button::new("Open Zip File");
-          if ui.add_sized(ui.available_size(), button).clicked() {
-            self.select_zip_file();
+          ui.horizontal(|ui| {
+            ui.add(egui::DragValue::new(&mut self.palette_index).prefix("Index "));
+            if ui.button("Add").clicked() {
+              // Start with the chart's color, if there is one.
+              let index = self.palette_index;
+              let reader = self.get_chart_reader();
+              let color = reader.map_or([255; 4], |reader| reader.palette_color(index));
+              color_map.entry(index).or_insert(color);
+            }
+          });
+
+          if color_map != self.color_map {
+            self.set_color_map(color_map);
           }
         });
 
         ui.add_space(ui.spacing().item_spacing.y);
         ui.separator();
 
+        if !MOBILE {
+          ui.horizontal(|ui| {
+            let button = egui::Button::new("Open Settings Folder");
+            let response = ui.add_sized(ui.available_size(), button);
+            let response = response.on_hover_text("Show the settings file in the file manager");
+            if response.clicked() {
+              self.open_settings_folder();
+            }
+          });
+        }
+
         ui.horizontal(|ui| {
-          let mut night_mode = self.night_mode;
-          if ui.checkbox(&mut night_mode, "Night Mode").clicked() {
-            self.set_night_mode(ctx, night_mode);
+          let button = egui::Button::new("Save Diagnostics");
+          let response = ui.add_sized(ui.available_size(), button);
+          let response = response.on_hover_text("Save version info and settings for a bug report");
+          if response.clicked() {
+            self.export_diagnostics();
+          }
+        });
+
+        ui.horizontal(|ui| {
+          let button = egui::Button::new("Reset Settings");
+          if ui.add_sized(ui.available_size(), button).clicked() {
+            let title = "Reset Settings".into();
+            let text = "Reset all settings to their defaults?".into();
+            self.reset_dlg = Some(confirm_dlg::ConfirmDlg::open(title, text));
           }
         });
       });
     }
 
+    self.show_split_view(ctx, &events);
+
     central_panel(ctx, self.side_panel, |ui| {
       ui.set_enabled(self.ui_enabled);
       if let Some(reader) = self.get_chart_reader() {
         let zoom = self.get_chart_zoom().unwrap();
         let scroll = self.take_chart_scroll();
-        let widget = if let Some(pos) = &scroll {
-          egui::ScrollArea::both().scroll_offset(pos.to_vec2())
-        } else {
-          egui::ScrollArea::both()
-        }
-        .scroll_bar_visibility(scroll_area::ScrollBarVisibility::AlwaysVisible);
-
-        ui.spacing_mut().scroll.bar_inner_margin = 0.0;
-
-        let response = widget.show(ui, |ui| {
-          let cursor_pos = ui.cursor().left_top();
-          let size = reader.transform().px_size();
-          let size = emath::vec2(size.w as f32, size.h as f32) * zoom;
-          let rect = emath::Rect::from_min_size(cursor_pos, size);
-
-          // Reserve space for the scroll bars.
-          ui.allocate_rect(rect, egui::Sense::hover());
-
-          // Place the image.
-          if let Some((part, texture)) = self.get_chart_texture() {
-            let scale = zoom * part.zoom.inverse();
-            let rect = util::scale_rect(part.rect.into(), scale);
-            let rect = rect.translate(cursor_pos.to_vec2());
-            ui.allocate_ui_at_rect(rect, |ui| {
-              let mut clip = ui.clip_rect();
-              clip.max -= emath::Vec2::splat(ui.spacing().scroll.bar_width * 0.5);
-              ui.set_clip_rect(clip);
-              ui.image((texture.id(), rect.size()));
-            });
-          }
-        });
+        let response = chart_scroll_area(ui, "chart_scroll", self.get_chart().unwrap(), scroll);
 
         // Set a new display rectangle.
         let pos = response.state.offset;
@@ -697,9 +3579,10 @@ impl eframe::App for App {
           size: response.inner_rect.size().into(),
         };
         self.set_chart_disp_rect(display_rect);
+        self.nearby.momentum = response.state.velocity() != emath::Vec2::ZERO;
 
         // Make sure the image position lands on an even pixel.
-        if response.state.velocity() == emath::vec2(0.0, 0.0) {
+        if self.should_snap(ctx, response.state.velocity()) {
           let floored = pos.floor();
           if floored != pos {
             self.set_chart_scroll(emath::pos2(floored.x, floored.y));
@@ -709,12 +3592,15 @@ impl eframe::App for App {
         // Get the minimum zoom.
         let min_zoom = self.get_chart().unwrap().get_min_zoom();
 
+        // The displayed image is stale until one matching the view arrives.
+        let mut stale = true;
         if let Some((part, _)) = self.get_chart_texture() {
           // Make sure the zoom is not below the minimum.
           let request_zoom = zoom.max(min_zoom);
 
           // Request a new image if needed.
-          if part.rect != display_rect || part.zoom != request_zoom.into() {
+          stale = *part != self.image_part(display_rect, request_zoom);
+          if stale {
             self.request_image(display_rect, request_zoom);
           }
 
@@ -722,11 +3608,104 @@ impl eframe::App for App {
             self.set_chart_zoom(request_zoom);
             ctx.request_repaint();
           }
-        } else if scroll.is_some() && zoom == 1.0 {
+        } else if mem::take(&mut self.initial_view) {
+          // Set up the view of a newly opened chart before requesting the initial image.
+          self.set_initial_view(min_zoom);
+          ctx.request_repaint();
+        } else if scroll.is_some() {
           // Request the initial image.
           self.request_image(display_rect, zoom);
         }
 
+        // Draw range rings around the center of the view.
+        if self.range_rings > 0 {
+          let center_px = self.get_chart().unwrap().get_center_px();
+          if let Some(meters) = px_meters(reader.transform(), center_px) {
+            let interval = self.units.to_meters(self.range_rings as f64);
+            let step = (interval / meters) as f32 * zoom;
+            let rect = response.inner_rect;
+            draw_range_rings(ui, rect, step, self.range_rings, self.units);
+          }
+        }
+
+        // Draw the bearing line.
+        if let Some((origin, bearing)) = self.bearing_line {
+          let transform = reader.transform();
+          let points = (0..=BEARING_LINE_STEPS).filter_map(|step| {
+            let dist = BEARING_LINE_LENGTH * step as f64 / BEARING_LINE_STEPS as f64;
+            let coord = util::gc_destination(origin, bearing, dist);
+            let px: emath::Pos2 = transform.nad83_to_px(coord).ok()?.into();
+            Some(response.inner_rect.min + px.to_vec2() * zoom - pos)
+          });
+          let points = points.collect();
+          let text = util::format_bearing(bearing, self.magnetic_variation(origin));
+          draw_bearing_line(ui, response.inner_rect, points, text);
+        }
+
+        // Mark the last clicked position.
+        if let Some(coord) = self.last_click {
+          if let Ok(px) = reader.transform().nad83_to_px(coord) {
+            let px: emath::Pos2 = px.into();
+            let pos = response.inner_rect.min + px.to_vec2() * zoom - pos;
+            draw_click_marker(ui, response.inner_rect, pos);
+          }
+        }
+
+        // Highlight the found airport until it's scrolled out of view.
+        if let Some((coord, id)) = &self.highlight {
+          let px = reader
+            .transform()
+            .nad83_to_px(*coord)
+            .map(emath::Pos2::from);
+          match px.map(|px| response.inner_rect.min + px.to_vec2() * zoom - pos) {
+            Ok(pos) if response.inner_rect.contains(pos) => {
+              draw_highlight(ui, response.inner_rect, pos, id.clone());
+            }
+            _ => self.highlight = None,
+          }
+        }
+
+        // Draw the airport markers.
+        self.request_markers();
+        if self.marker_size != util::MarkerSize::Off {
+          let markers = self.markers.items.iter().filter_map(|(px, info)| {
+            let marker_pos = response.inner_rect.min + px.to_vec2() * zoom - pos;
+            self.airport_visible(info).then_some((marker_pos, info))
+          });
+          let radius = self.marker_size.radius();
+          let labels = if self.marker_labels {
+            label_opacity(zoom)
+          } else {
+            0.0
+          };
+          draw_markers(ui, response.inner_rect, markers, radius, labels);
+
+          // Clicking (or tapping) a marker shows the airport's information, unless a measurement
+          // path is being added to.
+          let mut rect = response.inner_rect;
+          rect.max -= emath::Vec2::splat(ui.spacing().scroll.bar_width);
+          let click = ui.interact(rect, ui.id().with("marker_click"), egui::Sense::click());
+          let measuring = self.measure.as_ref().is_some_and(|measure| !measure.closed);
+          if let Some(click_pos) = click
+            .interact_pointer_pos()
+            .filter(|_| click.clicked() && !measuring)
+          {
+            // Allow some slop for small markers and touch screens.
+            let dist = radius.max(MARKER_HIT_DIST) / zoom;
+            let px = (click_pos - response.inner_rect.min + pos) / zoom;
+            let filter = |info: &nasr::AirportInfo| self.airport_visible(info);
+            if let Some(info) = self.markers.nearest(px.to_pos2(), dist, filter) {
+              let airport_dlg = airport_dlg::AirportDlg::open(info.clone(), self.coord_format);
+              self.airport_dlg = Some(airport_dlg);
+              self.reset_airport_menu();
+            }
+          }
+        }
+
+        if stale && reader.is_loading() {
+          draw_loading(ui, response.inner_rect, self.low_power);
+        }
+
         if let Some(zoom_pos) = events.zoom_pos {
           if response.inner_rect.contains(zoom_pos) {
             let new_zoom = zoom * events.zoom_mod;
@@ -735,13 +3714,125 @@ impl eframe::App for App {
               let new_zoom = new_zoom.clamp(min_zoom, 1.0);
               self.set_chart_zoom(new_zoom);
 
-              // Attempt to keep the point under the mouse cursor the same.
-              let zoom_pos = zoom_pos - response.inner_rect.min;
-              let pos = (pos + zoom_pos) * new_zoom / zoom - zoom_pos;
-              self.set_chart_scroll(pos.to_pos2().round());
+              // Attempt to keep the point under the mouse cursor the same.
+              let zoom_pos = zoom_pos - response.inner_rect.min;
+              let pos = (pos + zoom_pos) * new_zoom / zoom - zoom_pos;
+              self.set_chart_scroll(pos.to_pos2().round());
+
+              ctx.request_repaint();
+            }
+          }
+        }
+
+        // Draw the measurement path. While it's open, it continues to the cursor and clicking adds
+        // a point. Clicking the first point closes the path and shows the enclosed area.
+        if let Some(measure) = &self.measure {
+          let transform = reader.transform();
+          let points: Option<Vec<emath::Pos2>> = measure
+            .points
+            .iter()
+            .map(|coord| {
+              let px: emath::Pos2 = transform.nad83_to_px(*coord).ok()?.into();
+              Some(response.inner_rect.min + px.to_vec2() * zoom - pos)
+            })
+            .collect();
+
+          if let Some(mut points) = points {
+            let abv = self.units.abv();
+            if measure.closed {
+              let dist = self.units.from_meters(measure.length());
+              let area = area_text(self.units, util::polygon_area(&measure.points));
+              let text = format!("{dist:.1} {abv}, {area}");
+              draw_measure(ui, response.inner_rect, &points, true, text);
+            } else {
+              // Touch screens have no hover position, so the path only continues to the cursor
+              // when there is one.
+              let last = *measure.points.last().unwrap();
+              let length = measure.length();
+              let hover = ctx
+                .input(|state| state.pointer.hover_pos())
+                .filter(|hover_pos| response.inner_rect.contains(*hover_pos))
+                .and_then(|hover_pos| {
+                  let px = (hover_pos - response.inner_rect.min + pos) / zoom;
+                  let end = transform.px_to_nad83(px.into()).ok()?;
+                  Some((hover_pos, end))
+                });
+
+              let text = if let Some((hover_pos, end)) = hover {
+                let dist = self
+                  .units
+                  .from_meters(length + util::gc_distance(last, end));
+                let bearing = util::gc_bearing(last, end);
+                let bearing = util::format_bearing(bearing, self.magnetic_variation(last));
+                points.push(hover_pos);
+                format!("{dist:.1} {abv}, {bearing}")
+              } else {
+                points.push(*points.last().unwrap());
+                format!("{:.1} {abv}", self.units.from_meters(length))
+              };
+              draw_measure(ui, response.inner_rect, &points, false, text);
+
+              // The click position comes from the click itself because there's no hover position
+              // once a tap ends.
+              let mut rect = response.inner_rect;
+              rect.max -= emath::Vec2::splat(ui.spacing().scroll.bar_width);
+              let click = ui.interact(rect, ui.id().with("measure_click"), egui::Sense::click());
+              if let Some(click_pos) = click.interact_pointer_pos().filter(|_| click.clicked()) {
+                let first = points[0];
+                let px = (click_pos - response.inner_rect.min + pos) / zoom;
+                let coord = transform.px_to_nad83(px.into());
+                if let Some(measure) = &mut self.measure {
+                  if measure.points.len() > 2 && first.distance(click_pos) <= MARKER_HIT_DIST {
+                    measure.closed = true;
+                  } else if let Ok(coord) = coord {
+                    measure.points.push(coord);
+                  }
+                }
+              }
+            }
+          }
+        }
+
+        // Show the terrain elevation under the cursor.
+        if let Some((_, dem_reader)) = &self.dem_reader {
+          if let Some(hover_pos) = ctx.input(|state| state.pointer.hover_pos()) {
+            if response.inner_rect.contains(hover_pos) {
+              let px = (hover_pos - response.inner_rect.min + pos) / zoom;
+              if let Ok(coord) = reader.transform().px_to_nad83(px.into()) {
+                dem_reader.sample(coord);
+                if let Some(elevation) = dem_reader.elevation() {
+                  let text = elevation_text(self.units, elevation);
+                  draw_readout(ui, response.inner_rect, text);
+                }
+              }
+            }
+          }
+        }
+
+        // Offer to open the charts that continue past the edges of this one.
+        self.adjacent.update();
+        let view = response.inner_rect.size();
+        let rect = response.inner_rect.shrink(8.0);
+        for (align, text, zip, file) in self.adjacent_charts(pos, view) {
+          let id = egui::Id::new("adjacent_chart").with(align);
+          let pos = align.pos_in_rect(&rect);
+          let response = egui::Area::new(id)
+            .fixed_pos(pos)
+            .pivot(align)
+            .enabled(self.ui_enabled)
+            .show(ctx, |ui| {
+              ui.button(text).on_hover_text("Open the adjacent chart")
+            });
+          if response.inner.clicked() {
+            self.open_chart_data(ctx, &zip, &file);
+          }
+        }
 
-              ctx.request_repaint();
-            }
+        if let Some(press_pos) = events.recenter {
+          // Center the chart on the long-pressed position.
+          if response.inner_rect.contains(press_pos) {
+            let offset = pos + (press_pos - response.inner_rect.center());
+            self.set_chart_scroll(offset.to_pos2());
           }
         }
 
@@ -751,23 +3842,48 @@ impl eframe::App for App {
             let pos = (click_pos - response.inner_rect.min + pos) / zoom;
             let lcc = reader.transform().px_to_chart(pos.into());
             if let Ok(nad83) = reader.transform().chart_to_nad83(lcc) {
-              let lat = util::format_lat(nad83.y).unwrap();
-              let lon = util::format_lon(nad83.x).unwrap();
+              let lat_lon = self.coord_format.format(nad83).unwrap();
               self.select_menu.set_pos(click_pos);
-              self.airport_infos = AirportInfos::Menu(format!("{lat}, {lon}"), None);
+              self.last_click = Some(nad83);
+              self.airport_infos = AirportInfos::Menu(lat_lon, None);
               if let Some(nasr_reader) = &self.airport_reader {
+                // 1/2 nautical mile (926 meters) is the search radius at 1.0x zoom.
+                let radius = 926.0 / zoom as f64;
                 if nasr_reader.airport_spatial_idx() {
-                  // 1/2 nautical mile (926 meters) is the search radius at 1.0x zoom.
-                  let radius = 926.0 / zoom as f64;
                   nasr_reader.nearby(lcc, radius, self.include_nph);
+                } else if nasr_reader.airport_basic_idx() {
+                  // The spatial index isn't ready yet, so search by latitude and longitude.
+                  nasr_reader.nearby_nad83(nad83, radius, self.include_nph);
                 }
               }
             }
           }
         }
+      } else if matches!(self.chart, Chart::None) {
+        // Show a hint when there's no chart.
+        ui.vertical_centered(|ui| {
+          ui.add_space(ui.available_height() * 0.4);
+          ui.label(egui::RichText::new("Open a ZIP file to begin").weak());
+          if ui.button("Open Zip File").clicked() {
+            self.select_zip_file(ZipFilter::Any);
+          }
+        });
       }
     });
 
+    self.link_split(ctx);
+
+    if events.reload {
+      self.reload_chart(ctx);
+    }
+
+    self.dim_display(ctx, events.wake);
+
+    if let Some(fullscreen) = events.fullscreen {
+      ctx.send_viewport_cmd(egui::ViewportCommand::Fullscreen(fullscreen));
+      self.config.set_fullscreen(fullscreen);
+    }
+
     if events.quit {
       ctx.send_viewport_cmd(egui::ViewportCommand::Close);
     }
@@ -778,32 +3894,313 @@ impl eframe::App for App {
   }
 
   fn clear_color(&self, visuals: &egui::Visuals) -> [f32; 4] {
-    let color = if visuals.dark_mode {
-      visuals.extreme_bg_color
+    let [r, g, b] = if visuals.dark_mode {
+      self.night_bg_color.unwrap_or(rgb(visuals.extreme_bg_color))
     } else {
-      epaint::Color32::from_gray(220)
+      self.bg_color.unwrap_or(DEFAULT_BG_COLOR)
     };
 
     const CONV: f32 = 1.0 / 255.0;
-    [
-      color[0] as f32 * CONV,
-      color[1] as f32 * CONV,
-      color[2] as f32 * CONV,
-      color[3] as f32 * CONV,
-    ]
+    [r as f32 * CONV, g as f32 * CONV, b as f32 * CONV, 1.0]
+  }
+}
+
+/// Action to take on a chart tab.
+enum TabAction {
+  Select(usize),
+  Close(usize),
+}
+
+/// Action to take on a bookmark.
+#[derive(Clone, Copy)]
+enum BookmarkAction {
+  GoTo(usize),
+  Add(util::Coord),
+  Remove(usize),
+  MoveUp(usize),
+  MoveDown(usize),
+}
+
+/// Terrain elevation text for the readout.
+/// - `units`: distance units (metric shows meters, otherwise feet)
+/// - `meters`: elevation in meters
+fn elevation_text(units: util::Units, meters: f64) -> String {
+  if units == util::Units::Metric {
+    return format!("Elev {:.0} m", meters);
+  }
+  format!("Elev {:.0} ft", meters / util::METERS_PER_FOOT)
+}
+
+/// Get the GDAL path of the NASR airport CSV file.
+/// - `path`: NASR zip file or extracted CSV folder path
+/// - `zip`: path of the CSV zip within the NASR zip file
+fn airport_csv_path(path: &path::Path, zip: &path::Path) -> path::PathBuf {
+  if path.is_dir() {
+    // Extracted CSV folder.
+    return path.join("APT_BASE.csv");
+  }
+
+  // Concatenate the VSI prefix and the file path.
+  let path = ["/vsizip//vsizip/", path.to_str().unwrap()].concat();
+  let path = path::Path::new(path.as_str());
+  path.join(zip).join("APT_BASE.csv")
+}
+
+/// Reopen airport data from a previous session. Returns `None` if it's no longer there or can't be
+/// opened.
+/// - `path`: NASR zip file or extracted CSV folder path
+/// - `ctx`: egui context
+fn reopen_airport_data(path: &path::Path, ctx: &egui::Context) -> Option<nasr::AirportReader> {
+  if !path.exists() {
+    return None;
   }
+
+  let Ok(util::ZipInfo::Aero { csv, shp: _ }) = util::get_zip_info(path) else {
+    return None;
+  };
+
+  match nasr::AirportReader::new(airport_csv_path(path, &csv), ctx) {
+    Ok(nasr_reader) => Some(nasr_reader),
+    Err(err) => {
+      println!("{err}");
+      None
+    }
+  }
+}
+
+/// Get the text for the precision of a coordinate readout.
+/// - `units`: distance units
+/// - `meters`: precision in meters
+fn accuracy_text(units: util::Units, meters: f64) -> String {
+  if units == util::Units::Metric {
+    return format!("±{:.0} m", meters.max(1.0));
+  }
+  format!("±{:.0} ft", (meters / util::METERS_PER_FOOT).max(1.0))
+}
+
+/// Get the display text for an area.
+/// - `units`: distance units
+/// - `sq_meters`: area in square meters
+fn area_text(units: util::Units, sq_meters: f64) -> String {
+  const SQ_METERS_PER_ACRE: f64 = 4046.856_422_4;
+  const ACRES_PER_SQ_SM: f64 = 640.0;
+  match units {
+    util::Units::Nautical => format!(
+      "{:.1} sq NM",
+      sq_meters / (util::METERS_PER_NM * util::METERS_PER_NM)
+    ),
+    util::Units::Statute => {
+      let acres = sq_meters / SQ_METERS_PER_ACRE;
+      if acres < ACRES_PER_SQ_SM {
+        return format!("{acres:.0} acres");
+      }
+      format!("{:.1} sq SM", acres / ACRES_PER_SQ_SM)
+    }
+    util::Units::Metric => format!("{:.1} sq KM", sq_meters * 1.0e-6),
+  }
+}
+
+/// Type of zip file to show in the file dialog.
+#[derive(Clone, Copy)]
+enum ZipFilter {
+  Any,
+  Chart,
+  Aero,
 }
 
 enum AirportInfos {
   None,
   Menu(String, Option<Vec<nasr::AirportInfo>>),
-  Dialog(Vec<nasr::AirportInfo>),
+  Dialog(Vec<(nasr::SearchGroup, nasr::AirportInfo)>),
+}
+
+/// Airports shown as markers on the chart.
+#[derive(Default)]
+struct AirportMarkers {
+  /// Chart bounds of the most recent request.
+  request: Option<util::Bounds>,
+
+  /// Chart pixel positions and airport infos.
+  items: Vec<(emath::Pos2, nasr::AirportInfo)>,
+}
+
+impl AirportMarkers {
+  /// Find the airport with the marker nearest to a chart pixel position.
+  /// - `px`: chart pixel position
+  /// - `dist`: maximum distance in chart pixels
+  /// - `filter`: returns true for airports that can be selected
+  fn nearest(
+    &self,
+    px: emath::Pos2,
+    dist: f32,
+    filter: impl Fn(&nasr::AirportInfo) -> bool,
+  ) -> Option<&nasr::AirportInfo> {
+    let dsq = dist * dist;
+    let items = self.items.iter().filter(|(_, info)| filter(info));
+    let items = items.map(|(pos, info)| (pos.distance_sq(px), info));
+    let items = items.filter(|(item_dsq, _)| *item_dsq <= dsq);
+    let nearest = items.min_by(|(a, _), (b, _)| a.total_cmp(b));
+    nearest.map(|(_, info)| info)
+  }
+}
+
+/// Airports near the center of the view.
+#[derive(Default)]
+struct NearbyAirports {
+  /// Chart coordinate and search distance of the most recent request.
+  request: Option<(util::Coord, f64)>,
+
+  /// Chart coordinates of requests that haven't been replied to.
+  pending: Vec<util::Coord>,
+
+  /// Distance (meters), true bearing and airport info, sorted by distance.
+  items: Vec<(f64, f64, nasr::AirportInfo)>,
+
+  /// Chart coordinate and search distance of the view, and when it last changed.
+  view: Option<(util::Coord, f64, time::Instant)>,
+
+  /// True while the chart is scrolling.
+  momentum: bool,
+}
+
+impl NearbyAirports {
+  /// Set the airports from a nearby reply.
+  /// - `center`: NAD83 coordinate of the request center
+  /// - `infos`: nearby airports
+  fn set_items(&mut self, center: util::Coord, infos: Vec<nasr::AirportInfo>) {
+    let items = infos.into_iter().map(|info| {
+      let dist = util::gc_distance(center, info.coord);
+      let bearing = util::gc_bearing(center, info.coord);
+      (dist, bearing, info)
+    });
+    self.items = items.collect();
+    self.items.sort_unstable_by(|a, b| a.0.total_cmp(&b.0));
+  }
+
+  /// Track the view and get how much longer to wait before it's considered settled.
+  /// - `center`: chart coordinate of the view center
+  /// - `dist`: search distance in meters
+  fn settle_time(&mut self, center: util::Coord, dist: f64) -> time::Duration {
+    let now = time::Instant::now();
+    match self.view {
+      Some((view, view_dist, time)) if view == center && view_dist == dist => {
+        NEARBY_SETTLE_TIME.saturating_sub(now - time)
+      }
+      _ => {
+        self.view = Some((center, dist, now));
+        NEARBY_SETTLE_TIME
+      }
+    }
+  }
+}
+
+/// Magnetic variation at a location, taken from the closest airport.
+#[derive(Default)]
+struct MagVarLookup {
+  /// NAD83 coordinate of the location.
+  origin: Option<util::Coord>,
+
+  /// Chart coordinate of the nearby airports request that hasn't been replied to.
+  pending: Option<util::Coord>,
+
+  /// Magnetic variation in degrees, east is positive.
+  value: Option<f64>,
+}
+
+impl MagVarLookup {
+  /// Set the magnetic variation from the closest airport that has one.
+  /// - `infos`: airports near the origin
+  fn set_value(&mut self, infos: &[nasr::AirportInfo]) {
+    let Some(origin) = self.origin else {
+      return;
+    };
+
+    let infos = infos.iter().filter_map(|info| {
+      let var = info.mag_var?;
+      Some((util::gc_distance(origin, info.coord), var))
+    });
+    self.value = infos.min_by(|a, b| a.0.total_cmp(&b.0)).map(|(_, var)| var);
+  }
+}
+
+/// Coverage of the charts in the folder of the most recently opened chart.
+#[derive(Default)]
+struct AdjacentCharts {
+  folder: Option<path::PathBuf>,
+  reader: Option<chart::CoverageReader>,
+  charts: Vec<chart::Coverage>,
+}
+
+impl AdjacentCharts {
+  /// Start reading the coverage of the charts in a folder if it hasn't already been read.
+  /// - `folder`: chart folder
+  /// - `ctx`: egui context
+  fn scan(&mut self, folder: &path::Path, ctx: &egui::Context) {
+    if self.folder.as_deref() == Some(folder) {
+      return;
+    }
+
+    self.folder = Some(folder.to_owned());
+    self.charts.clear();
+    self.reader = Some(chart::CoverageReader::new(folder.to_owned(), ctx));
+  }
+
+  /// Collect the chart coverage that has been read.
+  fn update(&mut self) {
+    if let Some(reader) = &self.reader {
+      self.charts.extend(reader.get_replies());
+    }
+  }
+
+  /// Find a chart that covers a NAD83 coordinate.
+  /// - `coord`: NAD83 coordinate
+  /// - `exclude`: GDAL path of a chart to skip
+  fn find(&self, coord: util::Coord, exclude: &path::Path) -> Option<&chart::Coverage> {
+    self
+      .charts
+      .iter()
+      .find(|(zip, file, bounds)| bounds.contains(coord) && util::chart_path(zip, file) != exclude)
+  }
+}
+
+/// Multi-point measurement path.
+struct MeasurePath {
+  points: Vec<util::Coord>,
+  closed: bool,
+}
+
+impl MeasurePath {
+  /// Start a measurement path.
+  /// - `start`: NAD83 coordinate of the first point
+  fn new(start: util::Coord) -> Self {
+    Self {
+      points: vec![start],
+      closed: false,
+    }
+  }
+
+  /// Total length of the path, in meters, including the closing leg if it's closed.
+  fn length(&self) -> f64 {
+    let legs = self
+      .points
+      .windows(2)
+      .map(|pair| util::gc_distance(pair[0], pair[1]));
+    let closing = match (self.closed, self.points.first(), self.points.last()) {
+      (true, Some(first), Some(last)) => util::gc_distance(*last, *first),
+      _ => 0.0,
+    };
+    legs.sum::<f64>() + closing
+  }
 }
 
 struct InputEvents {
   zoom_mod: f32,
   zoom_pos: Option<emath::Pos2>,
   secondary_click: Option<emath::Pos2>,
+  recenter: Option<emath::Pos2>,
+  reload: bool,
+  wake: bool,
+  fullscreen: Option<bool>,
   quit: bool,
 }
 
@@ -820,6 +4217,10 @@ impl InputEvents {
       zoom_mod,
       zoom_pos,
       secondary_click: None,
+      recenter: None,
+      reload: false,
+      wake: false,
+      fullscreen: None,
       quit: false,
     }
   }
@@ -827,8 +4228,86 @@ impl InputEvents {
 
 const MIN_ZOOM: f32 = 1.0 / 8.0;
 
+/// Standard chart scale denominators: terminal area, sectional and world aeronautical charts.
+const CHART_SCALES: [f64; 3] = [250_000.0, 500_000.0, 1_000_000.0];
+
+/// Points per inch assumed for the display until the user sets it.
+const DEFAULT_SCREEN_DPI: u32 = 96;
+
+/// Meters in an inch.
+const METERS_PER_INCH: f64 = 0.0254;
+
+/// Minimum width or height, in pixels, of the whole chart image when zoomed out.
+const MIN_IMAGE_SIZE: f32 = 256.0;
+
+/// Minimum distance, in points, for a click or tap to select a marker.
+const MARKER_HIT_DIST: f32 = 12.0;
+
+/// Zoom below which airport labels are hidden.
+const LABEL_MIN_ZOOM: f32 = 0.375;
+
+/// Zoom at which airport labels are fully faded in.
+const LABEL_FULL_ZOOM: f32 = 0.5;
+
+/// Maximum search distance, in meters, for the nearby airports list.
+const NEARBY_MAX_DIST: f64 = 50.0 * util::METERS_PER_NM;
+
+/// Search distance, in meters, for the airport that provides the magnetic variation.
+const MAG_VAR_SEARCH_DIST: f64 = NEARBY_MAX_DIST;
+
+/// Maximum number of airports shown in the nearby airports list.
+const NEARBY_LIMIT: usize = 20;
+
+/// How long the view has to stay still before the nearby airports list is updated.
+const NEARBY_SETTLE_TIME: time::Duration = time::Duration::from_millis(250);
+
+/// Default background color (RGB) shown around the chart with the light theme.
+const DEFAULT_BG_COLOR: [u8; 3] = [220, 220, 220];
+
+/// True if this is a mobile build.
+const MOBILE: bool = cfg!(feature = "mobile");
+
+/// Choices for the range ring interval, in the selected units. Zero means no rings.
+const RANGE_RINGS: [u32; 5] = [0, 5, 10, 20, 50];
+
+/// Length of the bearing line in meters (300 nautical miles).
+const BEARING_LINE_LENGTH: f64 = 300.0 * util::METERS_PER_NM;
+
+/// Number of segments used to draw the bearing line.
+const BEARING_LINE_STEPS: u32 = 60;
+
+/// Minimum distance, in points, between range rings.
+const MIN_RING_STEP: f32 = 8.0;
+
+/// Choices for the chart image limit, in megapixels. Zero means no limit.
+const IMAGE_LIMITS: [u32; 5] = [0, 2, 4, 8, 16];
+
+/// Choices for the chart image cache size, in megabytes. Zero means no cache.
+const IMAGE_CACHE_SIZES: [u32; 5] = [0, 64, 128, 256, 512];
+
+/// Distance, in points, from the edge of the chart at which adjacent charts are offered.
+const ADJACENT_EDGE_DIST: f32 = 32.0;
+
+/// Distance, in pixels, past the edge of the chart to look for adjacent charts.
+const ADJACENT_PROBE_DIST: f32 = 16.0;
+
+/// Choices for how long, in minutes, to wait before dimming the display. Zero means never.
+const DIM_TIMEOUTS: [u32; 5] = [0, 1, 2, 5, 10];
+
+/// Choices for how much, in percent, to darken a dimmed display.
+const DIM_LEVELS: [u32; 3] = [25, 50, 75];
+
+/// Default amount, in percent, to darken a dimmed display.
+const DEFAULT_DIM_LEVEL: u32 = 50;
+
+/// Bytes per megabyte.
+const BYTES_PER_MB: usize = 1024 * 1024;
+
 struct ChartInfo {
   name: String,
+  title: String,
+  path: path::PathBuf,
+  dem_path: Option<path::PathBuf>,
   reader: rc::Rc<chart::RasterReader>,
   texture: Option<(chart::ImagePart, egui::TextureHandle)>,
   disp_rect: util::Rect,
@@ -842,7 +4321,20 @@ impl ChartInfo {
     let disp_size: emath::Vec2 = self.disp_rect.size.into();
     let sw = disp_size.x / chart_size.x;
     let sh = disp_size.y / chart_size.y;
-    sw.max(sh).max(MIN_ZOOM)
+
+    // Keep the zoomed out image from becoming uselessly small (or empty) for a small chart.
+    let min_image = (MIN_IMAGE_SIZE / chart_size.min_elem()).min(1.0);
+    sw.max(sh).max(MIN_ZOOM).max(min_image)
+  }
+
+  /// Zoom by a factor while keeping the center of the view in place.
+  /// - `factor`: zoom multiplier
+  fn zoom_by(&mut self, factor: f32) {
+    let new_zoom = (self.zoom * factor).clamp(self.get_min_zoom(), 1.0);
+    if new_zoom != self.zoom {
+      self.scroll = Some(self.get_zoom_pos(new_zoom).round());
+      self.zoom = new_zoom;
+    }
   }
 
   fn get_zoom_pos(&self, zoom: f32) -> emath::Pos2 {
@@ -854,20 +4346,149 @@ impl ChartInfo {
     let y = ratio * (pos.y + offset.y) - offset.y;
     emath::pos2(x, y)
   }
+
+  /// Get the chart pixel coordinate at the center of the display.
+  fn get_center_px(&self) -> util::Coord {
+    let pos: emath::Pos2 = self.disp_rect.pos.into();
+    let size: emath::Vec2 = self.disp_rect.size.into();
+    ((pos.to_vec2() + size * 0.5) / self.zoom).into()
+  }
+
+  /// Get the NAD83 bounds of the display from its corners.
+  fn get_view_bounds(&self) -> Option<util::Bounds> {
+    let rect: emath::Rect = self.disp_rect.into();
+    let corners = [
+      rect.left_top(),
+      rect.right_top(),
+      rect.left_bottom(),
+      rect.right_bottom(),
+    ];
+
+    let transform = self.reader.transform();
+    let mut bounds: Option<util::Bounds> = None;
+    for corner in corners {
+      let coord = transform.px_to_nad83((corner / self.zoom).into()).ok()?;
+      let bounds = bounds.get_or_insert(util::Bounds {
+        min: coord,
+        max: coord,
+      });
+      bounds.min.x = bounds.min.x.min(coord.x);
+      bounds.min.y = bounds.min.y.min(coord.y);
+      bounds.max.x = bounds.max.x.max(coord.x);
+      bounds.max.y = bounds.max.y.max(coord.y);
+    }
+    bounds
+  }
+
+  /// Get the NAD83 coordinate at the center of the display.
+  fn get_center_coord(&self) -> Option<util::Coord> {
+    let px = self.get_center_px();
+    self.reader.transform().px_to_nad83(px).ok()
+  }
+
+  /// Get the ground distance, in meters, covered by one screen pixel at the center of the view.
+  fn get_accuracy(&self) -> Option<f64> {
+    let meters = px_meters(self.reader.transform(), self.get_center_px())?;
+    Some(meters / self.zoom as f64)
+  }
+
+  /// Get the display scale denominator at the center of the view, e.g. 500,000 for 1:500,000.
+  /// - `dpi`: display points per inch
+  fn get_scale(&self, dpi: f64) -> Option<f64> {
+    let meters = px_meters(self.reader.transform(), self.get_center_px())?;
+    Some(meters * dpi / (self.zoom as f64 * METERS_PER_INCH))
+  }
+
+  /// Get the zoom that shows the center of the view at a display scale.
+  /// - `scale`: scale denominator
+  /// - `dpi`: display points per inch
+  fn get_scale_zoom(&self, scale: f64, dpi: f64) -> Option<f32> {
+    let meters = px_meters(self.reader.transform(), self.get_center_px())?;
+    Some((meters * dpi / (scale * METERS_PER_INCH)) as f32)
+  }
+
+  /// Get the scroll position and zoom that show the same location and scale as another chart.
+  /// - `other`: chart to match
+  fn get_link_view(&self, other: &ChartInfo) -> Option<(emath::Pos2, f32)> {
+    let other_px = other.get_center_px();
+    let coord = other.reader.transform().px_to_nad83(other_px).ok()?;
+    let px = self.reader.transform().nad83_to_px(coord).ok()?;
+
+    // Compare the ground distance covered by a pixel on each chart.
+    let ratio =
+      px_meters(self.reader.transform(), px)? / px_meters(other.reader.transform(), other_px)?;
+    let zoom = (other.zoom * ratio as f32).clamp(self.get_min_zoom(), 1.0);
+
+    // Keep the scroll position within the chart.
+    let px: emath::Pos2 = px.into();
+    let size: emath::Vec2 = self.disp_rect.size.into();
+    let chart_size: emath::Vec2 = self.reader.transform().px_size().into();
+    let max = (chart_size * zoom - size).max(emath::Vec2::ZERO);
+    let scroll = (px.to_vec2() * zoom - size * 0.5).clamp(emath::Vec2::ZERO, max);
+    Some((scroll.round().to_pos2(), zoom))
+  }
 }
 
 enum Chart {
   None,
   Load(path::PathBuf, Vec<path::PathBuf>),
+  Open(Box<ChartOpen>),
   Ready(Box<ChartInfo>),
 }
 
+/// A chart that's in the process of being opened.
+struct ChartOpen {
+  opener: chart::RasterOpener,
+  progress_dlg: progress_dlg::ProgressDlg,
+  name: String,
+  path: path::PathBuf,
+  folder: Option<path::PathBuf>,
+
+  /// Zip file path and the chart files that this chart was chosen from.
+  choices: Option<(path::PathBuf, Vec<path::PathBuf>)>,
+}
+
+/// Get the visuals for a UI theme.
+/// - `theme`: UI theme
+/// - `night_mode`: true if night mode is on
+/// - `default_theme`: visuals used when not in night mode
+fn theme_visuals(
+  theme: util::UiTheme,
+  night_mode: bool,
+  default_theme: &egui::Visuals,
+) -> egui::Visuals {
+  match theme {
+    util::UiTheme::Auto if night_mode => dark_theme(),
+    util::UiTheme::Auto => default_theme.clone(),
+    util::UiTheme::Light => egui::Visuals::light(),
+    util::UiTheme::Dark => dark_theme(),
+  }
+}
+
 fn dark_theme() -> egui::Visuals {
   let mut visuals = egui::Visuals::dark();
   visuals.extreme_bg_color = epaint::Color32::from_gray(20);
   visuals
 }
 
+/// Get the ground distance, in meters, covered by a chart pixel.
+/// - `transform`: chart transformation
+/// - `px`: chart pixel coordinate
+fn px_meters(transform: &chart::Transform, px: util::Coord) -> Option<f64> {
+  // Measure over a span of pixels for better precision.
+  const SPAN: f64 = 100.0;
+  let start = transform.px_to_nad83(px).ok()?;
+  let end = transform.px_to_nad83((px.x + SPAN, px.y).into()).ok()?;
+  let meters = util::gc_distance(start, end) / SPAN;
+  (meters > 0.0).then_some(meters)
+}
+
+/// Get the RGB components of a color.
+fn rgb(color: epaint::Color32) -> [u8; 3] {
+  let [r, g, b, _] = color.to_array();
+  [r, g, b]
+}
+
 fn top_panel<R>(
   height: u32,
   ctx: &egui::Context,
@@ -924,6 +4545,401 @@ fn side_panel<R>(
   response.response.rect.width().ceil() as u32
 }
 
+/// Button that was clicked in a data status row.
+#[derive(Clone, Copy, Eq, PartialEq)]
+enum DataButton {
+  File,
+  Folder,
+}
+
+/// Show a data status row.
+/// - `title`: data title
+/// - `status`: status text
+/// - `folder`: also show a button for opening a folder
+fn data_status(
+  ui: &mut egui::Ui,
+  title: &str,
+  status: egui::RichText,
+  folder: bool,
+) -> Option<DataButton> {
+  ui.horizontal(|ui| {
+    ui.label(title);
+    ui.with_layout(egui::Layout::right_to_left(emath::Align::Center), |ui| {
+      let mut clicked = ui.button("📂").clicked().then_some(DataButton::File);
+      if folder {
+        let response = ui.button("🗀").on_hover_text("Open an extracted folder");
+        if response.clicked() {
+          clicked = Some(DataButton::Folder);
+        }
+      }
+      ui.label(status);
+      clicked
+    })
+    .inner
+  })
+  .inner
+}
+
+/// Get the display text for a range ring interval.
+fn range_rings_text(interval: u32, units: util::Units) -> String {
+  if interval == 0 {
+    return "Off".into();
+  }
+  format!("{interval} {}", units.abv())
+}
+
+/// Get the display text for a chart image limit.
+fn image_limit_text(megapixels: u32) -> String {
+  if megapixels == 0 {
+    return "None".into();
+  }
+  format!("{megapixels} MP")
+}
+
+/// Get the display text for a dim timeout.
+fn dim_timeout_text(minutes: u32) -> String {
+  if minutes == 0 {
+    return "Never".into();
+  }
+  format!("{minutes} min")
+}
+
+/// Get the display text for a chart image cache size.
+fn image_cache_text(megabytes: u32) -> String {
+  if megabytes == 0 {
+    return "Off".into();
+  }
+  format!("{megabytes} MB")
+}
+
+/// Draw a measurement path with a label.
+/// - `clip`: clip rectangle
+/// - `points`: path points
+/// - `closed`: fill the enclosed area and label its center instead of the end point
+/// - `text`: label text
+fn draw_measure(
+  ui: &egui::Ui,
+  clip: emath::Rect,
+  points: &[emath::Pos2],
+  closed: bool,
+  text: String,
+) {
+  let painter = ui.painter().with_clip_rect(clip);
+  let visuals = ui.visuals();
+  let stroke = epaint::Stroke::new(2.0, visuals.selection.bg_fill);
+  if closed {
+    // egui only fills convex shapes, so fill the area with triangles. Areas that cross themselves
+    // are only outlined.
+    if let Some(indices) = util::triangulate(points) {
+      let fill = stroke.color.gamma_multiply(0.25);
+      let mut mesh = epaint::Mesh::default();
+      for point in points {
+        mesh.colored_vertex(*point, fill);
+      }
+      mesh.indices = indices;
+      painter.add(mesh);
+    }
+    painter.add(epaint::Shape::closed_line(points.to_vec(), stroke));
+  } else {
+    painter.add(epaint::Shape::line(points.to_vec(), stroke));
+  }
+
+  for point in points.iter().take(points.len() - usize::from(!closed)) {
+    painter.circle_filled(*point, 4.0, stroke.color);
+  }
+
+  // Place the label just above and to the right of the end point or the center of the area.
+  let end = if closed {
+    let sum = points
+      .iter()
+      .fold(emath::Vec2::ZERO, |sum, point| sum + point.to_vec2());
+    (sum / points.len() as f32).to_pos2()
+  } else {
+    *points.last().unwrap()
+  };
+  let color = visuals.strong_text_color();
+  let font_id = egui::TextStyle::Body.resolve(ui.style());
+  let galley = painter.layout_no_wrap(text, font_id, color);
+  let pos = end + emath::vec2(8.0, -8.0);
+  let rect = emath::Align2::LEFT_BOTTOM.anchor_size(pos, galley.size());
+  painter.rect_filled(rect.expand(4.0), 4.0, visuals.extreme_bg_color);
+  painter.galley(rect.min, galley, color);
+}
+
+/// Show a loading indicator in the upper right corner of the chart area.
+/// - `rect`: chart area rectangle
+/// - `low_power`: show a static indicator instead of an animated spinner
+fn draw_loading(ui: &mut egui::Ui, rect: emath::Rect, low_power: bool) {
+  let size = emath::Vec2::splat(16.0);
+  let right = rect.max.x - ui.spacing().scroll.bar_width - 8.0;
+  let min = emath::pos2(right - size.x, rect.min.y + 8.0);
+  let rect = emath::Rect::from_min_size(min, size);
+  if low_power {
+    // The spinner requests a repaint every frame.
+    ui.put(rect, egui::Label::new("⏳"));
+  } else {
+    ui.put(rect, egui::Spinner::new());
+  }
+}
+
+/// Draw a highlight ring around an airport with its ID above it.
+/// - `clip`: clip rectangle
+/// - `pos`: airport position
+/// - `text`: label text
+fn draw_highlight(ui: &egui::Ui, clip: emath::Rect, pos: emath::Pos2, text: String) {
+  const RADIUS: f32 = 16.0;
+  let painter = ui.painter().with_clip_rect(clip);
+  let visuals = ui.visuals();
+  painter.circle_stroke(
+    pos,
+    RADIUS,
+    epaint::Stroke::new(3.0, visuals.selection.bg_fill),
+  );
+
+  let color = visuals.strong_text_color();
+  let font_id = egui::TextStyle::Body.resolve(ui.style());
+  let galley = painter.layout_no_wrap(text, font_id, color);
+  let pos = pos - emath::vec2(0.0, RADIUS + 6.0);
+  let rect = emath::Align2::CENTER_BOTTOM.anchor_size(pos, galley.size());
+  painter.rect_filled(rect.expand(4.0), 4.0, visuals.extreme_bg_color);
+  painter.galley(rect.min, galley, color);
+}
+
+/// Draw a cross marking a clicked position.
+/// - `clip`: clip rectangle
+/// - `pos`: clicked position
+fn draw_click_marker(ui: &egui::Ui, clip: emath::Rect, pos: emath::Pos2) {
+  const SIZE: f32 = 8.0;
+  let painter = ui.painter().with_clip_rect(clip);
+  let visuals = ui.visuals();
+  let stroke = epaint::Stroke::new(2.0, visuals.selection.bg_fill);
+  painter.line_segment(
+    [pos - emath::vec2(SIZE, 0.0), pos + emath::vec2(SIZE, 0.0)],
+    stroke,
+  );
+  painter.line_segment(
+    [pos - emath::vec2(0.0, SIZE), pos + emath::vec2(0.0, SIZE)],
+    stroke,
+  );
+}
+
+/// Draw a readout label in the lower left corner of the chart area.
+/// - `clip`: chart area rectangle
+/// - `text`: label text
+fn draw_readout(ui: &egui::Ui, clip: emath::Rect, text: String) {
+  let painter = ui.painter().with_clip_rect(clip);
+  let visuals = ui.visuals();
+  let color = visuals.strong_text_color();
+  let font_id = egui::TextStyle::Body.resolve(ui.style());
+  let galley = painter.layout_no_wrap(text, font_id, color);
+  let pos = clip.left_bottom() + emath::vec2(8.0, -8.0);
+  let rect = emath::Align2::LEFT_BOTTOM.anchor_size(pos, galley.size());
+  painter.rect_filled(rect.expand(4.0), 4.0, visuals.extreme_bg_color);
+  painter.galley(rect.min, galley, color);
+}
+
+/// Draw a bearing line with a label at its origin.
+/// - `clip`: clip rectangle
+/// - `points`: screen positions along the line, starting at the origin
+/// - `text`: label text
+fn draw_bearing_line(ui: &egui::Ui, clip: emath::Rect, points: Vec<emath::Pos2>, text: String) {
+  let Some(&start) = points.first() else {
+    return;
+  };
+
+  let painter = ui.painter().with_clip_rect(clip);
+  let visuals = ui.visuals();
+  let stroke = epaint::Stroke::new(2.0, visuals.selection.bg_fill);
+  painter.add(epaint::Shape::line(points, stroke));
+  painter.circle_filled(start, 4.0, stroke.color);
+
+  // Place the label just below and to the right of the origin.
+  let color = visuals.strong_text_color();
+  let font_id = egui::TextStyle::Body.resolve(ui.style());
+  let galley = painter.layout_no_wrap(text, font_id, color);
+  let pos = start + emath::vec2(8.0, 8.0);
+  let rect = emath::Align2::LEFT_TOP.anchor_size(pos, galley.size());
+  painter.rect_filled(rect.expand(4.0), 4.0, visuals.extreme_bg_color);
+  painter.galley(rect.min, galley, color);
+}
+
+/// Draw concentric range rings, labeled with their distances, around the center of a rectangle.
+/// - `rect`: rectangle to center the rings in and clip to
+/// - `step`: distance between rings in points
+/// - `interval`: distance between rings in `units`
+/// - `units`: distance units for the labels
+fn draw_range_rings(
+  ui: &egui::Ui,
+  rect: emath::Rect,
+  step: f32,
+  interval: u32,
+  units: util::Units,
+) {
+  // Don't draw rings that are too close together to be useful.
+  if step < MIN_RING_STEP {
+    return;
+  }
+
+  let painter = ui.painter().with_clip_rect(rect);
+  let visuals = ui.visuals();
+  let stroke = epaint::Stroke::new(1.5, visuals.selection.bg_fill);
+  let color = visuals.strong_text_color();
+  let font_id = egui::TextStyle::Small.resolve(ui.style());
+  let center = rect.center();
+  let max = center.distance(rect.min);
+  let mut radius = step;
+  let mut dist = interval;
+  while radius <= max {
+    painter.circle_stroke(center, radius, stroke);
+
+    // Label the ring at the top.
+    let text = format!("{dist} {}", units.abv());
+    let galley = painter.layout_no_wrap(text, font_id.clone(), color);
+    let pos = center - emath::vec2(0.0, radius);
+    let label = emath::Align2::CENTER_CENTER.anchor_size(pos, galley.size());
+    painter.rect_filled(label.expand(2.0), 2.0, visuals.extreme_bg_color);
+    painter.galley(label.min, galley, color);
+
+    radius += step;
+    dist += interval;
+  }
+}
+
+/// Draw airport markers with optional ID labels.
+/// - `clip`: clip rectangle
+/// - `markers`: screen positions and airport infos
+/// - `radius`: marker radius
+/// - `labels`: opacity of the airport ID labels, zero to hide them
+fn draw_markers<'a>(
+  ui: &egui::Ui,
+  clip: emath::Rect,
+  markers: impl Iterator<Item = (emath::Pos2, &'a nasr::AirportInfo)>,
+  radius: f32,
+  labels: f32,
+) {
+  let painter = ui.painter().with_clip_rect(clip);
+  let visuals = ui.visuals();
+  let stroke = epaint::Stroke::new(1.5, visuals.strong_text_color());
+  let font_id = egui::TextStyle::Small.resolve(ui.style());
+  let cull = clip.expand(radius);
+  let markers: Vec<_> = markers.filter(|(pos, _)| cull.contains(*pos)).collect();
+  for (pos, info) in &markers {
+    let fill = if info.is_closed() {
+      visuals.weak_text_color()
+    } else if info.is_private() {
+      visuals.warn_fg_color
+    } else {
+      visuals.selection.bg_fill
+    };
+    painter.circle(*pos, radius, fill, stroke);
+  }
+
+  if labels <= 0.0 {
+    return;
+  }
+
+  // Labels are placed so that they don't cover markers or each other.
+  let size = emath::vec2(radius, radius) * 2.0;
+  let mut taken: Vec<emath::Rect> = markers
+    .iter()
+    .map(|(pos, _)| emath::Rect::from_center_size(*pos, size))
+    .collect();
+  let color = visuals.strong_text_color().gamma_multiply(labels);
+  let bg_color = visuals.extreme_bg_color.gamma_multiply(labels);
+  for (pos, info) in &markers {
+    // Private airports are flagged with an "R".
+    let text = if info.is_private() {
+      format!("{} (R)", info.id)
+    } else {
+      info.id.clone()
+    };
+
+    // Prefer the right side of the marker, then the left.
+    let galley = painter.layout_no_wrap(text, font_id.clone(), color);
+    let offset = emath::vec2(radius + 4.0, 0.0);
+    let sides = [
+      emath::Align2::LEFT_CENTER.anchor_size(*pos + offset, galley.size()),
+      emath::Align2::RIGHT_CENTER.anchor_size(*pos - offset, galley.size()),
+    ];
+    let own = emath::Rect::from_center_size(*pos, size);
+    let free = sides.into_iter().find(|rect| {
+      let rect = rect.expand(2.0);
+      !taken
+        .iter()
+        .any(|other| *other != own && other.intersects(rect))
+    });
+
+    if let Some(rect) = free {
+      painter.rect_filled(rect.expand(2.0), 2.0, bg_color);
+      painter.galley(rect.min, galley, color);
+      taken.push(rect.expand(2.0));
+    }
+  }
+}
+
+/// Get the opacity of the airport labels at a zoom level. Labels fade in as the chart is zoomed in.
+/// - `zoom`: chart zoom
+fn label_opacity(zoom: f32) -> f32 {
+  ((zoom - LABEL_MIN_ZOOM) / (LABEL_FULL_ZOOM - LABEL_MIN_ZOOM)).clamp(0.0, 1.0)
+}
+
+/// Show a chart's image in a scroll area.
+/// - `id`: scroll area ID source
+/// - `chart`: chart to show
+/// - `scroll`: new scroll position, if any
+fn chart_scroll_area(
+  ui: &mut egui::Ui,
+  id: &str,
+  chart: &ChartInfo,
+  scroll: Option<emath::Pos2>,
+) -> scroll_area::ScrollAreaOutput<()> {
+  let zoom = chart.zoom;
+  let widget = if let Some(pos) = &scroll {
+    egui::ScrollArea::both().scroll_offset(pos.to_vec2())
+  } else {
+    egui::ScrollArea::both()
+  }
+  .id_source(id)
+  .scroll_bar_visibility(scroll_area::ScrollBarVisibility::AlwaysVisible);
+
+  ui.spacing_mut().scroll.bar_inner_margin = 0.0;
+
+  widget.show(ui, |ui| {
+    let cursor_pos = ui.cursor().left_top();
+    let size = chart.reader.transform().px_size();
+    let size = emath::vec2(size.w as f32, size.h as f32) * zoom;
+    let rect = emath::Rect::from_min_size(cursor_pos, size);
+
+    // Reserve space for the scroll bars.
+    ui.allocate_rect(rect, egui::Sense::hover());
+
+    // Place the image.
+    if let Some((part, texture)) = &chart.texture {
+      let scale = zoom * part.zoom.inverse();
+      let rect = util::scale_rect(part.rect.into(), scale);
+      let rect = rect.translate(cursor_pos.to_vec2());
+      ui.allocate_ui_at_rect(rect, |ui| {
+        let mut clip = ui.clip_rect();
+        clip.max -= emath::Vec2::splat(ui.spacing().scroll.bar_width * 0.5);
+        ui.set_clip_rect(clip);
+        ui.image((texture.id(), rect.size()));
+      });
+    }
+  })
+}
+
+/// Get the tab index of an inactive chart.
+/// - `index`: index of the chart in the inactive charts
+/// - `active_tab`: tab index of the active chart
+/// - `active`: true if there's an active chart
+fn inactive_tab(index: usize, active_tab: usize, active: bool) -> usize {
+  // The active chart's tab isn't in the inactive charts.
+  if active && index >= active_tab {
+    return index + 1;
+  }
+  index
+}
+
 fn central_panel<R>(ctx: &egui::Context, left: bool, contents: impl FnOnce(&mut egui::Ui) -> R) {
   let available = ctx.available_rect();
   let left = if left { 1.0 } else { 0.0 };
@@ -944,3 +4960,25 @@ fn central_panel<R>(ctx: &egui::Context, left: bool, contents: impl FnOnce(&mut
     contents(ui);
   });
 }
+
+mod test {
+  #[test]
+  fn test_inactive_tab() {
+    // Tabs are A, B, C, D with B active, so C and D are to the right of the active tab.
+    let active_tab = 1;
+    let mut charts = vec!["A", "C", "D"];
+
+    // Reopening D activates its tab. Activating stashes B back in its tab first.
+    let index = charts.iter().position(|chart| *chart == "D").unwrap();
+    let tab = super::inactive_tab(index, active_tab, true);
+    assert!(tab == 3);
+    charts.insert(active_tab, "B");
+    assert!(charts.remove(tab) == "D");
+
+    // Charts to the left of the active tab keep their index.
+    assert!(super::inactive_tab(0, active_tab, true) == 0);
+
+    // Without an active chart, nothing is stashed.
+    assert!(super::inactive_tab(2, active_tab, false) == 2);
+  }
+}
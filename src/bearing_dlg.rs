@@ -0,0 +1,84 @@
+use crate::util;
+use eframe::{egui, emath};
+use std::mem;
+
+pub struct BearingDlg {
+  origin: util::Coord,
+  text: String,
+  focus: bool,
+}
+
+#[derive(PartialEq)]
+pub enum Response {
+  None,
+  Cancel,
+  /// Bearing in degrees true.
+  Bearing(f64),
+}
+
+impl BearingDlg {
+  /// Open the bearing dialog.
+  /// - `origin`: NAD83 coordinate that the bearing line starts from
+  pub fn open(origin: util::Coord) -> Self {
+    Self {
+      origin,
+      text: String::new(),
+      focus: true,
+    }
+  }
+
+  pub fn origin(&self) -> util::Coord {
+    self.origin
+  }
+
+  pub fn show(&mut self, ctx: &egui::Context) -> Response {
+    let key = util::dlg_key(ctx);
+    let mut response = Response::None;
+    let mut open = key != util::DlgKey::Cancel;
+    let bearing = self.bearing();
+
+    egui::Window::new(egui::RichText::from("🧭  Bearing Line").strong())
+      .open(&mut open)
+      .collapsible(false)
+      .resizable(false)
+      .anchor(emath::Align2::CENTER_CENTER, [0.0, 0.0])
+      .default_width(150.0)
+      .show(ctx, |ui| {
+        ui.add_space(8.0);
+        ui.horizontal(|ui| {
+          let widget = egui::TextEdit::singleline(&mut self.text).hint_text("Degrees true");
+          let edit_response = ui.add_sized(ui.available_size(), widget);
+          if mem::take(&mut self.focus) {
+            edit_response.request_focus();
+          }
+        });
+        ui.add_space(8.0);
+        ui.separator();
+        ui.horizontal(|ui| {
+          ui.add_enabled_ui(bearing.is_some(), |ui| {
+            if ui.button("Ok").clicked() {
+              response = Response::Bearing(bearing.unwrap());
+            }
+          });
+
+          if ui.button("Cancel").clicked() {
+            response = Response::Cancel;
+          }
+        });
+      });
+
+    if !open {
+      response = Response::Cancel;
+    } else if let (util::DlgKey::Confirm, Some(bearing)) = (key, bearing) {
+      response = Response::Bearing(bearing);
+    }
+
+    response
+  }
+
+  /// Parse the bearing text. Returns `None` if it's not a valid bearing.
+  fn bearing(&self) -> Option<f64> {
+    let bearing = self.text.trim().parse::<f64>().ok()?;
+    (0.0..=360.0).contains(&bearing).then_some(bearing % 360.0)
+  }
+}
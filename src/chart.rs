@@ -1,12 +1,25 @@
 use crate::util;
 use eframe::{egui, epaint};
 use gdal::{raster, spatial_ref};
-use std::{any, path, sync::mpsc, thread};
+use std::{any, cell, collections, iter, mem, path, sync, thread};
+use sync::{atomic, mpsc};
+
+/// Reads larger than this (in pixels) are preceded by a low resolution image when progressive reads
+/// are enabled.
+const PREVIEW_MIN_PIXELS: u64 = 1_000_000;
+
+/// Resolution of the low resolution image relative to the full read.
+const PREVIEW_SCALE: f32 = 0.25;
 
 /// RasterReader is used for opening and reading [VFR charts](https://www.faa.gov/air_traffic/flight_info/aeronav/digital_products/vfr/) in zipped GEO-TIFF format.
 pub struct RasterReader {
   transform: Transform,
-  tx: mpsc::Sender<ImagePart>,
+  title: Option<String>,
+  palette: Vec<[u8; 4]>,
+  pending: cell::RefCell<Option<ImagePart>>,
+  failed: cell::RefCell<Option<ImagePart>>,
+  cache_used: sync::Arc<atomic::AtomicUsize>,
+  tx: util::WorkerSender<RasterRequest>,
   rx: mpsc::Receiver<RasterReply>,
 }
 
@@ -20,66 +33,165 @@ impl RasterReader {
 
   fn _new(path: &path::Path, ctx: egui::Context) -> Result<Self, util::Error> {
     // Open the chart source.
-    let (source, transform, palette) = RasterSource::open(path)?;
+    let (source, palette) = RasterSource::open(path)?;
+    RasterReader::from_source(source, palette, ctx)
+  }
+
+  fn from_source(
+    source: RasterSource,
+    palette: Vec<raster::RgbaEntry>,
+    ctx: egui::Context,
+  ) -> Result<Self, util::Error> {
+    let transform = source.transform()?;
+    let title = source.title();
+
+    // Keep a copy of the original colors for the UI.
+    let colors = palette
+      .iter()
+      .map(|c| [c.r, c.g, c.b, c.a].map(|v| v as u8));
+    let colors = colors.collect();
 
     // Create the communication channels.
     let (tx, trx) = mpsc::channel();
     let (ttx, rx) = mpsc::channel();
+    let cache_used = sync::Arc::new(atomic::AtomicUsize::new(0));
 
     // Create the thread.
     thread::Builder::new()
       .name(any::type_name::<RasterReader>().to_owned())
-      .spawn(move || {
-        // Convert the color palette.
-        let light: Vec<epaint::Color32> = palette.iter().map(util::color).collect();
-        let dark: Vec<epaint::Color32> = palette.iter().map(util::inverted_color).collect();
-        drop(palette);
-
-        // Wait for a message. Exit when the connection is closed.
-        while let Ok(request) = trx.recv() {
-          let mut part = request;
-
-          // GDAL doesn't have any way to cancel a raster read operation and the
-          // requests can pile up during a long read, so grab all the pending
-          // requests in order to get to the most recent.
-          while let Ok(request) = trx.try_recv() {
-            part = request;
-          }
+      .spawn({
+        let cache_used = cache_used.clone();
+        move || {
+          // Convert the color palette.
+          let vision = util::ColorVision::Normal;
+          let color_map = util::ColorMap::new();
+          let emphasis = util::Emphasis::Off;
+          let (mut light, mut dark) = convert_palette(&palette, vision, &color_map, emphasis, &[]);
+
+          // The most recent image of the whole chart, kept so that zooming out to fit is instant.
+          let mut cache_overview = true;
+          let mut overview: Option<(ImagePart, epaint::ColorImage)> = None;
+
+          // Send a low resolution image before the full resolution one.
+          let mut progressive = false;
+
+          // Recently read images. Disabled until a budget is set.
+          let mut cache = ImageCache::new(0);
 
-          // Read the image data.
-          match source.read(&part) {
-            Ok(gdal_image) => {
-              let (w, h) = gdal_image.size;
-              let mut image = epaint::ColorImage {
-                size: [w, h],
-                pixels: Vec::with_capacity(w * h),
-              };
-
-              // Choose the palette.
-              let colors = if part.dark { &dark } else { &light };
-
-              // Convert the image to RGBA.
-              for val in gdal_image.data {
-                image.pixels.push(colors[val as usize]);
+          // Wait for a message. Exit when the connection is closed.
+          while let Ok(request) = trx.recv() {
+            // GDAL doesn't have any way to cancel a raster read operation and the
+            // requests can pile up during a long read, so grab all the pending
+            // requests in order to get to the most recent image request.
+            let mut part = None;
+            for request in iter::once(request).chain(trx.try_iter()) {
+              match request {
+                RasterRequest::Colors(vision, color_map, emphasis, indices) => {
+                  (light, dark) = convert_palette(&palette, vision, &color_map, emphasis, &indices);
+                  overview = None;
+                  cache.clear();
+                }
+                RasterRequest::CacheBudget(budget) => cache.set_budget(budget),
+                RasterRequest::CacheOverview(enabled) => {
+                  cache_overview = enabled;
+                  if !enabled {
+                    overview = None;
+                  }
+                }
+                RasterRequest::Progressive(enabled) => progressive = enabled,
+                RasterRequest::Image(request) => part = Some(request),
               }
+            }
 
-              // Send it.
-              ttx.send(RasterReply::Image(part, image)).unwrap();
+            cache_used.store(cache.used(), atomic::Ordering::Relaxed);
+            let Some(part) = part else {
+              continue;
+            };
 
-              // Request a repaint here so that the main thread will wake up and get the message.
+            // Use the cached overview if it matches.
+            if let Some((_, image)) = overview.as_ref().filter(|(cached, _)| *cached == part) {
+              if ttx.send(RasterReply::Image(part, image.clone())).is_err() {
+                break;
+              }
               ctx.request_repaint();
+              continue;
             }
-            Err(err) => {
-              let text = format!("{err}");
-              ttx.send(RasterReply::Error(part, text.into())).unwrap();
+
+            // Use a recently read image if there's one.
+            if let Some(image) = cache.get(&part) {
+              if ttx.send(RasterReply::Image(part, image.clone())).is_err() {
+                break;
+              }
+              ctx.request_repaint();
+              continue;
+            }
+
+            // GDAL can't read into an empty buffer.
+            let size = part.rect.size;
+            if !size.is_valid() {
+              let text = "Unable to read chart: invalid image size";
+              if ttx.send(RasterReply::Error(part, text.into())).is_err() {
+                break;
+              }
               ctx.request_repaint();
+              continue;
+            }
+
+            // Send a quick low resolution image first if the read is large.
+            if progressive && size.w as u64 * size.h as u64 > PREVIEW_MIN_PIXELS {
+              let preview = part.scaled(PREVIEW_SCALE);
+              let colors = if preview.dark { &dark } else { &light };
+              if let Ok(image) = source.read_image(&preview, colors) {
+                if ttx.send(RasterReply::Image(preview, image)).is_err() {
+                  break;
+                }
+                ctx.request_repaint();
+              }
+            }
+
+            // Read the image data.
+            let colors = if part.dark { &dark } else { &light };
+            match source.read_image(&part, colors) {
+              Ok(image) => {
+                // Keep a copy if this is the whole chart, otherwise add it to the cache.
+                if cache_overview && source.is_overview(&part) {
+                  overview = Some((part.clone(), image.clone()));
+                } else {
+                  cache.insert(part.clone(), image.clone());
+                  cache_used.store(cache.used(), atomic::Ordering::Relaxed);
+                }
+
+                // Send it. The receiver is gone if the reader was dropped.
+                if ttx.send(RasterReply::Image(part, image)).is_err() {
+                  break;
+                }
+
+                // Request a repaint here so that the main thread will wake up and get the message.
+                ctx.request_repaint();
+              }
+              Err(err) => {
+                let text = format!("{err}");
+                if ttx.send(RasterReply::Error(part, text.into())).is_err() {
+                  break;
+                }
+                ctx.request_repaint();
+              }
             }
           }
         }
       })
       .unwrap();
 
-    Ok(Self { transform, tx, rx })
+    Ok(Self {
+      transform,
+      title,
+      palette: colors,
+      pending: cell::RefCell::new(None),
+      failed: cell::RefCell::new(None),
+      cache_used,
+      tx: util::WorkerSender::new(tx),
+      rx,
+    })
   }
 
   /// Get the transformation.
@@ -87,27 +199,212 @@ impl RasterReader {
     &self.transform
   }
 
-  /// Kick-off an image read operation.
+  /// Get the chart title from the chart's metadata, if it has one.
+  pub fn title(&self) -> Option<&str> {
+    self.title.as_deref()
+  }
+
+  /// Get the original (unmultiplied RGBA) color of a palette entry.
+  /// - `index`: palette index
+  pub fn palette_color(&self, index: u8) -> [u8; 4] {
+    self.palette[index as usize]
+  }
+
+  /// Change the palette colors for subsequent image reads.
+  /// - `vision`: color vision type to remap the colors for
+  /// - `color_map`: palette index to color overrides
+  /// - `emphasis`: chart features to make stand out
+  /// - `indices`: palette indices of the emphasized features, or empty to pick them by color
+  pub fn set_colors(
+    &self,
+    vision: util::ColorVision,
+    color_map: util::ColorMap,
+    emphasis: util::Emphasis,
+    indices: Vec<u8>,
+  ) {
+    // Outstanding reads will have the old colors.
+    self.pending.replace(None);
+    self.failed.replace(None);
+    self
+      .tx
+      .send(RasterRequest::Colors(vision, color_map, emphasis, indices));
+  }
+
+  /// Enable or disable caching of the whole chart image. The cache is cleared when the colors
+  /// change.
+  /// - `enabled`: true to cache the overview image
+  pub fn set_cache_overview(&self, enabled: bool) {
+    self.tx.send(RasterRequest::CacheOverview(enabled));
+  }
+
+  /// Enable or disable reading a low resolution image before each large image read.
+  /// - `enabled`: true to send a low resolution image first
+  pub fn set_progressive(&self, enabled: bool) {
+    self.tx.send(RasterRequest::Progressive(enabled));
+  }
+
+  /// Set the memory budget for recently read images. Least recently used images are evicted when the
+  /// budget is exceeded.
+  /// - `budget`: budget in bytes, zero disables the cache
+  pub fn set_cache_budget(&self, budget: usize) {
+    self.tx.send(RasterRequest::CacheBudget(budget));
+  }
+
+  /// Memory used by recently read images, in bytes.
+  pub fn cache_used(&self) -> usize {
+    self.cache_used.load(atomic::Ordering::Relaxed)
+  }
+
+  /// Kick-off an image read operation. Does nothing if the same part is already being read or if
+  /// reading it failed.
   /// - `part`: the area to read from the source image.
   pub fn read_image(&self, part: ImagePart) {
-    self.tx.send(part).unwrap();
+    let part = Some(part);
+    if part == *self.pending.borrow() || part == *self.failed.borrow() {
+      return;
+    }
+
+    if self.tx.send(RasterRequest::Image(part.clone().unwrap())) {
+      self.pending.replace(part);
+    }
+  }
+
+  /// False if the reader's thread has exited and it can't read any more images.
+  pub fn is_available(&self) -> bool {
+    self.tx.is_available()
+  }
+
+  /// Returns true if an image read is in progress.
+  pub fn is_loading(&self) -> bool {
+    self.pending.borrow().is_some()
   }
 
   /// Get all available replies.
   pub fn get_replies(&self) -> Vec<RasterReply> {
-    self.rx.try_iter().collect()
+    let replies: Vec<RasterReply> = self.rx.try_iter().collect();
+    for reply in &replies {
+      let (part, failed) = match reply {
+        RasterReply::Image(part, _) => (part, false),
+        RasterReply::Error(part, _) => (part, true),
+      };
+
+      if self.pending.borrow().as_ref() == Some(part) {
+        self.pending.replace(None);
+        if failed {
+          self.failed.replace(Some(part.clone()));
+        }
+      }
+    }
+    replies
   }
 }
 
+enum RasterRequest {
+  /// Change the palette colors.
+  Colors(util::ColorVision, util::ColorMap, util::Emphasis, Vec<u8>),
+
+  /// Enable or disable the overview image cache.
+  CacheOverview(bool),
+
+  /// Enable or disable low resolution images ahead of large reads.
+  Progressive(bool),
+
+  /// Set the image cache memory budget.
+  CacheBudget(usize),
+
+  /// Read part of the image.
+  Image(ImagePart),
+}
+
 pub enum RasterReply {
   /// Image result from a read operation.
   Image(ImagePart, epaint::ColorImage),
 
   /// Error message from a read operation.
-  #[allow(dead_code)]
   Error(ImagePart, util::Error),
 }
 
+/// Least recently used cache of chart images, limited by memory use.
+struct ImageCache {
+  budget: usize,
+  used: usize,
+  entries: collections::VecDeque<(ImagePart, epaint::ColorImage)>,
+}
+
+impl ImageCache {
+  /// Create a new image cache.
+  /// - `budget`: memory budget in bytes
+  fn new(budget: usize) -> Self {
+    Self {
+      budget,
+      used: 0,
+      entries: collections::VecDeque::new(),
+    }
+  }
+
+  /// Memory used by an image, in bytes.
+  fn image_size(image: &epaint::ColorImage) -> usize {
+    image.pixels.len() * mem::size_of::<epaint::Color32>()
+  }
+
+  /// Memory used by the cached images, in bytes.
+  fn used(&self) -> usize {
+    self.used
+  }
+
+  /// Change the memory budget, evicting images if needed.
+  /// - `budget`: memory budget in bytes
+  fn set_budget(&mut self, budget: usize) {
+    self.budget = budget;
+    self.evict();
+  }
+
+  /// Get a cached image and mark it as the most recently used.
+  /// - `part`: image part
+  fn get(&mut self, part: &ImagePart) -> Option<&epaint::ColorImage> {
+    let index = self.entries.iter().position(|(cached, _)| cached == part)?;
+    let entry = self.entries.remove(index)?;
+    self.entries.push_back(entry);
+    self.entries.back().map(|(_, image)| image)
+  }
+
+  /// Add an image to the cache. Images that are larger than the budget are not cached.
+  /// - `part`: image part
+  /// - `image`: image data
+  fn insert(&mut self, part: ImagePart, image: epaint::ColorImage) {
+    let size = Self::image_size(&image);
+    if size > self.budget {
+      return;
+    }
+
+    if let Some(index) = self.entries.iter().position(|(cached, _)| *cached == part) {
+      if let Some((_, old)) = self.entries.remove(index) {
+        self.used -= Self::image_size(&old);
+      }
+    }
+
+    self.entries.push_back((part, image));
+    self.used += size;
+    self.evict();
+  }
+
+  /// Remove all the images.
+  fn clear(&mut self) {
+    self.entries.clear();
+    self.used = 0;
+  }
+
+  /// Remove the least recently used images until the memory use is within budget.
+  fn evict(&mut self) {
+    while self.used > self.budget {
+      let Some((_, image)) = self.entries.pop_front() else {
+        break;
+      };
+      self.used -= Self::image_size(&image);
+    }
+  }
+}
+
 /// Transformations between pixel, chart (LCC) and NAD83 coordinates.
 pub struct Transform {
   px_size: util::Size,
@@ -155,6 +452,11 @@ impl Transform {
     self.spatial_ref.to_proj4().unwrap()
   }
 
+  /// Get the spatial reference as well known text (WKT).
+  pub fn get_wkt(&self) -> String {
+    self.spatial_ref.to_pretty_wkt().unwrap()
+  }
+
   /// Get the full size of the chart in pixels.
   pub fn px_size(&self) -> util::Size {
     self.px_size
@@ -197,7 +499,6 @@ impl Transform {
 
   /// Convert a pixel coordinate to a NAD83 coordinate.
   /// - `coord`: pixel coordinate
-  #[allow(unused)]
   pub fn px_to_nad83(&self, coord: util::Coord) -> Result<util::Coord, gdal::errors::GdalError> {
     self.chart_to_nad83(self.px_to_chart(coord))
   }
@@ -210,24 +511,378 @@ impl Transform {
   }
 }
 
+/// RasterOpener opens a chart raster on a separate thread so that opening a very large file doesn't
+/// block the UI.
+pub struct RasterOpener {
+  rx: mpsc::Receiver<Result<(RasterSource, Vec<raster::RgbaEntry>), util::Error>>,
+  ctx: egui::Context,
+}
+
+impl RasterOpener {
+  /// Start opening a chart.
+  /// > **NOTE**: GDAL can't abort an open operation, so dropping the opener only discards the
+  /// > result once the thread finishes.
+  /// - `path`: chart file path
+  /// - `ctx`: egui context for requesting a repaint
+  pub fn new<P: AsRef<path::Path>>(path: P, ctx: &egui::Context) -> Self {
+    let path = path.as_ref().to_owned();
+    let ctx = ctx.clone();
+    let (tx, rx) = mpsc::channel();
+    thread::Builder::new()
+      .name(any::type_name::<RasterOpener>().to_owned())
+      .spawn({
+        let ctx = ctx.clone();
+        move || {
+          // The receiver is gone if the open was canceled.
+          if tx.send(RasterSource::open(&path)).is_ok() {
+            ctx.request_repaint();
+          }
+        }
+      })
+      .unwrap();
+
+    Self { rx, ctx }
+  }
+
+  /// Get the chart raster reader once the chart has been opened.
+  pub fn get(&self) -> Option<Result<RasterReader, util::Error>> {
+    match self.rx.try_recv() {
+      Ok(result) => Some(result.and_then(|(source, palette)| {
+        RasterReader::from_source(source, palette, self.ctx.clone())
+      })),
+      Err(mpsc::TryRecvError::Empty) => None,
+      Err(mpsc::TryRecvError::Disconnected) => Some(Err("Unable to open chart".into())),
+    }
+  }
+}
+
+/// ThumbnailReader reads small images of whole charts on a separate thread.
+pub struct ThumbnailReader {
+  rx: mpsc::Receiver<(path::PathBuf, epaint::ColorImage)>,
+}
+
+impl ThumbnailReader {
+  /// Start reading thumbnails. Reading stops early if the reader is dropped.
+  /// - `paths`: chart file paths
+  /// - `size`: maximum thumbnail width or height, in pixels
+  /// - `ctx`: egui context for requesting a repaint
+  pub fn new(paths: Vec<path::PathBuf>, size: u32, ctx: &egui::Context) -> Self {
+    let ctx = ctx.clone();
+    let (tx, rx) = mpsc::channel();
+    thread::Builder::new()
+      .name(any::type_name::<ThumbnailReader>().to_owned())
+      .spawn(move || {
+        for path in paths {
+          let image = RasterSource::open(&path).and_then(|(source, palette)| {
+            let image = source.thumbnail(&palette, size);
+            image.map_err(|err| format!("{err}").into())
+          });
+
+          match image {
+            Ok(image) => {
+              // The receiver is gone if the reader was dropped.
+              if tx.send((path, image)).is_err() {
+                break;
+              }
+              ctx.request_repaint();
+            }
+            Err(err) => println!("{err}"),
+          }
+        }
+      })
+      .unwrap();
+
+    Self { rx }
+  }
+
+  /// Get the thumbnails that have been read since the last call.
+  pub fn get_replies(&self) -> Vec<(path::PathBuf, epaint::ColorImage)> {
+    self.rx.try_iter().collect()
+  }
+}
+
+/// Coverage of a chart: the zip file, the chart file within it and the chart's NAD83 bounds.
+pub type Coverage = (path::PathBuf, path::PathBuf, util::Bounds);
+
+/// CoverageReader reads the NAD83 bounds of the charts in a folder on a separate thread. Only the
+/// chart headers are read, not the raster data.
+pub struct CoverageReader {
+  rx: mpsc::Receiver<Coverage>,
+}
+
+impl CoverageReader {
+  /// Start reading chart bounds. Reading stops early if the reader is dropped.
+  /// - `folder`: folder containing chart zip files
+  /// - `ctx`: egui context for requesting a repaint
+  pub fn new(folder: path::PathBuf, ctx: &egui::Context) -> Self {
+    let ctx = ctx.clone();
+    let (tx, rx) = mpsc::channel();
+    thread::Builder::new()
+      .name(any::type_name::<CoverageReader>().to_owned())
+      .spawn(move || {
+        for (zip, file) in util::find_charts(&folder) {
+          let path = util::chart_path(&zip, &file);
+          let bounds = RasterSource::open(&path).and_then(|(source, _)| source.nad83_bounds());
+          match bounds {
+            Ok(bounds) => {
+              // The receiver is gone if the reader was dropped.
+              if tx.send((zip, file, bounds)).is_err() {
+                break;
+              }
+              ctx.request_repaint();
+            }
+            Err(err) => println!("{err}"),
+          }
+        }
+      })
+      .unwrap();
+
+    Self { rx }
+  }
+
+  /// Get the chart coverage that has been read since the last call.
+  pub fn get_replies(&self) -> Vec<Coverage> {
+    self.rx.try_iter().collect()
+  }
+}
+
+/// DemReader is used for sampling terrain elevation from a digital elevation model raster.
+pub struct DemReader {
+  tx: util::WorkerSender<util::Coord>,
+  rx: mpsc::Receiver<(util::Coord, Option<f64>)>,
+  last: cell::Cell<Option<util::Coord>>,
+  elevation: cell::Cell<Option<(util::Coord, Option<f64>)>>,
+}
+
+impl DemReader {
+  /// Open an elevation raster.
+  /// - `path`: raster file path
+  /// - `ctx`: egui context for requesting a repaint
+  pub fn new<P: AsRef<path::Path>>(path: P, ctx: &egui::Context) -> Result<Self, util::Error> {
+    DemReader::_new(path.as_ref(), ctx.clone())
+  }
+
+  fn _new(path: &path::Path, ctx: egui::Context) -> Result<Self, util::Error> {
+    // Create the communication channels.
+    let (tx, trx) = mpsc::channel();
+    let (ttx, rx) = mpsc::channel();
+    let (otx, orx) = mpsc::channel();
+
+    // Create the thread. The source is opened on the thread because its coordinate transformation
+    // can't be sent between threads.
+    thread::Builder::new()
+      .name(any::type_name::<DemReader>().to_owned())
+      .spawn({
+        let path = path.to_owned();
+        move || {
+          let source = match DemSource::open(&path) {
+            Ok(source) => source,
+            Err(err) => {
+              let _ = otx.send(Err(err));
+              return;
+            }
+          };
+
+          if otx.send(Ok(())).is_err() {
+            return;
+          }
+
+          // Wait for a message. Exit when the connection is closed.
+          while let Ok(request) = trx.recv() {
+            // Only the most recent position is of interest.
+            let coord = iter::once(request).chain(trx.try_iter()).last().unwrap();
+            if ttx.send((coord, source.sample(coord))).is_err() {
+              break;
+            }
+            ctx.request_repaint();
+          }
+        }
+      })
+      .unwrap();
+
+    // Wait for the source to be opened.
+    match orx.recv() {
+      Ok(Ok(())) => (),
+      Ok(Err(err)) => return Err(err),
+      Err(_) => return Err("Unable to open elevation data".into()),
+    }
+
+    Ok(Self {
+      tx: util::WorkerSender::new(tx),
+      rx,
+      last: cell::Cell::new(None),
+      elevation: cell::Cell::new(None),
+    })
+  }
+
+  /// Request the elevation at a coordinate.
+  /// - `coord`: NAD83 coordinate
+  pub fn sample(&self, coord: util::Coord) {
+    if self.last.get() != Some(coord) {
+      self.last.set(Some(coord));
+      self.tx.send(coord);
+    }
+  }
+
+  /// Get the most recently sampled elevation, in meters, if it's for the last requested
+  /// coordinate.
+  pub fn elevation(&self) -> Option<f64> {
+    if let Some(reply) = self.rx.try_iter().last() {
+      self.elevation.set(Some(reply));
+    }
+
+    let (coord, elevation) = self.elevation.get()?;
+    if self.last.get() == Some(coord) {
+      return elevation;
+    }
+    None
+  }
+}
+
+/// Elevation raster data source.
+struct DemSource {
+  dataset: gdal::Dataset,
+  to_px: gdal::GeoTransform,
+  from_nad83: spatial_ref::CoordTransform,
+  px_size: util::Size,
+  no_data: Option<f64>,
+}
+
+impl DemSource {
+  /// Open an elevation data source.
+  /// - `path`: raster file path
+  fn open(path: &path::Path) -> Result<Self, util::Error> {
+    let open = || -> Result<Self, gdal::errors::GdalError> {
+      let dataset = gdal::Dataset::open_ex(path, RasterSource::open_options())?;
+      let to_px = gdal::GeoTransformEx::invert(&dataset.geo_transform()?)?;
+
+      // Respect X/Y order when converting from lat/lon coordinates.
+      let spatial_ref = dataset.spatial_ref()?;
+      spatial_ref.set_axis_mapping_strategy(0);
+      let nad83 = spatial_ref::SpatialRef::from_epsg(4269)?;
+      nad83.set_axis_mapping_strategy(0);
+
+      let from_nad83 = spatial_ref::CoordTransform::new(&nad83, &spatial_ref)?;
+      let px_size = dataset.raster_size().into();
+      let no_data = dataset.rasterband(1)?.no_data_value();
+      Ok(Self {
+        dataset,
+        to_px,
+        from_nad83,
+        px_size,
+        no_data,
+      })
+    };
+
+    match open() {
+      Ok(source) => Ok(source),
+      Err(err) => Err(format!("Unable to open elevation data: {err}").into()),
+    }
+  }
+
+  /// Read the elevation at a NAD83 coordinate.
+  /// - `coord`: NAD83 coordinate
+  fn sample(&self, coord: util::Coord) -> Option<f64> {
+    let mut x = [coord.x];
+    let mut y = [coord.y];
+    self
+      .from_nad83
+      .transform_coords(&mut x, &mut y, &mut [])
+      .ok()?;
+
+    // Make sure the pixel is inside the raster.
+    let (x, y) = gdal::GeoTransformEx::apply(&self.to_px, x[0], y[0]);
+    let (x, y) = (x.floor(), y.floor());
+    if x < 0.0 || y < 0.0 || x >= self.px_size.w as f64 || y >= self.px_size.h as f64 {
+      return None;
+    }
+
+    let raster = self.dataset.rasterband(1).ok()?;
+    let buffer = raster.read_as::<f64>((x as isize, y as isize), (1, 1), (1, 1), None);
+    let elevation = *buffer.ok()?.data.first()?;
+    if self.no_data == Some(elevation) {
+      return None;
+    }
+    Some(elevation)
+  }
+}
+
 /// The part of the image needed for display.
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub struct ImagePart {
   pub rect: util::Rect,
   pub zoom: util::Hashable,
   pub dark: bool,
+  pub gray: bool,
+  pub sharpen: util::Hashable,
 }
 
 impl ImagePart {
-  pub fn new(rect: util::Rect, zoom: f32, dark: bool) -> Self {
+  pub fn new(rect: util::Rect, zoom: f32, dark: bool, gray: bool, sharpen: f32) -> Self {
     // A zoom value of zero is not valid.
     assert!(zoom > 0.0);
     Self {
       rect,
       zoom: zoom.into(),
       dark,
+      gray,
+      sharpen: sharpen.into(),
     }
   }
+
+  /// Get the same area of the image at a different resolution.
+  /// - `scale`: resolution scale
+  fn scaled(&self, scale: f32) -> Self {
+    let zoom = f32::from(self.zoom) * scale;
+    let sharpen = self.sharpen.into();
+    Self::new(self.rect.scaled(scale), zoom, self.dark, self.gray, sharpen)
+  }
+}
+
+/// Convert the chart palette to light and dark (night mode) colors.
+/// - `palette`: chart palette
+/// - `vision`: color vision type to remap the colors for
+/// - `color_map`: palette index to color overrides, which take precedence over the remapping
+/// - `emphasis`: chart features to make stand out
+/// - `indices`: palette indices of the emphasized features, or empty to pick them by color
+fn convert_palette(
+  palette: &[raster::RgbaEntry],
+  vision: util::ColorVision,
+  color_map: &util::ColorMap,
+  emphasis: util::Emphasis,
+  indices: &[u8],
+) -> (Vec<epaint::Color32>, Vec<epaint::Color32>) {
+  let mut light = Vec::with_capacity(palette.len());
+  let mut dark = Vec::with_capacity(palette.len());
+  for (index, entry) in palette.iter().enumerate() {
+    let [r, g, b, a] = match color_map.get(&(index as u8)) {
+      Some(&color) => color,
+      None => {
+        let mut rgb = [entry.r as u8, entry.g as u8, entry.b as u8];
+        let emphasized = if indices.is_empty() {
+          emphasis.matches(rgb)
+        } else {
+          emphasis != util::Emphasis::Off && indices.contains(&(index as u8))
+        };
+        if emphasized {
+          rgb = util::emphasize(rgb);
+        }
+
+        let [r, g, b] = vision.remap(rgb);
+        [r, g, b, entry.a as u8]
+      }
+    };
+
+    let entry = raster::RgbaEntry {
+      r: r.into(),
+      g: g.into(),
+      b: b.into(),
+      a: a.into(),
+    };
+    light.push(util::color(&entry));
+    dark.push(util::inverted_color(&entry));
+  }
+  (light, dark)
 }
 
 /// Chart raster data source.
@@ -247,46 +902,35 @@ impl RasterSource {
 
   /// Open a chart data source.
   /// - `path`: raster file path
-  fn open(
-    path: &path::Path,
-  ) -> Result<(Self, Transform, Vec<gdal::raster::RgbaEntry>), util::Error> {
+  fn open(path: &path::Path) -> Result<(Self, Vec<gdal::raster::RgbaEntry>), util::Error> {
     match gdal::Dataset::open_ex(path, Self::open_options()) {
       Ok(dataset) => {
-        // Get and check the dataset's spatial reference.
-        let spatial_ref = match dataset.spatial_ref() {
-          Ok(sr) => {
-            match sr.to_proj4() {
-              Ok(proj4) => {
-                let proj4 = proj4.to_lowercase();
-                for item in ["+proj=lcc", "+datum=nad83", "+units=m"] {
-                  if !proj4.contains(item) {
-                    return Err("Unable to open chart: invalid spatial reference".into());
-                  }
+        // Check the dataset's spatial reference.
+        match dataset.spatial_ref() {
+          Ok(sr) => match sr.to_proj4() {
+            Ok(proj4) => {
+              let proj4 = proj4.to_lowercase();
+              for item in ["+proj=lcc", "+datum=nad83", "+units=m"] {
+                if !proj4.contains(item) {
+                  return Err("Unable to open chart: invalid spatial reference".into());
                 }
               }
-              Err(err) => return Err(format!("Unable to open chart: {err}").into()),
             }
-            sr
-          }
+            Err(err) => return Err(format!("Unable to open chart: {err}").into()),
+          },
           Err(err) => return Err(format!("Unable to open chart: {err}").into()),
-        };
+        }
 
         // This dataset must have a geo-transformation.
-        let geo_transform = match dataset.geo_transform() {
-          Ok(gt) => gt,
-          Err(err) => return Err(format!("Unable to open chart: {err}").into()),
-        };
+        if let Err(err) = dataset.geo_transform() {
+          return Err(format!("Unable to open chart: {err}").into());
+        }
 
         let px_size: util::Size = dataset.raster_size().into();
         if !px_size.is_valid() {
           return Err("Unable to open chart: invalid pixel size".into());
         }
 
-        let chart_transform = match Transform::new(px_size, spatial_ref, geo_transform) {
-          Ok(trans) => trans,
-          Err(err) => return Err(format!("Unable to open chart: {err}").into()),
-        };
-
         let (band_idx, palette) = || -> Result<(isize, Vec<raster::RgbaEntry>), util::Error> {
           // The raster bands start at index one.
           for index in 1..=dataset.raster_count() {
@@ -327,7 +971,6 @@ impl RasterSource {
             band_idx,
             px_size,
           },
-          chart_transform,
           palette,
         ))
       }
@@ -335,10 +978,87 @@ impl RasterSource {
     }
   }
 
+  /// Create the chart transformation. This is done separately from opening because the
+  /// transformation can't be sent between threads.
+  fn transform(&self) -> Result<Transform, util::Error> {
+    let transform = || -> Result<Transform, gdal::errors::GdalError> {
+      let spatial_ref = self.dataset.spatial_ref()?;
+      let geo_transform = self.dataset.geo_transform()?;
+      Transform::new(self.px_size, spatial_ref, geo_transform)
+    };
+
+    match transform() {
+      Ok(transform) => Ok(transform),
+      Err(err) => Err(format!("Unable to open chart: {err}").into()),
+    }
+  }
+
+  /// Get the NAD83 bounds of the whole chart, including its margins.
+  fn nad83_bounds(&self) -> Result<util::Bounds, util::Error> {
+    // Sample along the edges because the edges of a LCC chart are curved in NAD83.
+    const STEPS: u32 = 8;
+    let transform = self.transform()?;
+    let (w, h) = (self.px_size.w as f64, self.px_size.h as f64);
+    let mut min = util::Coord {
+      x: f64::MAX,
+      y: f64::MAX,
+    };
+    let mut max = util::Coord {
+      x: f64::MIN,
+      y: f64::MIN,
+    };
+    for step in 0..=STEPS {
+      let t = step as f64 / STEPS as f64;
+      for (x, y) in [(t * w, 0.0), (t * w, h), (0.0, t * h), (w, t * h)] {
+        match transform.px_to_nad83(util::Coord { x, y }) {
+          Ok(coord) => {
+            min = util::Coord {
+              x: min.x.min(coord.x),
+              y: min.y.min(coord.y),
+            };
+            max = util::Coord {
+              x: max.x.max(coord.x),
+              y: max.y.max(coord.y),
+            };
+          }
+          Err(err) => return Err(format!("Unable to get chart bounds: {err}").into()),
+        }
+      }
+    }
+    Ok(util::Bounds { min, max })
+  }
+
+  /// Get the chart title from the dataset metadata.
+  fn title(&self) -> Option<String> {
+    use gdal::Metadata;
+    for key in ["TIFFTAG_DOCUMENTNAME", "TIFFTAG_IMAGEDESCRIPTION"] {
+      if let Some(text) = self.dataset.metadata_item(key, "") {
+        let text = text.trim();
+        if !text.is_empty() {
+          return Some(text.into());
+        }
+      }
+    }
+    None
+  }
+
+  /// Scale and correct the source rectangle (GDAL does not tolerate read requests outside the
+  /// original raster size).
+  fn src_rect(&self, part: &ImagePart) -> util::Rect {
+    part.rect.scaled(part.zoom.inverse()).fitted(self.px_size)
+  }
+
+  /// Returns true if the image part covers the whole chart.
+  fn is_overview(&self, part: &ImagePart) -> bool {
+    // Allow a pixel for rounding when the rectangle is scaled.
+    let src_rect = self.src_rect(part);
+    let w = self.px_size.w.saturating_sub(src_rect.size.w);
+    let h = self.px_size.h.saturating_sub(src_rect.size.h);
+    src_rect.pos == util::Pos::default() && w <= 1 && h <= 1
+  }
+
   fn read(&self, part: &ImagePart) -> Result<gdal::raster::Buffer<u8>, gdal::errors::GdalError> {
-    // Scale and correct the source rectangle (GDAL does not tolerate
-    // read requests outside the original raster size).
-    let src_rect = part.rect.scaled(part.zoom.inverse()).fitted(self.px_size);
+    let src_rect = self.src_rect(part);
     let raster = self.dataset.rasterband(self.band_idx).unwrap();
     raster.read_as::<u8>(
       src_rect.pos.into(),
@@ -347,4 +1067,164 @@ impl RasterSource {
       Some(gdal::raster::ResampleAlg::Average),
     )
   }
+
+  /// Read the whole chart as a small image.
+  /// - `palette`: chart palette
+  /// - `size`: maximum width or height, in pixels
+  fn thumbnail(
+    &self,
+    palette: &[raster::RgbaEntry],
+    size: u32,
+  ) -> Result<epaint::ColorImage, gdal::errors::GdalError> {
+    let max = self.px_size.w.max(self.px_size.h).max(1);
+    let zoom = (size as f32 / max as f32).min(1.0);
+    let rect = util::Rect {
+      pos: util::Pos::default(),
+      size: self.px_size,
+    };
+
+    let part = ImagePart::new(rect.scaled(zoom), zoom, false, false, 0.0);
+    let (vision, color_map) = (util::ColorVision::Normal, util::ColorMap::new());
+    let (colors, _) = convert_palette(palette, vision, &color_map, util::Emphasis::Off, &[]);
+    self.read_image(&part, &colors)
+  }
+
+  /// Read part of the image and convert it to RGBA.
+  /// - `part`: the area to read
+  /// - `colors`: palette colors to use
+  fn read_image(
+    &self,
+    part: &ImagePart,
+    colors: &[epaint::Color32],
+  ) -> Result<epaint::ColorImage, gdal::errors::GdalError> {
+    let gdal_image = self.read(part)?;
+    let (w, h) = gdal_image.size;
+    let mut image = epaint::ColorImage {
+      size: [w, h],
+      pixels: Vec::with_capacity(w * h),
+    };
+
+    // Convert the palette to grayscale if needed.
+    let gray: Vec<epaint::Color32>;
+    let mut colors = colors;
+    if part.gray {
+      gray = colors.iter().map(|color| util::grayscale(*color)).collect();
+      colors = &gray;
+    }
+
+    // Convert the image to RGBA.
+    for val in gdal_image.data {
+      image.pixels.push(colors[val as usize]);
+    }
+
+    // Sharpen the image if it was resampled.
+    if f32::from(part.zoom) < 1.0 {
+      util::sharpen(&mut image, part.sharpen.into());
+    }
+    Ok(image)
+  }
+}
+
+mod test {
+  /// Create a transform for a synthetic Lambert conformal conic chart.
+  #[cfg(test)]
+  fn transform() -> super::Transform {
+    let proj4 = "+proj=lcc +lat_0=38 +lon_0=-122 +lat_1=33.3333 +lat_2=45.6667 +x_0=0 +y_0=0 \
+                 +datum=NAD83 +units=m +no_defs";
+    let spatial_ref = gdal::spatial_ref::SpatialRef::from_proj4(proj4).unwrap();
+    let px_size = crate::util::Size { w: 12000, h: 9000 };
+    let geo_transform = [-250000.0, 42.5, 0.0, 190000.0, 0.0, -42.5];
+    super::Transform::new(px_size, spatial_ref, geo_transform).unwrap()
+  }
+
+  /// Pixel coordinates at the corners, the edges and the center of the synthetic chart.
+  #[cfg(test)]
+  fn points() -> Vec<crate::util::Coord> {
+    let mut points = Vec::new();
+    for x in [0.0, 6000.0, 12000.0] {
+      for y in [0.0, 4500.0, 9000.0] {
+        points.push(crate::util::Coord { x, y });
+      }
+    }
+    points
+  }
+
+  #[test]
+  fn test_px_chart_round_trip() {
+    let transform = transform();
+    for px in points() {
+      let chart = transform.px_to_chart(px);
+      let result = transform.chart_to_px(chart);
+      assert!((result.x - px.x).abs() < 1e-6 && (result.y - px.y).abs() < 1e-6);
+    }
+  }
+
+  #[test]
+  fn test_px_nad83_round_trip() {
+    let transform = transform();
+    for px in points() {
+      let nad83 = transform.px_to_nad83(px).unwrap();
+      assert!((-180.0..=180.0).contains(&nad83.x) && (-90.0..=90.0).contains(&nad83.y));
+
+      let result = transform.nad83_to_px(nad83).unwrap();
+      assert!((result.x - px.x).abs() < 1e-3 && (result.y - px.y).abs() < 1e-3);
+    }
+  }
+
+  #[test]
+  fn test_origin() {
+    // The projection origin is at a known chart coordinate.
+    let transform = transform();
+    let origin = crate::util::Coord { x: -122.0, y: 38.0 };
+    let chart = transform.nad83_to_chart(origin).unwrap();
+    assert!(chart.x.abs() < 1e-3 && chart.y.abs() < 1e-3);
+
+    let nad83 = transform.chart_to_nad83(chart).unwrap();
+    assert!((nad83.x - origin.x).abs() < 1e-9 && (nad83.y - origin.y).abs() < 1e-9);
+  }
+
+  #[test]
+  fn test_bounds() {
+    let transform = transform();
+    let bounds = transform.bounds();
+    assert!(bounds.min.x == -250000.0 && bounds.max.y == 190000.0);
+    assert!(bounds.max.x == -250000.0 + 12000.0 * 42.5);
+    assert!(bounds.min.y == 190000.0 - 9000.0 * 42.5);
+  }
+
+  #[test]
+  fn test_image_cache() {
+    use eframe::epaint::{Color32, ColorImage};
+    let part = |x| {
+      let pos = crate::util::Pos { x, y: 0 };
+      let size = crate::util::Size { w: 10, h: 10 };
+      let rect = crate::util::Rect { pos, size };
+      super::ImagePart::new(rect, 1.0, false, false, 0.0)
+    };
+
+    // Each image is 400 bytes, so the budget holds two.
+    let mut cache = super::ImageCache::new(1000);
+    cache.insert(part(0), ColorImage::new([10, 10], Color32::BLACK));
+    cache.insert(part(1), ColorImage::new([10, 10], Color32::BLACK));
+    assert!(cache.used() == 800);
+
+    // Using the first image makes the second one the least recently used.
+    assert!(cache.get(&part(0)).is_some());
+    cache.insert(part(2), ColorImage::new([10, 10], Color32::BLACK));
+    assert!(cache.used() == 800);
+    assert!(cache.get(&part(1)).is_none());
+    assert!(cache.get(&part(0)).is_some());
+    assert!(cache.get(&part(2)).is_some());
+
+    // Images larger than the budget are not cached.
+    cache.insert(part(3), ColorImage::new([20, 20], Color32::BLACK));
+    assert!(cache.get(&part(3)).is_none());
+
+    cache.set_budget(500);
+    assert!(cache.used() == 400);
+    assert!(cache.get(&part(2)).is_some());
+
+    cache.clear();
+    assert!(cache.used() == 0);
+  }
 }
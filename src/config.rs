@@ -1,5 +1,5 @@
 use crate::util;
-use std::{path, sync};
+use std::{mem, path, sync};
 
 /// Storage for configuration items, persisted as JSON.
 #[derive(Clone)]
@@ -12,121 +12,635 @@ pub struct Storage {
 impl Storage {
   pub fn new(store_win: bool) -> Option<Self> {
     let path = Storage::path()?;
+    Some(Storage::open(path, store_win))
+  }
+
+  fn open(path: path::PathBuf, store_win: bool) -> Self {
     let items = sync::Arc::new(sync::RwLock::new(inner::Items::load(path)));
     let thread = sync::Arc::new(inner::PersistThread::new(items.clone()));
-    Some(Self {
+    Self {
       items,
       thread,
       store_win,
-    })
+    }
   }
 
   pub fn set_win_info(&mut self, win_info: &util::WinInfo) {
     if self.store_win {
-      let value = win_info.to_value();
-      let mut items = self.items.write().unwrap();
-      items.set(Storage::WIN_INFO_KEY, value);
+      self.write(|settings| settings.win_info = win_info.clone());
     }
   }
 
   pub fn get_win_info(&self) -> util::WinInfo {
-    let items = self.items.read().unwrap();
-    util::WinInfo::from_value(items.get(Storage::WIN_INFO_KEY))
+    self.read(|settings| settings.win_info.clone())
+  }
+
+  /// Check if the window info is stored.
+  pub fn win_stored(&self) -> bool {
+    self.store_win
   }
 
   pub fn set_night_mode(&mut self, dark: bool) {
-    let value = serde_json::Value::Bool(dark);
-    let mut items = self.items.write().unwrap();
-    items.set(Storage::NIGHT_MODE_KEY, value);
-    self.thread.persist();
+    self.write(|settings| settings.night_mode = Some(dark));
   }
 
   pub fn get_night_mode(&self) -> Option<bool> {
-    let items = self.items.read().unwrap();
-    items.get(Storage::NIGHT_MODE_KEY)?.as_bool()
+    self.read(|settings| settings.night_mode)
+  }
+
+  pub fn set_grayscale(&mut self, gray: bool) {
+    self.write(|settings| settings.grayscale = Some(gray));
+  }
+
+  pub fn get_grayscale(&self) -> Option<bool> {
+    self.read(|settings| settings.grayscale)
+  }
+
+  pub fn set_bg_color(&mut self, color: [u8; 3]) {
+    self.write(|settings| settings.bg_color = Some(color));
+  }
+
+  pub fn get_bg_color(&self) -> Option<[u8; 3]> {
+    self.read(|settings| settings.bg_color)
+  }
+
+  pub fn set_night_bg_color(&mut self, color: [u8; 3]) {
+    self.write(|settings| settings.night_bg_color = Some(color));
+  }
+
+  pub fn get_night_bg_color(&self) -> Option<[u8; 3]> {
+    self.read(|settings| settings.night_bg_color)
   }
 
   pub fn set_asset_path(&mut self, path: String) {
-    let value = serde_json::Value::String(path);
-    let mut items = self.items.write().unwrap();
-    items.set(Storage::ASSET_PATH_KEY, value);
-    self.thread.persist();
+    self.write(|settings| settings.asset_path = Some(path));
   }
 
   pub fn get_asset_path(&self) -> Option<String> {
-    let items = self.items.read().unwrap();
-    Some(items.get(Storage::ASSET_PATH_KEY)?.as_str()?.into())
+    self.read(|settings| settings.asset_path.clone())
+  }
+
+  pub fn set_nasr_path(&mut self, path: String) {
+    self.write(|settings| settings.nasr_path = Some(path));
+  }
+
+  pub fn get_nasr_path(&self) -> Option<String> {
+    self.read(|settings| settings.nasr_path.clone())
+  }
+
+  pub fn set_charts_path(&mut self, path: String) {
+    self.write(|settings| settings.charts_path = Some(path));
+  }
+
+  pub fn get_charts_path(&self) -> Option<String> {
+    self.read(|settings| settings.charts_path.clone())
+  }
+
+  pub fn set_auto_load_aero(&mut self, auto: bool) {
+    self.write(|settings| settings.auto_load_aero = Some(auto));
+  }
+
+  pub fn get_auto_load_aero(&self) -> Option<bool> {
+    self.read(|settings| settings.auto_load_aero)
+  }
+
+  pub fn set_auto_hide_panel(&mut self, auto: bool) {
+    self.write(|settings| settings.auto_hide_panel = Some(auto));
+  }
+
+  pub fn get_auto_hide_panel(&self) -> Option<bool> {
+    self.read(|settings| settings.auto_hide_panel)
+  }
+
+  pub fn set_show_closed(&mut self, show: bool) {
+    self.write(|settings| settings.show_closed = Some(show));
+  }
+
+  pub fn get_show_closed(&self) -> Option<bool> {
+    self.read(|settings| settings.show_closed)
+  }
+
+  pub fn set_show_private(&mut self, show: bool) {
+    self.write(|settings| settings.show_private = Some(show));
+  }
+
+  pub fn get_show_private(&self) -> Option<bool> {
+    self.read(|settings| settings.show_private)
+  }
+
+  pub fn set_towered_only(&mut self, towered: bool) {
+    self.write(|settings| settings.towered_only = Some(towered));
+  }
+
+  pub fn get_towered_only(&self) -> Option<bool> {
+    self.read(|settings| settings.towered_only)
+  }
+
+  pub fn set_screen_dpi(&mut self, dpi: u32) {
+    self.write(|settings| settings.screen_dpi = Some(dpi));
+  }
+
+  pub fn get_screen_dpi(&self) -> Option<u32> {
+    self.read(|settings| settings.screen_dpi)
+  }
+
+  pub fn set_range_rings(&mut self, interval: u32) {
+    self.write(|settings| settings.range_rings = Some(interval));
+  }
+
+  pub fn get_range_rings(&self) -> Option<u32> {
+    self.read(|settings| settings.range_rings)
+  }
+
+  pub fn set_zoom_lock(&mut self, lock: bool) {
+    self.write(|settings| settings.zoom_lock = Some(lock));
+  }
+
+  pub fn get_zoom_lock(&self) -> Option<bool> {
+    self.read(|settings| settings.zoom_lock)
+  }
+
+  pub fn set_scroll_snap(&mut self, snap: util::ScrollSnap) {
+    self.write(|settings| settings.scroll_snap = Some(snap));
+  }
+
+  pub fn get_scroll_snap(&self) -> Option<util::ScrollSnap> {
+    self.read(|settings| settings.scroll_snap)
+  }
+
+  pub fn set_highlight_found(&mut self, highlight: bool) {
+    self.write(|settings| settings.highlight_found = Some(highlight));
+  }
+
+  pub fn get_highlight_found(&self) -> Option<bool> {
+    self.read(|settings| settings.highlight_found)
+  }
+
+  pub fn set_show_center(&mut self, show: bool) {
+    self.write(|settings| settings.show_center = Some(show));
+  }
+
+  pub fn get_show_center(&self) -> Option<bool> {
+    self.read(|settings| settings.show_center)
+  }
+
+  pub fn set_open_fit(&mut self, fit: bool) {
+    self.write(|settings| settings.open_fit = Some(fit));
+  }
+
+  pub fn get_open_fit(&self) -> Option<bool> {
+    self.read(|settings| settings.open_fit)
+  }
+
+  pub fn set_low_power(&mut self, low_power: bool) {
+    self.write(|settings| settings.low_power = Some(low_power));
+  }
+
+  pub fn get_low_power(&self) -> Option<bool> {
+    self.read(|settings| settings.low_power)
+  }
+
+  pub fn set_cache_overview(&mut self, cache: bool) {
+    self.write(|settings| settings.cache_overview = Some(cache));
+  }
+
+  pub fn get_cache_overview(&self) -> Option<bool> {
+    self.read(|settings| settings.cache_overview)
+  }
+
+  pub fn set_progressive(&mut self, progressive: bool) {
+    self.write(|settings| settings.progressive = Some(progressive));
+  }
+
+  pub fn get_progressive(&self) -> Option<bool> {
+    self.read(|settings| settings.progressive)
+  }
+
+  pub fn set_zoom_step(&mut self, step: util::ZoomStep) {
+    self.write(|settings| settings.zoom_step = Some(step));
+  }
+
+  pub fn get_zoom_step(&self) -> Option<util::ZoomStep> {
+    self.read(|settings| settings.zoom_step)
+  }
+
+  pub fn set_goto_anchor(&mut self, anchor: util::GotoAnchor) {
+    self.write(|settings| settings.goto_anchor = Some(anchor));
+  }
+
+  pub fn get_goto_anchor(&self) -> Option<util::GotoAnchor> {
+    self.read(|settings| settings.goto_anchor)
+  }
+
+  pub fn set_marker_size(&mut self, size: util::MarkerSize) {
+    self.write(|settings| settings.marker_size = Some(size));
+  }
+
+  pub fn get_marker_size(&self) -> Option<util::MarkerSize> {
+    self.read(|settings| settings.marker_size)
+  }
+
+  pub fn set_marker_labels(&mut self, labels: bool) {
+    self.write(|settings| settings.marker_labels = Some(labels));
+  }
+
+  pub fn get_marker_labels(&self) -> Option<bool> {
+    self.read(|settings| settings.marker_labels)
+  }
+
+  pub fn set_long_press_feedback(&mut self, feedback: bool) {
+    self.write(|settings| settings.long_press_feedback = Some(feedback));
+  }
+
+  pub fn get_long_press_feedback(&self) -> Option<bool> {
+    self.read(|settings| settings.long_press_feedback)
+  }
+
+  pub fn set_long_press_action(&mut self, action: util::LongPressAction) {
+    self.write(|settings| settings.long_press_action = Some(action));
+  }
+
+  pub fn get_long_press_action(&self) -> Option<util::LongPressAction> {
+    self.read(|settings| settings.long_press_action)
+  }
+
+  pub fn set_escape_action(&mut self, action: util::EscapeAction) {
+    self.write(|settings| settings.escape_action = Some(action));
+  }
+
+  pub fn get_escape_action(&self) -> Option<util::EscapeAction> {
+    self.read(|settings| settings.escape_action)
+  }
+
+  pub fn set_sharpen(&mut self, sharpen: f32) {
+    self.write(|settings| settings.sharpen = Some(sharpen));
+  }
+
+  pub fn get_sharpen(&self) -> Option<f32> {
+    let sharpen = self.read(|settings| settings.sharpen)?;
+    (0.0..=1.0).contains(&sharpen).then_some(sharpen)
+  }
+
+  pub fn set_color_map(&mut self, color_map: util::ColorMap) {
+    self.write(|settings| settings.color_map = Some(color_map));
+  }
+
+  pub fn get_color_map(&self) -> Option<util::ColorMap> {
+    self.read(|settings| settings.color_map.clone())
+  }
+
+  pub fn set_color_vision(&mut self, vision: util::ColorVision) {
+    self.write(|settings| settings.color_vision = Some(vision));
+  }
+
+  pub fn get_color_vision(&self) -> Option<util::ColorVision> {
+    self.read(|settings| settings.color_vision)
+  }
+
+  pub fn set_emphasis(&mut self, emphasis: util::Emphasis) {
+    self.write(|settings| settings.emphasis = Some(emphasis));
+  }
+
+  pub fn get_emphasis(&self) -> Option<util::Emphasis> {
+    self.read(|settings| settings.emphasis)
+  }
+
+  /// Set the palette indices to emphasize for a feature.
+  /// - `emphasis`: emphasized feature
+  /// - `indices`: palette indices, or empty to pick them by color
+  pub fn set_emphasis_indices(&mut self, emphasis: util::Emphasis, indices: Vec<u8>) {
+    self.write(|settings| match emphasis {
+      util::Emphasis::Off => (),
+      util::Emphasis::Water => settings.water_indices = Some(indices),
+      util::Emphasis::Terrain => settings.terrain_indices = Some(indices),
+    });
+  }
+
+  pub fn get_emphasis_indices(&self, emphasis: util::Emphasis) -> Option<Vec<u8>> {
+    self.read(|settings| match emphasis {
+      util::Emphasis::Off => None,
+      util::Emphasis::Water => settings.water_indices.clone(),
+      util::Emphasis::Terrain => settings.terrain_indices.clone(),
+    })
+  }
+
+  pub fn set_image_limit(&mut self, megapixels: u32) {
+    self.write(|settings| settings.image_limit = Some(megapixels));
+  }
+
+  pub fn get_image_limit(&self) -> Option<u32> {
+    self.read(|settings| settings.image_limit)
+  }
+
+  pub fn set_chart_thumbnails(&mut self, show: bool) {
+    self.write(|settings| settings.chart_thumbnails = Some(show));
+  }
+
+  pub fn get_chart_thumbnails(&self) -> Option<bool> {
+    self.read(|settings| settings.chart_thumbnails)
+  }
+
+  pub fn set_ui_theme(&mut self, theme: util::UiTheme) {
+    self.write(|settings| settings.ui_theme = Some(theme));
+  }
+
+  pub fn get_ui_theme(&self) -> Option<util::UiTheme> {
+    self.read(|settings| settings.ui_theme)
+  }
+
+  pub fn set_fullscreen(&mut self, fullscreen: bool) {
+    self.write(|settings| settings.fullscreen = Some(fullscreen));
+  }
+
+  pub fn get_fullscreen(&self) -> Option<bool> {
+    self.read(|settings| settings.fullscreen)
+  }
+
+  pub fn set_show_magnetic(&mut self, show: bool) {
+    self.write(|settings| settings.show_magnetic = Some(show));
+  }
+
+  pub fn get_show_magnetic(&self) -> Option<bool> {
+    self.read(|settings| settings.show_magnetic)
+  }
+
+  pub fn set_image_cache(&mut self, megabytes: u32) {
+    self.write(|settings| settings.image_cache = Some(megabytes));
+  }
+
+  pub fn get_image_cache(&self) -> Option<u32> {
+    self.read(|settings| settings.image_cache)
+  }
+
+  pub fn set_dim_timeout(&mut self, minutes: u32) {
+    self.write(|settings| settings.dim_timeout = Some(minutes));
+  }
+
+  pub fn get_dim_timeout(&self) -> Option<u32> {
+    self.read(|settings| settings.dim_timeout)
+  }
+
+  pub fn set_dim_level(&mut self, percent: u32) {
+    self.write(|settings| settings.dim_level = Some(percent));
+  }
+
+  pub fn get_dim_level(&self) -> Option<u32> {
+    self.read(|settings| settings.dim_level)
+  }
+
+  pub fn set_units(&mut self, units: util::Units) {
+    self.write(|settings| settings.units = Some(units));
+  }
+
+  pub fn get_units(&self) -> Option<util::Units> {
+    self.read(|settings| settings.units)
+  }
+
+  pub fn set_coord_format(&mut self, format: util::CoordFormat) {
+    self.write(|settings| settings.coord_format = Some(format));
+  }
+
+  pub fn get_coord_format(&self) -> Option<util::CoordFormat> {
+    self.read(|settings| settings.coord_format)
+  }
+
+  pub fn set_bookmarks(&mut self, bookmarks: Vec<util::Bookmark>) {
+    self.write(|settings| settings.bookmarks = Some(bookmarks));
+  }
+
+  pub fn get_bookmarks(&self) -> Option<Vec<util::Bookmark>> {
+    self.read(|settings| settings.bookmarks.clone())
+  }
+
+  /// Set the location that newly opened charts are centered on.
+  /// - `home`: NAD83 coordinate or `None` to clear it
+  pub fn set_home(&mut self, home: Option<util::Coord>) {
+    self.write(|settings| settings.home = home.map(|coord| [coord.x, coord.y]));
+  }
+
+  pub fn get_home(&self) -> Option<util::Coord> {
+    self.read(|settings| settings.home.map(|[x, y]| util::Coord { x, y }))
+  }
+
+  /// Reset all settings, except for the window info, bookmarks and home location, to their
+  /// defaults.
+  pub fn reset(&mut self) {
+    self.write(|settings| {
+      let win_info = mem::take(&mut settings.win_info);
+      let bookmarks = settings.bookmarks.take();
+      let home = settings.home.take();
+      *settings = inner::Settings {
+        win_info,
+        bookmarks,
+        home,
+        ..Default::default()
+      };
+    });
+  }
+
+  /// Read from the settings.
+  fn read<R>(&self, read: impl FnOnce(&inner::Settings) -> R) -> R {
+    read(self.items.read().unwrap().settings())
+  }
+
+  /// Modify the settings and persist any changes.
+  fn write(&mut self, write: impl FnOnce(&mut inner::Settings)) {
+    self.items.write().unwrap().update(write);
+    self.thread.persist();
+  }
+
+  /// Get the settings as JSON for a bug report. Paths are reduced to their file names and
+  /// saved locations are left out.
+  pub fn sanitized_json(&self) -> String {
+    let mut settings = self.read(|settings| settings.clone());
+    for path in [
+      &mut settings.asset_path,
+      &mut settings.charts_path,
+      &mut settings.nasr_path,
+    ] {
+      *path = path.as_deref().map(|path| {
+        let name = path::Path::new(path).file_name();
+        name.map_or(String::new(), |name| name.to_string_lossy().into())
+      });
+    }
+    settings.bookmarks = None;
+    settings.home = None;
+    serde_json::to_string_pretty(&settings).unwrap_or_default()
+  }
+
+  /// Get the folder that contains the settings file.
+  pub fn folder() -> Option<path::PathBuf> {
+    Storage::path()?.parent().map(path::Path::to_owned)
   }
 
   fn path() -> Option<path::PathBuf> {
     dirs::config_dir().map(|path| path.join(util::APP_NAME).with_extension("json"))
   }
-
-  const WIN_INFO_KEY: &'static str = "win_info";
-  const NIGHT_MODE_KEY: &'static str = "night_mode";
-  const ASSET_PATH_KEY: &'static str = "asset_path";
 }
 
 mod inner {
+  use crate::util;
+  use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize};
   use std::{
     fs, io, path,
     sync::{self, atomic, mpsc},
     thread,
   };
 
+  /// Configuration settings. Each field is loaded independently so that a bad value doesn't
+  /// affect the others.
+  #[derive(Clone, Default, Deserialize, PartialEq, Serialize)]
+  #[serde(default)]
+  pub struct Settings {
+    #[serde(deserialize_with = "lenient")]
+    pub win_info: util::WinInfo,
+    #[serde(deserialize_with = "lenient")]
+    pub night_mode: Option<bool>,
+    #[serde(deserialize_with = "lenient")]
+    pub grayscale: Option<bool>,
+    #[serde(deserialize_with = "lenient")]
+    pub bg_color: Option<[u8; 3]>,
+    #[serde(deserialize_with = "lenient")]
+    pub night_bg_color: Option<[u8; 3]>,
+    #[serde(deserialize_with = "lenient")]
+    pub asset_path: Option<String>,
+    #[serde(deserialize_with = "lenient")]
+    pub charts_path: Option<String>,
+    #[serde(deserialize_with = "lenient")]
+    pub nasr_path: Option<String>,
+    #[serde(deserialize_with = "lenient")]
+    pub auto_load_aero: Option<bool>,
+    #[serde(deserialize_with = "lenient")]
+    pub show_closed: Option<bool>,
+    #[serde(deserialize_with = "lenient")]
+    pub show_private: Option<bool>,
+    #[serde(deserialize_with = "lenient")]
+    pub auto_hide_panel: Option<bool>,
+    #[serde(deserialize_with = "lenient")]
+    pub show_center: Option<bool>,
+    #[serde(deserialize_with = "lenient")]
+    pub highlight_found: Option<bool>,
+    #[serde(deserialize_with = "lenient")]
+    pub low_power: Option<bool>,
+    #[serde(deserialize_with = "lenient")]
+    pub cache_overview: Option<bool>,
+    #[serde(deserialize_with = "lenient")]
+    pub progressive: Option<bool>,
+    #[serde(deserialize_with = "lenient")]
+    pub open_fit: Option<bool>,
+    #[serde(deserialize_with = "lenient")]
+    pub scroll_snap: Option<util::ScrollSnap>,
+    #[serde(deserialize_with = "lenient")]
+    pub zoom_lock: Option<bool>,
+    #[serde(deserialize_with = "lenient")]
+    pub bookmarks: Option<Vec<util::Bookmark>>,
+    #[serde(deserialize_with = "lenient")]
+    pub home: Option<[f64; 2]>,
+    #[serde(deserialize_with = "lenient")]
+    pub range_rings: Option<u32>,
+    #[serde(deserialize_with = "lenient")]
+    pub screen_dpi: Option<u32>,
+    #[serde(deserialize_with = "lenient")]
+    pub towered_only: Option<bool>,
+    #[serde(deserialize_with = "lenient")]
+    pub marker_size: Option<util::MarkerSize>,
+    #[serde(deserialize_with = "lenient")]
+    pub zoom_step: Option<util::ZoomStep>,
+    #[serde(deserialize_with = "lenient")]
+    pub goto_anchor: Option<util::GotoAnchor>,
+    #[serde(deserialize_with = "lenient")]
+    pub marker_labels: Option<bool>,
+    #[serde(deserialize_with = "lenient")]
+    pub long_press_feedback: Option<bool>,
+    #[serde(deserialize_with = "lenient")]
+    pub long_press_action: Option<util::LongPressAction>,
+    #[serde(deserialize_with = "lenient")]
+    pub escape_action: Option<util::EscapeAction>,
+    #[serde(deserialize_with = "lenient")]
+    pub sharpen: Option<f32>,
+    #[serde(deserialize_with = "lenient")]
+    pub image_limit: Option<u32>,
+    #[serde(deserialize_with = "lenient")]
+    pub image_cache: Option<u32>,
+    #[serde(deserialize_with = "lenient")]
+    pub dim_timeout: Option<u32>,
+    #[serde(deserialize_with = "lenient")]
+    pub dim_level: Option<u32>,
+    #[serde(deserialize_with = "lenient")]
+    pub chart_thumbnails: Option<bool>,
+    #[serde(deserialize_with = "lenient")]
+    pub show_magnetic: Option<bool>,
+    #[serde(deserialize_with = "lenient")]
+    pub fullscreen: Option<bool>,
+    #[serde(deserialize_with = "lenient")]
+    pub ui_theme: Option<util::UiTheme>,
+    #[serde(deserialize_with = "lenient")]
+    pub color_map: Option<util::ColorMap>,
+    #[serde(deserialize_with = "lenient")]
+    pub color_vision: Option<util::ColorVision>,
+    #[serde(deserialize_with = "lenient")]
+    pub emphasis: Option<util::Emphasis>,
+    #[serde(deserialize_with = "lenient")]
+    pub water_indices: Option<Vec<u8>>,
+    #[serde(deserialize_with = "lenient")]
+    pub terrain_indices: Option<Vec<u8>>,
+    #[serde(deserialize_with = "lenient")]
+    pub units: Option<util::Units>,
+    #[serde(deserialize_with = "lenient")]
+    pub coord_format: Option<util::CoordFormat>,
+  }
+
+  /// Deserialize a value, falling back to the default if it's not valid.
+  fn lenient<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+  where
+    D: Deserializer<'de>,
+    T: DeserializeOwned + Default,
+  {
+    let value = serde_json::Value::deserialize(deserializer)?;
+    Ok(T::deserialize(value).unwrap_or_default())
+  }
+
   pub struct Items {
     path: path::PathBuf,
-    items: serde_json::Value,
+    settings: Settings,
     changed: atomic::AtomicBool,
   }
 
   impl Items {
     pub fn load(path: path::PathBuf) -> Self {
-      let items = Self::load_items(&path);
+      let settings = Self::load_settings(&path);
       let changed = atomic::AtomicBool::new(false);
       Self {
         path,
-        items,
+        settings,
         changed,
       }
     }
 
-    pub fn get(&self, key: &str) -> Option<&serde_json::Value> {
-      self.items.get(key)
+    pub fn settings(&self) -> &Settings {
+      &self.settings
     }
 
-    pub fn set(&mut self, key: &str, item: serde_json::Value) {
-      if self.items.get(key) == Some(&item) {
-        return;
-      }
-      self.items[key] = item;
-      self.changed.store(true, atomic::Ordering::Relaxed);
-    }
-
-    #[allow(unused)]
-    pub fn remove(&mut self, key: &str) {
-      if self.items.as_object_mut().unwrap().remove(key).is_some() {
+    pub fn update(&mut self, update: impl FnOnce(&mut Settings)) {
+      let prev = self.settings.clone();
+      update(&mut self.settings);
+      if self.settings != prev {
         self.changed.store(true, atomic::Ordering::Relaxed);
       }
     }
 
-    fn load_items(path: &path::Path) -> serde_json::Value {
+    fn load_settings(path: &path::Path) -> Settings {
       match fs::File::open(path) {
         Ok(file) => {
           let reader = io::BufReader::new(file);
           match serde_json::from_reader(reader) {
-            Ok(items) => {
-              let items: serde_json::Value = items;
-              if items.is_object() {
-                return items;
-              }
-            }
+            Ok(settings) => return settings,
             Err(err) => println!("{path:?}: {err}"),
           }
         }
         Err(err) => println!("{path:?}: {err}"),
       }
-      serde_json::json!({})
+      Settings::default()
     }
 
     fn store_items(&self) {
@@ -134,7 +648,7 @@ mod inner {
         match fs::File::create(&self.path) {
           Ok(file) => {
             let writer = io::BufWriter::new(file);
-            match serde_json::to_writer(writer, &self.items) {
+            match serde_json::to_writer(writer, &self.settings) {
               Ok(()) => (),
               Err(err) => println!("{:?}: {err}", self.path),
             }
@@ -190,3 +704,119 @@ mod inner {
     }
   }
 }
+
+mod test {
+  /// Open storage using a temporary file.
+  #[cfg(test)]
+  fn open_storage(name: &str, clear: bool) -> super::Storage {
+    let name = format!("{}_{name}_test", crate::util::APP_NAME);
+    let path = std::env::temp_dir().join(name).with_extension("json");
+    if clear {
+      let _ = std::fs::remove_file(&path);
+    }
+    super::Storage::open(path, true)
+  }
+
+  #[test]
+  fn test_sanitized_json() {
+    let mut storage = open_storage("sanitized", true);
+    storage.set_asset_path("/home/user/charts/sectional".into());
+    storage.set_home(Some(crate::util::Coord { x: -120.0, y: 39.0 }));
+    let json = storage.sanitized_json();
+    assert!(json.contains("\"sectional\""));
+    assert!(!json.contains("/home/user"));
+
+    let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert!(value["home"].is_null());
+    assert!(value["bookmarks"].is_null());
+  }
+
+  #[test]
+  fn test_legacy_file() {
+    use crate::util::{CoordFormat, Units};
+
+    // Write a file using the original format, including a bad value.
+    let name = format!("{}_legacy_test", crate::util::APP_NAME);
+    let path = std::env::temp_dir().join(name).with_extension("json");
+    let json = serde_json::json!({
+      "win_info": {"pos": [10, 20], "size": [800, 600], "maxed": false},
+      "night_mode": true,
+      "asset_path": "/tmp/charts",
+      "sharpen": "bad",
+      "units": "sm",
+      "coord_format": "dm",
+    });
+    std::fs::write(&path, json.to_string()).unwrap();
+
+    let storage = super::Storage::open(path, true);
+    let win_info = storage.get_win_info();
+    assert!(win_info.pos == Some(crate::util::Pos { x: 10, y: 20 }));
+    assert!(win_info.size == Some(crate::util::Size { w: 800, h: 600 }));
+    assert!(!win_info.maxed);
+    assert!(storage.get_night_mode() == Some(true));
+    assert!(storage.get_asset_path().as_deref() == Some("/tmp/charts"));
+    assert!(storage.get_sharpen().is_none());
+    assert!(storage.get_units() == Some(Units::Statute));
+    assert!(storage.get_coord_format() == Some(CoordFormat::DegMin));
+  }
+
+  #[test]
+  fn test_reset() {
+    use crate::util::{Bookmark, Coord, Units};
+
+    let mut storage = open_storage("reset", true);
+    let win_info = crate::util::WinInfo {
+      maxed: true,
+      ..Default::default()
+    };
+    storage.set_win_info(&win_info);
+    storage.set_night_mode(true);
+    storage.set_units(Units::Metric);
+    let bookmarks = vec![Bookmark::new("Home".into(), Coord { x: -122.0, y: 38.0 })];
+    storage.set_bookmarks(bookmarks.clone());
+    let home = Coord { x: -121.5, y: 38.5 };
+    storage.set_home(Some(home));
+    storage.reset();
+    assert!(storage.get_win_info() == win_info);
+    assert!(storage.get_bookmarks() == Some(bookmarks));
+    assert!(storage.get_home() == Some(home));
+    assert!(storage.get_night_mode().is_none());
+    assert!(storage.get_units().is_none());
+  }
+
+  #[test]
+  fn test_units() {
+    use crate::util::Units;
+
+    let mut storage = open_storage("units", true);
+    assert!(storage.get_units().is_none());
+    for units in Units::ALL {
+      storage.set_units(units);
+      assert!(storage.get_units() == Some(units));
+    }
+
+    // Make sure the value survives a round-trip to the file.
+    storage.set_units(Units::Statute);
+    drop(storage);
+    let storage = open_storage("units", false);
+    assert!(storage.get_units() == Some(Units::Statute));
+  }
+
+  #[test]
+  fn test_coord_format() {
+    use crate::util::CoordFormat;
+
+    let mut storage = open_storage("coord_format", true);
+    assert!(storage.get_coord_format().is_none());
+    for format in CoordFormat::ALL {
+      storage.set_coord_format(format);
+      assert!(storage.get_coord_format() == Some(format));
+    }
+
+    // Make sure the value survives a round-trip to the file.
+    storage.set_coord_format(CoordFormat::DegMin);
+    drop(storage);
+    let storage = open_storage("coord_format", false);
+    assert!(storage.get_coord_format() == Some(CoordFormat::DegMin));
+  }
+}
@@ -0,0 +1,57 @@
+use crate::util;
+use eframe::{egui, emath};
+
+pub struct ConfirmDlg {
+  title: String,
+  text: String,
+}
+
+#[derive(Eq, PartialEq)]
+pub enum Response {
+  None,
+  Cancel,
+  Confirm,
+}
+
+impl ConfirmDlg {
+  pub fn open(title: String, text: String) -> Self {
+    Self { title, text }
+  }
+
+  pub fn show(&mut self, ctx: &egui::Context) -> Response {
+    let key = util::dlg_key(ctx);
+    let mut response = Response::None;
+    let mut open = key != util::DlgKey::Cancel;
+
+    egui::Window::new(egui::RichText::from(format!("❓  {}", self.title)).strong())
+      .open(&mut open)
+      .collapsible(false)
+      .resizable(false)
+      .anchor(emath::Align2::CENTER_CENTER, [0.0, 0.0])
+      .show(ctx, |ui| {
+        ui.add_space(8.0);
+        ui.vertical_centered(|ui| {
+          ui.add(egui::Label::new(&self.text).wrap(false));
+        });
+        ui.add_space(8.0);
+        ui.separator();
+        ui.horizontal(|ui| {
+          if ui.button("Ok").clicked() {
+            response = Response::Confirm;
+          }
+
+          if ui.button("Cancel").clicked() {
+            response = Response::Cancel;
+          }
+        });
+      });
+
+    if !open {
+      response = Response::Cancel;
+    } else if key == util::DlgKey::Confirm {
+      response = Response::Confirm;
+    }
+
+    response
+  }
+}
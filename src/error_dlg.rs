@@ -17,9 +17,8 @@ impl ErrorDlg {
   }
 
   pub fn show(&mut self, ctx: &egui::Context) -> bool {
-    if ctx
-      .input(|state| state.key_pressed(egui::Key::Enter) || state.key_pressed(egui::Key::Escape))
-    {
+    // Enter and Escape both close the dialog.
+    if util::dlg_key(ctx) != util::DlgKey::None {
       self.text = None;
     }
 
@@ -1,9 +1,11 @@
+use crate::util;
 use eframe::{egui, emath};
 use std::mem;
 
 #[derive(Default)]
 pub struct FindDlg {
   text: String,
+  chart_only: bool,
   focus: bool,
 }
 
@@ -11,20 +13,25 @@ pub struct FindDlg {
 pub enum Response {
   None,
   Cancel,
-  Term(String),
+  /// Search term and whether to limit the results to the chart.
+  Term(String, bool),
 }
 
 impl FindDlg {
-  pub fn open() -> Self {
+  /// Open the find dialog.
+  /// - `chart_only`: initial state of the "limit to chart" checkbox
+  pub fn open(chart_only: bool) -> Self {
     Self {
       text: String::new(),
+      chart_only,
       focus: true,
     }
   }
 
   pub fn show(&mut self, ctx: &egui::Context) -> Response {
+    let key = util::dlg_key(ctx);
     let mut response = Response::None;
-    let mut open = !ctx.input(|state| state.key_pressed(egui::Key::Escape));
+    let mut open = key != util::DlgKey::Cancel;
 
     egui::Window::new(egui::RichText::from("🔎  Find").strong())
       .open(&mut open)
@@ -41,17 +48,14 @@ impl FindDlg {
             self.focus = false;
             edit_response.request_focus();
           }
-
-          if edit_response.lost_focus() && ui.input(|state| state.key_pressed(egui::Key::Enter)) {
-            response = Response::Term(mem::take(&mut self.text));
-          }
         });
+        ui.checkbox(&mut self.chart_only, "Limit to chart");
         ui.add_space(8.0);
         ui.separator();
         ui.horizontal(|ui| {
           ui.add_enabled_ui(!self.text.is_empty(), |ui| {
             if ui.button("Ok").clicked() {
-              response = Response::Term(mem::take(&mut self.text));
+              response = Response::Term(mem::take(&mut self.text), self.chart_only);
             }
           });
 
@@ -63,6 +67,8 @@ impl FindDlg {
 
     if !open {
       response = Response::Cancel;
+    } else if key == util::DlgKey::Confirm && !self.text.is_empty() {
+      response = Response::Term(mem::take(&mut self.text), self.chart_only);
     }
 
     response
@@ -4,14 +4,19 @@
 #[macro_use]
 mod util;
 
+mod airport_dlg;
 mod app;
+mod bearing_dlg;
 mod chart;
 mod config;
+mod confirm_dlg;
 mod error_dlg;
 mod find_dlg;
 mod nasr;
+mod progress_dlg;
 mod select_dlg;
 mod select_menu;
+mod toast;
 mod touch;
 
 use eframe::egui;
@@ -26,6 +31,7 @@ struct Opts {
 
 fn parse_args() -> Opts {
   let mut sim = false;
+  let mut fullscreen = false;
   let mut theme = None;
   let mut deco = cfg!(not(feature = "mobile"));
   let icon = image::load_from_memory(util::APP_ICON).unwrap();
@@ -46,6 +52,9 @@ fn parse_args() -> Opts {
       // Hide window decorations.
       "--no-deco" => deco = false,
 
+      // Start in fullscreen mode.
+      "--fullscreen" => fullscreen = true,
+
       // Simulate what it would look like on a device like PinePhone or Librem 5.
       "--sim" => sim = cfg!(not(feature = "mobile")),
       _ => (),
@@ -53,6 +62,9 @@ fn parse_args() -> Opts {
   }
 
   let config = config::Storage::new(deco && !sim).unwrap();
+
+  // Fullscreen is also restored from the last session.
+  let fullscreen = !sim && (fullscreen || config.get_fullscreen().unwrap_or(false));
   let (viewport, scale) = {
     use eframe::emath;
     if sim {
@@ -75,6 +87,9 @@ fn parse_args() -> Opts {
       if let Some(size) = win_info.size {
         viewport = viewport.with_inner_size(size);
       }
+      if let Some(pos) = win_info.pos.filter(|_| !win_info.maxed) {
+        viewport = viewport.with_position(pos);
+      }
       (viewport, None)
     } else {
       let viewport = egui::ViewportBuilder::default()
@@ -85,7 +100,7 @@ fn parse_args() -> Opts {
   };
 
   let native = eframe::NativeOptions {
-    viewport,
+    viewport: viewport.with_fullscreen(fullscreen),
     ..Default::default()
   };
 
@@ -1,6 +1,6 @@
 use crate::util;
 use eframe::egui;
-use gdal::{errors, spatial_ref, vector};
+use gdal::{spatial_ref, vector};
 use std::{any, collections, path, sync, thread};
 use sync::{atomic, mpsc};
 
@@ -11,8 +11,9 @@ use sync::{atomic, mpsc};
 pub struct AirportReader {
   request_count: sync::Arc<atomic::AtomicI64>,
   airport_status: AirportStatusSync,
+  parse_stats: sync::Arc<sync::OnceLock<ParseStats>>,
   ctx: egui::Context,
-  tx: mpsc::Sender<AirportRequest>,
+  tx: util::WorkerSender<AirportRequest>,
   rx: mpsc::Receiver<AirportReply>,
 }
 
@@ -25,16 +26,17 @@ impl AirportReader {
   }
 
   fn _new(path: &path::Path, ctx: egui::Context) -> Result<Self, util::Error> {
-    let mut source = match AirportSource::open(path) {
-      Ok(source) => source,
-      Err(err) => {
-        let err = format!("Unable to open airport data source: {err}");
-        return Err(err.into());
-      }
-    };
+    let mut source = AirportSource::open(path)?;
+
+    // Make sure that this is the expected airport data.
+    if let Some(field) = source.missing_field() {
+      let err = format!("This doesn't look like NASR airport data\n(no {field} field)");
+      return Err(err.into());
+    }
 
     let airport_status = AirportStatusSync::new();
     let request_count = sync::Arc::new(atomic::AtomicI64::new(0));
+    let parse_stats = sync::Arc::new(sync::OnceLock::new());
     let (tx, trx) = mpsc::channel();
     let (ttx, rx) = mpsc::channel();
 
@@ -44,10 +46,17 @@ impl AirportReader {
       .spawn({
         let mut airport_status = airport_status.clone();
         let request_count = request_count.clone();
+        let parse_stats = parse_stats.clone();
         let ctx = ctx.clone();
         move || {
           // Create the name and ID indexes.
-          if source.create_basic_indexes() {
+          let (indexed, stats) = source.create_basic_indexes();
+          if stats.failed_count() > 0 {
+            println!("{}", stats.summary());
+          }
+          let _ = parse_stats.set(stats);
+
+          if indexed {
             airport_status.set_has_basic_idx();
           }
 
@@ -60,8 +69,10 @@ impl AirportReader {
           let send = {
             let ctx = ctx.clone();
             move |reply: AirportReply, dec: bool| {
-              ttx.send(reply).unwrap();
-              ctx.request_repaint();
+              // The receiver is gone if the reader was dropped, in which case the loop below exits.
+              if ttx.send(reply).is_ok() {
+                ctx.request_repaint();
+              }
               if dec {
                 assert!(request_count.fetch_sub(1, atomic::Ordering::Relaxed) > 0);
               }
@@ -71,8 +82,11 @@ impl AirportReader {
           // Request a repaint so that the UI knows the basic indexes are ready.
           ctx.request_repaint();
 
+          // Requests that were received while checking for superseded requests.
+          let mut queue = collections::VecDeque::new();
+
           // Wait for a message. Exit when the connection is closed.
-          while let Ok(request) = trx.recv() {
+          while let Some(request) = queue.pop_front().or_else(|| trx.recv().ok()) {
             match request {
               AirportRequest::SpatialRef(spatial_info) => {
                 if airport_status.get() >= AirportStatus::BasicIdx {
@@ -124,35 +138,77 @@ impl AirportReader {
               }
               AirportRequest::Nearby(coord, dist, nph) => {
                 let infos = source.nearby(coord, dist, nph);
-                send(AirportReply::Nearby(infos), true);
+                send(AirportReply::Nearby(coord, infos), true);
               }
-              AirportRequest::Search(term, nph) => {
-                if let Some(to_chart) = to_chart.as_ref() {
-                  let term = term.trim().to_uppercase();
-
-                  // Search for an airport ID first.
-                  let reply = if let Some(info) = source.airport(&term) {
-                    if to_chart.contains(info.coord) {
-                      AirportReply::Airport(info)
-                    } else {
-                      let err = format!("{}\nis not on this chart", info.desc);
-                      AirportReply::Error(err.into())
-                    }
+              AirportRequest::NearbyLive(coord, dist, nph) => {
+                // Skip the search if a newer live request is waiting.
+                queue.extend(trx.try_iter());
+                let superseded = queue
+                  .iter()
+                  .any(|request| matches!(request, AirportRequest::NearbyLive(..)));
+                let infos = if superseded {
+                  Vec::new()
+                } else {
+                  source.nearby(coord, dist, nph)
+                };
+                send(AirportReply::Nearby(coord, infos), true);
+              }
+              AirportRequest::MagVar(coord, dist) => {
+                let infos = source.nearby(coord, dist, true);
+                send(AirportReply::MagVar(coord, infos), true);
+              }
+              AirportRequest::NearbyNad83(coord, dist, nph) => {
+                let infos = source.nearby_nad83(coord, dist, nph);
+                send(AirportReply::Nearby(coord, infos), true);
+              }
+              AirportRequest::InBounds(bounds, nph) => {
+                let infos = source.in_bounds(&bounds, nph);
+                send(AirportReply::InBounds(bounds, infos), true);
+              }
+              AirportRequest::Remarks(id) => {
+                let remarks = source.remarks(&id);
+                send(AirportReply::Remarks(id, remarks), true);
+              }
+              AirportRequest::Search(term, bounds, nph) => {
+                // Limiting the search to bounds requires the chart transformation.
+                let within = match (bounds, to_chart.as_ref()) {
+                  (Some(_), None) => {
+                    let err = "Chart transformation is needed for search\n";
+                    send(AirportReply::Error(err.into()), true);
+                    continue;
+                  }
+                  (Some(bounds), Some(to_chart)) => Some(Within { to_chart, bounds }),
+                  (None, _) => None,
+                };
+
+                let term = term.trim().to_uppercase();
+
+                // Search for an airport ID first.
+                let reply = if let Some(info) = source.airport(&term) {
+                  if within
+                    .as_ref()
+                    .map_or(true, |within| within.contains(info.coord))
+                  {
+                    AirportReply::Airport(info)
                   } else {
-                    // Airport ID not found, search the airport names.
-                    let infos = source.search(&term, to_chart, nph);
-                    if infos.is_empty() {
-                      let err = format!("Nothing on this chart matches\n'{term}'");
-                      AirportReply::Error(err.into())
-                    } else {
-                      AirportReply::Search(infos)
-                    }
-                  };
-                  send(reply, true);
+                    let err = format!("{}\nis not on this chart", info.desc);
+                    AirportReply::Error(err.into())
+                  }
                 } else {
-                  let err = "Chart transformation is needed for search\n";
-                  send(AirportReply::Error(err.into()), true);
-                }
+                  // Airport ID not found, search the airport IDs and names.
+                  let (infos, truncated) = source.search(&term, within.as_ref(), nph);
+                  if infos.is_empty() {
+                    let err = if within.is_some() {
+                      format!("Nothing on this chart matches\n'{term}'")
+                    } else {
+                      format!("Nothing matches\n'{term}'")
+                    };
+                    AirportReply::Error(err.into())
+                  } else {
+                    AirportReply::Search(infos, truncated)
+                  }
+                };
+                send(reply, true);
               }
             }
           }
@@ -163,8 +219,9 @@ impl AirportReader {
     Ok(Self {
       request_count,
       airport_status,
+      parse_stats,
       ctx,
-      tx,
+      tx: util::WorkerSender::new(tx),
       rx,
     })
   }
@@ -174,6 +231,12 @@ impl AirportReader {
     self.airport_status.get() >= AirportStatus::BasicIdx
   }
 
+  /// Statistics about records that couldn't be parsed, available once the basic indexes have been
+  /// created.
+  pub fn parse_stats(&self) -> Option<&ParseStats> {
+    self.parse_stats.get()
+  }
+
   /// True if the airport source has a spatial index.
   pub fn airport_spatial_idx(&self) -> bool {
     self.airport_status.get() >= AirportStatus::SpatialIdx
@@ -185,13 +248,13 @@ impl AirportReader {
   /// - `bounds`: Chart bounds in LCC coordinates.
   pub fn set_spatial_ref(&self, proj4: String, bounds: util::Bounds) {
     let request = AirportRequest::SpatialRef(Some((proj4, bounds)));
-    self.tx.send(request).unwrap();
+    self.tx.send(request);
   }
 
   /// Clear the chart spatial reference.
   pub fn clear_spatial_ref(&self) {
     let request = AirportRequest::SpatialRef(None);
-    self.tx.send(request).unwrap();
+    self.tx.send(request);
   }
 
   /// Lookup airport information using it's identifier.
@@ -200,9 +263,7 @@ impl AirportReader {
   #[allow(unused)]
   pub fn airport(&self, id: String) {
     if !id.is_empty() {
-      self.tx.send(AirportRequest::Airport(id)).unwrap();
-      self.request_count.fetch_add(1, atomic::Ordering::Relaxed);
-      self.ctx.request_repaint();
+      self.send(AirportRequest::Airport(id));
     }
   }
 
@@ -213,24 +274,68 @@ impl AirportReader {
   /// - `nph`: include non-public heliports
   pub fn nearby(&self, coord: util::Coord, dist: f64, nph: bool) {
     if dist >= 0.0 {
-      self
-        .tx
-        .send(AirportRequest::Nearby(coord, dist, nph))
-        .unwrap();
-      self.request_count.fetch_add(1, atomic::Ordering::Relaxed);
-      self.ctx.request_repaint();
+      self.send(AirportRequest::Nearby(coord, dist, nph));
     }
   }
 
-  /// Find an airport by ID or airport(s) by (partial) name match.
+  /// Request nearby airports for a list that follows the view. The reply is empty if a newer live
+  /// request arrives before this one is started.
   /// > **NOTE**: requires a chart spatial reference.
+  /// - `coord`: chart coordinate (LCC)
+  /// - `dist`: search distance in meters
+  /// - `nph`: include non-public heliports
+  pub fn nearby_live(&self, coord: util::Coord, dist: f64, nph: bool) {
+    if dist >= 0.0 {
+      self.send(AirportRequest::NearbyLive(coord, dist, nph));
+    }
+  }
+
+  /// Request the airports near a location for looking up its magnetic variation. The reply is kept
+  /// separate from the other nearby requests.
+  /// > **NOTE**: requires a chart spatial reference.
+  /// - `coord`: chart coordinate (LCC)
+  /// - `dist`: search distance in meters
+  pub fn mag_var(&self, coord: util::Coord, dist: f64) {
+    if dist >= 0.0 {
+      self.send(AirportRequest::MagVar(coord, dist));
+    }
+  }
+
+  /// Request nearby airports using NAD83 coordinates. This is slower than `nearby` because it
+  /// checks every airport.
+  /// > **NOTE**: does not require a chart spatial reference.
+  /// - `coord`: NAD83 coordinate
+  /// - `dist`: search distance in meters
+  /// - `nph`: include non-public heliports
+  pub fn nearby_nad83(&self, coord: util::Coord, dist: f64, nph: bool) {
+    if dist >= 0.0 {
+      self.send(AirportRequest::NearbyNad83(coord, dist, nph));
+    }
+  }
+
+  /// Request airports within chart bounds.
+  /// > **NOTE**: requires a chart spatial reference.
+  /// - `bounds`: chart (LCC) bounds
+  /// - `nph`: include non-public heliports
+  pub fn in_bounds(&self, bounds: util::Bounds, nph: bool) {
+    self.send(AirportRequest::InBounds(bounds, nph));
+  }
+
+  /// Request the remarks for an airport.
+  /// > **NOTE**: Does not require a chart spatial reference.
+  /// - `id`: airport ID
+  pub fn remarks(&self, id: String) {
+    self.send(AirportRequest::Remarks(id));
+  }
+
+  /// Find an airport by ID or airport(s) by (partial) name match.
+  /// > **NOTE**: requires a chart spatial reference if `bounds` is specified.
   /// - `term`: search term
+  /// - `bounds`: only include airports within these chart (LCC) bounds
   /// - `nph`: include non-public heliports
-  pub fn search(&self, term: String, nph: bool) {
+  pub fn search(&self, term: String, bounds: Option<util::Bounds>, nph: bool) {
     if !term.is_empty() {
-      self.tx.send(AirportRequest::Search(term, nph)).unwrap();
-      self.request_count.fetch_add(1, atomic::Ordering::Relaxed);
-      self.ctx.request_repaint();
+      self.send(AirportRequest::Search(term, bounds, nph));
     }
   }
 
@@ -243,29 +348,82 @@ impl AirportReader {
   pub fn get_replies(&self) -> Vec<AirportReply> {
     self.rx.try_iter().collect()
   }
+
+  /// False if the reader's thread has exited and it can't handle any more requests.
+  pub fn is_available(&self) -> bool {
+    self.tx.is_available()
+  }
+
+  /// Send a request that has a reply.
+  /// - `request`: request to send
+  fn send(&self, request: AirportRequest) {
+    // The reply never arrives if the worker is gone, so don't count it as pending.
+    if self.tx.send(request) {
+      self.request_count.fetch_add(1, atomic::Ordering::Relaxed);
+      self.ctx.request_repaint();
+    }
+  }
 }
 
 enum AirportRequest {
   SpatialRef(Option<(String, util::Bounds)>),
   Airport(String),
   Nearby(util::Coord, f64, bool),
-  Search(String, bool),
+  NearbyLive(util::Coord, f64, bool),
+  MagVar(util::Coord, f64),
+  NearbyNad83(util::Coord, f64, bool),
+  InBounds(util::Bounds, bool),
+  Remarks(String),
+  Search(String, Option<util::Bounds>, bool),
 }
 
 pub enum AirportReply {
   /// Airport info from ID search.
   Airport(AirportInfo),
 
-  /// Airport infos from a nearby search.
-  Nearby(Vec<AirportInfo>),
+  /// Airport infos from a nearby search around the requested chart coordinate.
+  Nearby(util::Coord, Vec<AirportInfo>),
+
+  /// Airport infos around the requested chart coordinate for a magnetic variation lookup.
+  MagVar(util::Coord, Vec<AirportInfo>),
+
+  /// Airport infos within the requested chart bounds.
+  InBounds(util::Bounds, Vec<AirportInfo>),
+
+  /// Remarks for the requested airport ID.
+  Remarks(String, Vec<String>),
 
-  /// Airport infos matching a name search.
-  Search(Vec<AirportInfo>),
+  /// Grouped airport infos matching a search and true if there were too many to return.
+  Search(Vec<(SearchGroup, AirportInfo)>, bool),
 
   /// Request resulted in an error.
   Error(util::Error),
 }
 
+/// Groups of search results, in order of precedence.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SearchGroup {
+  /// Airport IDs that start with the search term.
+  Id,
+
+  /// Airport names that contain the search term.
+  Name,
+
+  /// Airport names that are similar to the search term.
+  Similar,
+}
+
+impl SearchGroup {
+  /// Header text for the UI.
+  pub fn text(self) -> &'static str {
+    match self {
+      Self::Id => "Matching IDs",
+      Self::Name => "Matching Names",
+      Self::Similar => "Similar Names",
+    }
+  }
+}
+
 struct ToChart {
   /// Coordinate transformation from NAD83 to LCC.
   trans: spatial_ref::CoordTransform,
@@ -277,15 +435,36 @@ struct ToChart {
 impl ToChart {
   /// Test if a NAD83 coordinate is contained within the chart bounds.
   fn contains(&self, nad83: util::Coord) -> bool {
+    self.within(nad83, &self.bounds)
+  }
+
+  /// Test if a NAD83 coordinate is contained within chart (LCC) bounds.
+  fn within(&self, nad83: util::Coord, bounds: &util::Bounds) -> bool {
     use util::Transform;
     match self.trans.transform(nad83) {
-      Ok(lcc) => return self.bounds.contains(lcc),
+      Ok(lcc) => return bounds.contains(lcc),
       Err(err) => println!("{err}"),
     }
     false
   }
 }
 
+/// Bounds used to limit a search.
+struct Within<'a> {
+  /// Coordinate transformation from NAD83 to LCC.
+  to_chart: &'a ToChart,
+
+  /// Search bounds in LCC coordinates.
+  bounds: util::Bounds,
+}
+
+impl Within<'_> {
+  /// Test if a NAD83 coordinate is contained within the search bounds.
+  fn contains(&self, nad83: util::Coord) -> bool {
+    self.to_chart.within(nad83, &self.bounds)
+  }
+}
+
 #[derive(Eq, Ord, PartialEq, PartialOrd)]
 enum AirportStatus {
   None,
@@ -343,9 +522,11 @@ impl AirportStatusSync {
 
 struct AirportSource {
   dataset: gdal::Dataset,
+  remarks: Option<gdal::Dataset>,
   count: u64,
   name_vec: Vec<(String, u64)>,
   id_map: collections::HashMap<String, u64>,
+  remark_map: collections::HashMap<String, Vec<u64>>,
   sp_idx: rstar::RTree<LocIdx>,
 }
 
@@ -359,30 +540,62 @@ impl AirportSource {
 
   /// Open an airport data source.
   /// - `path`: NASR airport CSV file path
-  fn open(path: &path::Path) -> Result<Self, errors::GdalError> {
+  fn open(path: &path::Path) -> Result<Self, util::Error> {
     use gdal::vector::LayerAccess;
 
-    // Open the dataset and get the layer.
-    let dataset = gdal::Dataset::open_ex(path, Self::open_options())?;
-    let layer = dataset.layer(0)?;
+    // Open the dataset.
+    let dataset = match gdal::Dataset::open_ex(path, Self::open_options()) {
+      Ok(dataset) => dataset,
+      Err(err) => {
+        let err = format!("Unable to open airport data source: {err}");
+        return Err(err.into());
+      }
+    };
+
+    // Get the layer by name rather than assuming that it's the first one.
+    let Ok(layer) = dataset.layer_by_name(Self::BASE_LAYER) else {
+      let err = format!(
+        "This doesn't look like NASR airport data\n(no {} layer)",
+        Self::BASE_LAYER
+      );
+      return Err(err.into());
+    };
     let count = layer.feature_count();
 
+    // Remarks are in a separate file, which may not be present in older data.
+    let path = path.with_file_name([Self::REMARK_LAYER, ".csv"].concat());
+    let remarks = gdal::Dataset::open_ex(path, Self::open_options()).ok();
+    let remarks = remarks.filter(|remarks| remarks.layer_by_name(Self::REMARK_LAYER).is_ok());
+
     Ok(Self {
       dataset,
+      remarks,
       count,
       name_vec: Vec::new(),
       id_map: collections::HashMap::new(),
+      remark_map: collections::HashMap::new(),
       sp_idx: rstar::RTree::new(),
     })
   }
 
-  // Create the name and ID indexes.
-  fn create_basic_indexes(&mut self) -> bool {
+  /// Get the first required field that's missing from the airport data, if any.
+  fn missing_field(&self) -> Option<&'static str> {
+    use vector::LayerAccess;
+    let layer = self.layer();
+    let fields: collections::HashSet<String> = layer.defn().fields().map(|f| f.name()).collect();
+    AirportInfo::REQUIRED_FIELDS
+      .into_iter()
+      .find(|field| !fields.contains(*field))
+  }
+
+  // Create the name and ID indexes. Also returns statistics about records that can't be parsed.
+  fn create_basic_indexes(&mut self) -> (bool, ParseStats) {
     use vector::LayerAccess;
 
     let count = self.count as usize;
     let mut name_vec = Vec::with_capacity(count);
     let mut id_map = collections::HashMap::with_capacity(count);
+    let mut stats = ParseStats::default();
     for feature in self.layer().features() {
       if let Some(fid) = feature.fid() {
         // Add the airport name to the name vector.
@@ -394,12 +607,44 @@ impl AirportSource {
         if let Some(id) = feature.get_string(AirportInfo::AIRPORT_ID) {
           id_map.insert(id, fid);
         }
+
+        // Also index ICAO IDs so that they can be found with a search.
+        if let Some(id) = feature.get_icao_id() {
+          id_map.entry(id).or_insert(fid);
+        }
+      }
+
+      stats.add(AirportInfo::new(feature).err());
+    }
+
+    // Index the remarks by airport ID.
+    let mut remark_map: collections::HashMap<String, Vec<u64>> = collections::HashMap::new();
+    if let Some(layer) = self.remark_layer() {
+      for feature in layer.features() {
+        if let (Some(fid), Some(id)) = (feature.fid(), feature.get_string(AirportInfo::AIRPORT_ID))
+        {
+          remark_map.entry(id).or_default().push(fid);
+        }
       }
     }
 
     self.name_vec = name_vec;
     self.id_map = id_map;
-    !self.name_vec.is_empty() && !self.id_map.is_empty()
+    self.remark_map = remark_map;
+    (!self.name_vec.is_empty() && !self.id_map.is_empty(), stats)
+  }
+
+  /// Get the remarks for an airport.
+  /// - `id`: airport ID
+  fn remarks(&self, id: &str) -> Vec<String> {
+    use vector::LayerAccess;
+    let (Some(fids), Some(layer)) = (self.remark_map.get(id), self.remark_layer()) else {
+      return Vec::new();
+    };
+
+    let remarks = fids.iter().filter_map(|fid| layer.feature(*fid));
+    let remarks = remarks.filter_map(|feature| feature.get_string("REMARK"));
+    remarks.filter(|remark| !remark.trim().is_empty()).collect()
   }
 
   /// Create the spatial index.
@@ -431,7 +676,7 @@ impl AirportSource {
     use vector::LayerAccess;
     let layer = self.layer();
     if let Some(fid) = self.id_map.get(id) {
-      return layer.feature(*fid).and_then(AirportInfo::new);
+      return layer.feature(*fid).and_then(|f| AirportInfo::new(f).ok());
     }
     None
   }
@@ -458,7 +703,7 @@ impl AirportSource {
 
     let mut airports = Vec::with_capacity(fids.len());
     for fid in fids {
-      if let Some(info) = layer.feature(fid).and_then(AirportInfo::new) {
+      if let Some(info) = layer.feature(fid).and_then(|f| AirportInfo::new(f).ok()) {
         if nph || !info.non_public_heliport() {
           airports.push(info);
         }
@@ -469,19 +714,21 @@ impl AirportSource {
     airports
   }
 
-  /// Search for airports with names that contain the specified text.
-  /// - `term`: search text
-  /// - `to_chart`: coordinate transformation and chart bounds
+  /// Find airports within a search radius without using the spatial index.
+  /// - `coord`: NAD83 coordinate
+  /// - `dist`: search distance in meters
   /// - `nph`: include non-public heliports
-  fn search(&self, term: &str, to_chart: &ToChart, nph: bool) -> Vec<AirportInfo> {
+  fn nearby_nad83(&self, coord: util::Coord, dist: f64, nph: bool) -> Vec<AirportInfo> {
     use vector::LayerAccess;
-    let layer = self.layer();
     let mut airports = Vec::new();
-    for (name, fid) in &self.name_vec {
-      if name.contains(term) {
-        if let Some(info) = layer.feature(*fid).and_then(AirportInfo::new) {
-          // Make sure the coordinate (NAD83) is within the chart bounds.
-          if (nph || !info.non_public_heliport()) && to_chart.contains(info.coord) {
+    for feature in self.layer().features() {
+      let Some(loc) = feature.get_coord() else {
+        continue;
+      };
+
+      if util::gc_distance(coord, loc) <= dist {
+        if let Ok(info) = AirportInfo::new(feature) {
+          if nph || !info.non_public_heliport() {
             airports.push(info);
           }
         }
@@ -492,9 +739,165 @@ impl AirportSource {
     airports
   }
 
+  /// Find airports within chart bounds.
+  /// > **NOTE**: requires spatial index.
+  /// - `bounds`: chart (LCC) bounds
+  /// - `nph`: include non-public heliports
+  fn in_bounds(&self, bounds: &util::Bounds, nph: bool) -> Vec<AirportInfo> {
+    use vector::LayerAccess;
+    let layer = self.layer();
+    let min = [bounds.min.x, bounds.min.y];
+    let max = [bounds.max.x, bounds.max.y];
+    let envelope = rstar::AABB::from_corners(min, max);
+
+    // Collect the feature IDs.
+    let mut fids = Vec::new();
+    for item in self.sp_idx.locate_in_envelope(&envelope) {
+      fids.push(item.fid);
+    }
+
+    // Sort the feature IDs so that lookups are sequential.
+    fids.sort_unstable();
+
+    let mut airports = Vec::with_capacity(fids.len());
+    for fid in fids {
+      if let Some(info) = layer.feature(fid).and_then(|f| AirportInfo::new(f).ok()) {
+        if nph || !info.non_public_heliport() {
+          airports.push(info);
+        }
+      }
+    }
+    airports
+  }
+
+  /// Search for airports with IDs that start with the specified text, names that contain it and
+  /// names that are similar to it, in that order. Returns the airports along with their groups
+  /// and true if any of the groups were truncated.
+  /// - `term`: search text
+  /// - `within`: search bounds, if the search is limited
+  /// - `nph`: include non-public heliports
+  fn search(
+    &self,
+    term: &str,
+    within: Option<&Within>,
+    nph: bool,
+  ) -> (Vec<(SearchGroup, AirportInfo)>, bool) {
+    use vector::LayerAccess;
+
+    // Match the IDs and names first, since reading the features is comparatively slow.
+    let mut ids: Vec<(&str, u64)> = self
+      .id_map
+      .iter()
+      .filter(|(id, _)| id.starts_with(term))
+      .map(|(id, fid)| (id.as_str(), *fid))
+      .collect();
+
+    // Shorter IDs are closer matches.
+    ids.sort_unstable_by(|a, b| a.0.len().cmp(&b.0.len()).then(a.0.cmp(b.0)));
+
+    let mut exact = Vec::new();
+    let mut prefix = Vec::new();
+    let mut contains = Vec::new();
+    let mut similar = Vec::new();
+    for (name, fid) in &self.name_vec {
+      if name == term {
+        exact.push(*fid);
+      } else if name.starts_with(term) {
+        prefix.push(*fid);
+      } else if name.contains(term) {
+        contains.push(*fid);
+      } else if let Some(dist) = util::fuzzy_match(term, name) {
+        similar.push((dist, *fid));
+      }
+    }
+
+    // Closest matches first.
+    similar.sort_unstable_by_key(|(dist, _)| *dist);
+
+    let groups = [
+      (
+        SearchGroup::Id,
+        vec![ids.into_iter().map(|(_, fid)| fid).collect()],
+      ),
+      (SearchGroup::Name, vec![exact, prefix, contains]),
+      (
+        SearchGroup::Similar,
+        vec![similar.into_iter().map(|(_, fid)| fid).collect()],
+      ),
+    ];
+
+    let layer = self.layer();
+    let mut found = collections::HashSet::new();
+    let mut airports = Vec::new();
+    let mut truncated = false;
+    for (group, tiers) in groups {
+      let mut count = 0;
+      for fids in tiers {
+        let start = airports.len();
+        let mut full = false;
+        for fid in fids {
+          // The same airport may be matched by more than one ID or group.
+          if found.contains(&fid) {
+            continue;
+          }
+
+          let Some(info) = layer.feature(fid).and_then(|f| AirportInfo::new(f).ok()) else {
+            continue;
+          };
+
+          // Make sure the coordinate (NAD83) is within the search bounds.
+          let inside = within.map_or(true, |within| within.contains(info.coord));
+          if !inside || (!nph && info.non_public_heliport()) {
+            continue;
+          }
+
+          if count == Self::MAX_GROUP_RESULTS {
+            // A result is being dropped: stop here and let the user know that there are more.
+            truncated = true;
+            full = true;
+            break;
+          }
+
+          // Only airports that are listed are marked as found so that one left out of a full group
+          // can still be listed by a later group.
+          found.insert(fid);
+          airports.push((group, info));
+          count += 1;
+        }
+
+        // Names of equal relevance are sorted alphabetically.
+        if group == SearchGroup::Name {
+          airports[start..].sort_unstable_by(|a, b| a.1.desc.cmp(&b.1.desc));
+        }
+
+        // Skip the remaining tiers once the group is full.
+        if full {
+          break;
+        }
+      }
+    }
+
+    (airports, truncated)
+  }
+
   fn layer(&self) -> vector::Layer {
-    self.dataset.layer(0).unwrap()
+    // The layer was checked when the source was opened.
+    self.dataset.layer_by_name(Self::BASE_LAYER).unwrap()
   }
+
+  fn remark_layer(&self) -> Option<vector::Layer> {
+    let remarks = self.remarks.as_ref()?;
+    remarks.layer_by_name(Self::REMARK_LAYER).ok()
+  }
+
+  /// Airport layer name, which GDAL takes from the CSV file name.
+  const BASE_LAYER: &'static str = "APT_BASE";
+
+  /// Airport remarks layer name.
+  const REMARK_LAYER: &'static str = "APT_RMK";
+
+  /// Maximum number of airports returned for each search group.
+  const MAX_GROUP_RESULTS: usize = 50;
 }
 
 /// Location spatial index item.
@@ -523,7 +926,7 @@ impl rstar::PointDistance for LocIdx {
 }
 
 /// Airport information.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct AirportInfo {
   /// Feature record ID.
   #[allow(unused)]
@@ -532,6 +935,9 @@ pub struct AirportInfo {
   /// Airport ID.
   pub id: String,
 
+  /// ICAO airport ID, if it has one.
+  pub icao_id: Option<String>,
+
   /// Airport name.
   pub name: String,
 
@@ -544,31 +950,59 @@ pub struct AirportInfo {
   /// Airport usage.
   pub airport_use: AirportUse,
 
+  /// Operational status.
+  pub status: OperStatus,
+
+  /// True if the airport has a control tower.
+  pub towered: bool,
+
+  /// Magnetic variation in degrees, east is positive.
+  pub mag_var: Option<f64>,
+
   /// Short description for UI lists.
   pub desc: String,
 }
 
 impl AirportInfo {
-  fn new(feature: vector::Feature) -> Option<Self> {
+  fn new(feature: vector::Feature) -> Result<Self, ParseFailure> {
     let mut info = Self {
-      fid: feature.fid()?,
-      id: feature.get_string(AirportInfo::AIRPORT_ID)?,
-      name: feature.get_string(AirportInfo::AIRPORT_NAME)?,
-      coord: feature.get_coord()?,
-      airport_type: feature.get_airport_type()?,
-      airport_use: feature.get_airport_use()?,
+      fid: feature.fid().ok_or(ParseFailure("record ID"))?,
+      id: feature
+        .get_string(AirportInfo::AIRPORT_ID)
+        .ok_or(ParseFailure(AirportInfo::AIRPORT_ID))?,
+      icao_id: feature.get_icao_id(),
+      name: feature
+        .get_string(AirportInfo::AIRPORT_NAME)
+        .ok_or(ParseFailure(AirportInfo::AIRPORT_NAME))?,
+      coord: feature
+        .get_coord()
+        .ok_or(ParseFailure("LAT_DECIMAL/LONG_DECIMAL"))?,
+      airport_type: feature
+        .get_airport_type()
+        .ok_or(ParseFailure("SITE_TYPE_CODE"))?,
+      airport_use: feature
+        .get_airport_use()
+        .ok_or(ParseFailure("OWNERSHIP_TYPE_CODE/FACILITY_USE_CODE"))?,
+      status: feature.get_oper_status(),
+      towered: feature.get_towered(),
+      mag_var: feature.get_mag_var(),
       desc: String::new(),
     };
 
     info.desc = format!(
       "{} ({}), {}, {}",
       info.short_name(),
-      info.id,
+      info.ids(),
       info.airport_type.abv(),
       info.airport_use.abv()
     );
 
-    Some(info)
+    // Flag closed airports so that they stand out in lists.
+    if info.is_closed() {
+      info.desc += ", CLOSED";
+    }
+
+    Ok(info)
   }
 
   /// Returns a potentially shortened airport name.
@@ -580,6 +1014,24 @@ impl AirportInfo {
     &self.name
   }
 
+  /// Returns the airport ID along with the ICAO ID if it's different.
+  pub fn ids(&self) -> String {
+    match &self.icao_id {
+      Some(icao_id) if *icao_id != self.id => format!("{}/{icao_id}", self.id),
+      _ => self.id.clone(),
+    }
+  }
+
+  /// Returns true if the airport is closed.
+  pub fn is_closed(&self) -> bool {
+    self.status != OperStatus::Operational
+  }
+
+  /// Returns true if the airport is for private use.
+  pub fn is_private(&self) -> bool {
+    self.airport_use == AirportUse::Private
+  }
+
   /// Returns true if this is a non-public heliport.
   pub fn non_public_heliport(&self) -> bool {
     self.airport_type == AirportType::Helicopter && self.airport_use != AirportUse::Public
@@ -587,6 +1039,63 @@ impl AirportInfo {
 
   const AIRPORT_ID: &'static str = "ARPT_ID";
   const AIRPORT_NAME: &'static str = "ARPT_NAME";
+
+  /// Fields that must be present for the data to be usable.
+  const REQUIRED_FIELDS: [&'static str; 5] = [
+    AirportInfo::AIRPORT_ID,
+    AirportInfo::AIRPORT_NAME,
+    "LAT_DECIMAL",
+    "LONG_DECIMAL",
+    "SITE_TYPE_CODE",
+  ];
+}
+
+/// The missing or invalid field that prevents an airport record from being parsed.
+#[derive(Debug)]
+struct ParseFailure(&'static str);
+
+/// Counts of airport records that couldn't be parsed, by reason.
+#[derive(Debug, Default)]
+pub struct ParseStats {
+  /// Total number of records.
+  pub total: u64,
+
+  /// Number of failed records for each missing or invalid field.
+  pub failed: collections::BTreeMap<&'static str, u64>,
+}
+
+impl ParseStats {
+  /// Add a record.
+  /// - `failure`: the reason the record failed to parse, if any
+  fn add(&mut self, failure: Option<ParseFailure>) {
+    self.total += 1;
+    if let Some(ParseFailure(field)) = failure {
+      *self.failed.entry(field).or_default() += 1;
+    }
+  }
+
+  /// Number of records that couldn't be parsed.
+  pub fn failed_count(&self) -> u64 {
+    self.failed.values().sum()
+  }
+
+  /// Number of records that were loaded.
+  pub fn loaded_count(&self) -> u64 {
+    self.total.saturating_sub(self.failed_count())
+  }
+
+  /// Text describing the failures.
+  pub fn summary(&self) -> String {
+    let mut text = format!(
+      "{} of {} airport records skipped",
+      self.failed_count(),
+      self.total
+    );
+    for (field, count) in &self.failed {
+      text += &format!("\n{field}: {count}");
+    }
+    text
+  }
 }
 
 trait GetF64 {
@@ -621,7 +1130,7 @@ impl GetString for vector::Feature<'_> {
   }
 }
 
-#[derive(Eq, Debug, PartialEq)]
+#[derive(Clone, Eq, Debug, PartialEq)]
 pub enum AirportType {
   Airport,
   Balloon,
@@ -645,6 +1154,19 @@ impl AirportType {
   }
 }
 
+trait GetIcaoId {
+  fn get_icao_id(&self) -> Option<String>;
+}
+
+impl GetIcaoId for vector::Feature<'_> {
+  fn get_icao_id(&self) -> Option<String> {
+    // Not all airports have an ICAO ID.
+    let id = self.field_as_string_by_name("ICAO_ID").ok()??;
+    let id = id.trim();
+    (!id.is_empty()).then(|| id.to_owned())
+  }
+}
+
 trait GetAirportType {
   fn get_airport_type(&self) -> Option<AirportType>;
 }
@@ -663,7 +1185,7 @@ impl GetAirportType for vector::Feature<'_> {
   }
 }
 
-#[derive(Eq, Debug, PartialEq)]
+#[derive(Clone, Eq, Debug, PartialEq)]
 pub enum AirportUse {
   AirForce,
   Army,
@@ -708,6 +1230,71 @@ impl GetAirportUse for vector::Feature<'_> {
   }
 }
 
+#[derive(Clone, Eq, Debug, PartialEq)]
+pub enum OperStatus {
+  Operational,
+  ClosedIndefinitely,
+  ClosedPermanently,
+}
+
+impl OperStatus {
+  /// Descriptive text for the UI.
+  pub fn text(&self) -> &'static str {
+    match *self {
+      Self::Operational => "Operational",
+      Self::ClosedIndefinitely => "Closed Indefinitely",
+      Self::ClosedPermanently => "Closed Permanently",
+    }
+  }
+}
+
+trait GetOperStatus {
+  fn get_oper_status(&self) -> OperStatus;
+}
+
+impl GetOperStatus for vector::Feature<'_> {
+  fn get_oper_status(&self) -> OperStatus {
+    // Older data may not have the status field, so assume that the airport is operational.
+    match self.field_as_string_by_name("ARPT_STATUS") {
+      Ok(Some(status)) => match status.trim() {
+        "CI" => OperStatus::ClosedIndefinitely,
+        "CP" => OperStatus::ClosedPermanently,
+        _ => OperStatus::Operational,
+      },
+      _ => OperStatus::Operational,
+    }
+  }
+}
+
+trait GetTowered {
+  fn get_towered(&self) -> bool;
+}
+
+impl GetTowered for vector::Feature<'_> {
+  fn get_towered(&self) -> bool {
+    // Tower types are "ATCT", "NON-ATCT" and combinations like "ATCT-TRACON".
+    match self.field_as_string_by_name("TWR_TYPE_CODE") {
+      Ok(Some(code)) => code.trim().starts_with("ATCT"),
+      _ => false,
+    }
+  }
+}
+
+trait GetMagVar {
+  fn get_mag_var(&self) -> Option<f64>;
+}
+
+impl GetMagVar for vector::Feature<'_> {
+  fn get_mag_var(&self) -> Option<f64> {
+    let var = self.get_f64("MAG_VARN")?;
+    match self.get_string("MAG_HEMIS")?.trim() {
+      "E" => Some(var),
+      "W" => Some(-var),
+      _ => None,
+    }
+  }
+}
+
 trait GetCoord {
   fn get_coord(&self) -> Option<util::Coord>;
 }
@@ -720,3 +1307,92 @@ impl GetCoord for vector::Feature<'_> {
     })
   }
 }
+
+mod test {
+  #[test]
+  fn test_source_layer() {
+    let folder = ["/vsimem/", crate::util::APP_NAME, "_layer_test"].concat();
+    let csv = b"ARPT_ID,ARPT_NAME\n".to_vec();
+
+    // The layer is found by name.
+    let path = [&folder, "/APT_BASE.csv"].concat();
+    gdal::vsi::create_mem_file(&path, csv.clone()).unwrap();
+    let source = super::AirportSource::open(std::path::Path::new(&path));
+    let _ = gdal::vsi::unlink_mem_file(&path);
+    assert!(source.is_ok_and(|source| source.remarks.is_none()));
+
+    // Other CSV files are rejected.
+    let path = [&folder, "/APT_RWY.csv"].concat();
+    gdal::vsi::create_mem_file(&path, csv).unwrap();
+    let source = super::AirportSource::open(std::path::Path::new(&path));
+    let _ = gdal::vsi::unlink_mem_file(&path);
+    assert!(source.is_err_and(|err| err.contains("no APT_BASE layer")));
+  }
+
+  #[test]
+  fn test_parse_stats() {
+    let mut stats = super::ParseStats::default();
+    stats.add(None);
+    stats.add(Some(super::ParseFailure("ARPT_ID")));
+    stats.add(Some(super::ParseFailure("SITE_TYPE_CODE")));
+    stats.add(Some(super::ParseFailure("ARPT_ID")));
+    assert!(stats.total == 4);
+    assert!(stats.failed_count() == 3);
+    assert!(stats.loaded_count() == 1);
+    assert!(stats.summary() == "3 of 4 airport records skipped\nARPT_ID: 2\nSITE_TYPE_CODE: 1");
+  }
+
+  #[test]
+  fn test_search_bounds() {
+    use crate::util::{Bounds, Coord};
+    use gdal::spatial_ref::{CoordTransform, SpatialRef};
+
+    let nad83 = SpatialRef::from_epsg(4269).unwrap();
+    nad83.set_axis_mapping_strategy(0);
+
+    let proj4 = "+proj=lcc +lat_0=38 +lon_0=-122 +lat_1=33.6666666666667 +lat_2=39.3333333333333 \
+                 +x_0=0 +y_0=0 +datum=NAD83 +units=m +no_defs";
+    let lcc = SpatialRef::from_proj4(proj4).unwrap();
+    let trans = CoordTransform::new(&nad83, &lcc).unwrap();
+
+    // 100 km around the projection origin.
+    let bounds = Bounds {
+      min: Coord {
+        x: -100000.0,
+        y: -100000.0,
+      },
+      max: Coord {
+        x: 100000.0,
+        y: 100000.0,
+      },
+    };
+
+    let to_chart = super::ToChart {
+      trans,
+      bounds: bounds.clone(),
+    };
+
+    // Search within the western half of the chart.
+    let mut west = bounds;
+    west.max.x = 0.0;
+    let within = super::Within {
+      to_chart: &to_chart,
+      bounds: west,
+    };
+
+    // West of the origin.
+    let coord = Coord { x: -122.5, y: 38.0 };
+    assert!(to_chart.contains(coord));
+    assert!(within.contains(coord));
+
+    // East of the origin.
+    let coord = Coord { x: -121.5, y: 38.0 };
+    assert!(to_chart.contains(coord));
+    assert!(!within.contains(coord));
+
+    // Off the chart entirely.
+    let coord = Coord { x: -118.0, y: 38.0 };
+    assert!(!to_chart.contains(coord));
+    assert!(!within.contains(coord));
+  }
+}
@@ -0,0 +1,41 @@
+use crate::util;
+use eframe::{egui, emath};
+
+/// Dialog for showing that a long operation is in progress, with a button to cancel it.
+pub struct ProgressDlg {
+  title: String,
+  text: String,
+}
+
+impl ProgressDlg {
+  pub fn open(title: String, text: String) -> Self {
+    Self { title, text }
+  }
+
+  /// Show the dialog. Returns false if the operation was canceled.
+  pub fn show(&mut self, ctx: &egui::Context) -> bool {
+    // There's no default action to confirm, only Escape applies.
+    let mut open = util::dlg_key(ctx) != util::DlgKey::Cancel;
+    let mut cancel = false;
+
+    egui::Window::new(egui::RichText::from(format!("⏳  {}", self.title)).strong())
+      .open(&mut open)
+      .collapsible(false)
+      .resizable(false)
+      .anchor(emath::Align2::CENTER_CENTER, [0.0, 0.0])
+      .show(ctx, |ui| {
+        ui.add_space(8.0);
+        ui.horizontal(|ui| {
+          ui.spinner();
+          ui.add(egui::Label::new(&self.text).wrap(false));
+        });
+        ui.add_space(8.0);
+        ui.separator();
+        if ui.button("Cancel").clicked() {
+          cancel = true;
+        }
+      });
+
+    open && !cancel
+  }
+}
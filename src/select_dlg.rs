@@ -1,3 +1,4 @@
+use crate::util;
 use eframe::{egui, emath};
 use std::mem;
 
@@ -15,6 +16,40 @@ impl SelectDlg {
     ctx: &egui::Context,
     choices: I,
   ) -> Option<Response> {
+    self.show_grouped(ctx, choices.map(|text| ("", text)))
+  }
+
+  /// Show choices under group headers. A header is shown whenever the group text changes, unless
+  /// it's empty. The response index counts choices across all groups.
+  pub fn show_grouped<'a, I: Iterator<Item = (&'a str, &'a str)>>(
+    &mut self,
+    ctx: &egui::Context,
+    choices: I,
+  ) -> Option<Response> {
+    let choices = choices.map(|(group, text)| (group, text, None));
+    self.show_items(ctx, choices, [200.0, 500.0])
+  }
+
+  /// Show choices with an image next to each one. Choices without an image get an empty space so
+  /// that the text lines up.
+  pub fn show_images<'a, I: Iterator<Item = (&'a str, Option<&'a egui::TextureHandle>)>>(
+    &mut self,
+    ctx: &egui::Context,
+    choices: I,
+  ) -> Option<Response> {
+    let choices = choices.map(|(text, image)| ("", text, Some(image)));
+    self.show_items(ctx, choices, [360.0, 500.0])
+  }
+
+  fn show_items<'a, I>(
+    &mut self,
+    ctx: &egui::Context,
+    choices: I,
+    size: [f32; 2],
+  ) -> Option<Response>
+  where
+    I: Iterator<Item = (&'a str, &'a str, Option<Option<&'a egui::TextureHandle>>)>,
+  {
     let mut selection = None;
     let mut open = true;
     let win = egui::Window::new(egui::RichText::from("👉  Select").strong())
@@ -25,7 +60,7 @@ impl SelectDlg {
 
     // Hack to reset the window size.
     let win = if mem::take(&mut self.reset) {
-      win.fixed_size([200.0, 500.0])
+      win.fixed_size(size)
     } else {
       win
     };
@@ -34,8 +69,30 @@ impl SelectDlg {
       ui.add_space(8.0);
       ui.vertical_centered(|ui| {
         egui::ScrollArea::vertical().show(ui, |ui| {
-          for (index, text) in choices.enumerate() {
+          let mut current = "";
+          for (index, (group, text, image)) in choices.enumerate() {
+            if group != current {
+              current = group;
+              if !group.is_empty() {
+                ui.add_space(4.0);
+                ui.label(egui::RichText::new(group).strong());
+              }
+            }
+
             ui.horizontal(|ui| {
+              if let Some(image) = image {
+                let size = emath::vec2(THUMBNAIL_SIZE, THUMBNAIL_SIZE);
+                match image {
+                  Some(texture) => {
+                    let image = egui::Image::new(texture).max_size(size);
+                    ui.add_sized(size, image);
+                  }
+                  None => {
+                    ui.add_space(size.x);
+                  }
+                }
+              }
+
               let widget = egui::SelectableLabel::new(false, text);
               if ui.add_sized(ui.available_size(), widget).clicked() {
                 selection = Some(Response::Index(index));
@@ -46,7 +103,8 @@ impl SelectDlg {
       });
     });
 
-    if !open || ctx.input(|state| state.key_pressed(egui::Key::Escape)) {
+    // There's no default choice, so only Escape applies.
+    if !open || util::dlg_key(ctx) == util::DlgKey::Cancel {
       selection = Some(Response::Close);
     }
 
@@ -55,6 +113,9 @@ impl SelectDlg {
   }
 }
 
+/// Size of the area used for images, in points.
+pub const THUMBNAIL_SIZE: f32 = 96.0;
+
 pub enum Response {
   Close,
   Index(usize),
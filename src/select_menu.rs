@@ -110,6 +110,5 @@ impl SelectMenu {
 pub enum Response {
   Close,
   LatLon,
-  #[allow(dead_code)]
   Index(usize),
 }
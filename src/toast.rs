@@ -0,0 +1,41 @@
+use eframe::{egui, emath};
+use std::time;
+
+/// Brief message shown near the bottom of the window.
+pub struct Toast {
+  text: String,
+  time: time::Instant,
+}
+
+impl Toast {
+  pub fn new(text: String) -> Self {
+    Self {
+      text,
+      time: time::Instant::now(),
+    }
+  }
+
+  /// Show the toast. Returns false once it has expired.
+  pub fn show(&self, ctx: &egui::Context) -> bool {
+    let elapsed = self.time.elapsed();
+    if elapsed >= Toast::DURATION {
+      return false;
+    }
+
+    egui::Area::new("toast".into())
+      .order(egui::Order::Tooltip)
+      .anchor(emath::Align2::CENTER_BOTTOM, [0.0, -32.0])
+      .interactable(false)
+      .show(ctx, |ui| {
+        egui::Frame::popup(ui.style()).show(ui, |ui| {
+          ui.label(&self.text);
+        });
+      });
+
+    // Make sure there's a repaint when the toast expires.
+    ctx.request_repaint_after(Toast::DURATION - elapsed);
+    true
+  }
+
+  const DURATION: time::Duration = time::Duration::from_secs(2);
+}
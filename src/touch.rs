@@ -1,5 +1,6 @@
+use crate::util;
 use eframe::{egui, emath};
-use std::{any, collections, sync::mpsc, thread, time};
+use std::{any, collections, process, sync::mpsc, thread, time};
 
 enum Request {
   Refresh(time::SystemTime),
@@ -14,7 +15,7 @@ struct TouchInfo {
 pub struct LongPressTracker {
   sender: Option<mpsc::Sender<Request>>,
   thread: Option<thread::JoinHandle<()>>,
-  ids: collections::HashSet<u64>,
+  ids: collections::HashSet<(u64, u64)>,
   info: Option<TouchInfo>,
 }
 
@@ -26,31 +27,31 @@ impl LongPressTracker {
       thread::Builder::new()
         .name(any::type_name::<LongPressTracker>().to_owned())
         .spawn(move || {
-          while let Ok(request) = receiver.recv() {
-            let mut request = Some(request);
-            let mut time = None;
-            loop {
-              if let Some(request) = request.take() {
-                match request {
-                  Request::Refresh(t) => time = Some(t),
-                  Request::Cancel => time = None,
-                }
-              }
-
-              if check_time(time) {
-                ctx.request_repaint();
-                time = None;
-              }
-
-              // Check for another request.
-              request = receiver.try_recv().ok();
-              if request.is_none() && time.is_none() {
-                break;
-              }
-
-              // Sleep for a very short duration so that this tread doesn't peg one of the cores.
-              const PAUSE: time::Duration = time::Duration::from_millis(1);
-              thread::sleep(PAUSE);
+          let mut time = None;
+          loop {
+            // Block until a request arrives when no touch is pending, otherwise wait until the
+            // long-press duration has elapsed.
+            let request = match wait_time(time) {
+              Some(timeout) => match receiver.recv_timeout(timeout) {
+                Ok(request) => Some(request),
+                Err(mpsc::RecvTimeoutError::Timeout) => None,
+                Err(mpsc::RecvTimeoutError::Disconnected) => break,
+              },
+              None => match receiver.recv() {
+                Ok(request) => Some(request),
+                Err(_) => break,
+              },
+            };
+
+            match request {
+              Some(Request::Refresh(t)) => time = Some(t),
+              Some(Request::Cancel) => time = None,
+              None => (),
+            }
+
+            if check_time(time) {
+              ctx.request_repaint();
+              time = None;
             }
           }
         })
@@ -65,7 +66,19 @@ impl LongPressTracker {
     }
   }
 
-  pub fn initiate(&mut self, id: egui::TouchId, phase: egui::TouchPhase, pos: emath::Pos2) {
+  /// Track a touch event. Finger and stylus touches are treated the same.
+  /// - `device_id`: touch device, since touch IDs are only unique per device
+  /// - `id`: touch ID
+  /// - `phase`: touch phase
+  /// - `pos`: touch position
+  pub fn initiate(
+    &mut self,
+    device_id: egui::TouchDeviceId,
+    id: egui::TouchId,
+    phase: egui::TouchPhase,
+    pos: emath::Pos2,
+  ) {
+    let id = (device_id.0, id.0);
     match phase {
       egui::TouchPhase::Start => {
         // Only allow one touch.
@@ -77,13 +90,16 @@ impl LongPressTracker {
         } else {
           self.remove_info();
         }
-        self.ids.insert(id.0);
+        self.ids.insert(id);
       }
       egui::TouchPhase::Move => {
-        self.remove_info();
+        // A stylus reports a steady stream of tiny moves while it's held still.
+        if self.info.as_ref().is_some_and(|info| moved(info.pos, pos)) {
+          self.remove_info();
+        }
       }
       egui::TouchPhase::End | egui::TouchPhase::Cancel => {
-        self.ids.remove(&id.0);
+        self.ids.remove(&id);
         self.remove_info();
       }
     }
@@ -108,10 +124,16 @@ impl LongPressTracker {
   }
 
   fn send(&self, request: Request) {
-    self.sender.as_ref().unwrap().send(request).unwrap();
+    // Long-presses just stop being detected if the thread has exited.
+    if let Err(err) = self.sender.as_ref().unwrap().send(request) {
+      println!("{err}");
+    }
   }
 
   const LONG_PRESS_DUR: time::Duration = time::Duration::from_secs(1);
+
+  /// Distance, in points, that a touch can drift before it's no longer a long-press.
+  const LONG_PRESS_SLOP: f32 = 8.0;
 }
 
 impl Drop for LongPressTracker {
@@ -124,6 +146,64 @@ impl Drop for LongPressTracker {
   }
 }
 
+/// Plays a short feedback event to confirm that a long-press registered. This uses the feedbackd
+/// command line client, which gives a tick or a vibration depending on the device's profile. The
+/// client runs on a single worker thread so that it never blocks the UI and at most one runs at a
+/// time.
+pub struct FeedbackPlayer {
+  tx: util::WorkerSender<()>,
+}
+
+impl FeedbackPlayer {
+  pub fn new() -> Self {
+    let (tx, rx) = mpsc::channel();
+    let result = thread::Builder::new()
+      .name(any::type_name::<FeedbackPlayer>().to_owned())
+      .spawn(move || {
+        // Wait for a request. Exit when the connection is closed.
+        while rx.recv().is_ok() {
+          // Requests that arrived while the last event was playing are combined into one.
+          while rx.try_recv().is_ok() {}
+
+          let result = process::Command::new("fbcli")
+            .args(["-E", "button-pressed"])
+            .stdout(process::Stdio::null())
+            .stderr(process::Stdio::null())
+            .status();
+          if let Err(err) = result {
+            println!("Unable to play feedback: {err}");
+          }
+        }
+      });
+
+    // Feedback just isn't played if the thread couldn't be started.
+    if let Err(err) = result {
+      println!("{err}");
+    }
+
+    Self {
+      tx: util::WorkerSender::new(tx),
+    }
+  }
+
+  /// Play the feedback event.
+  pub fn play(&self) {
+    self.tx.send(());
+  }
+}
+
+/// Get how long to wait for the long-press duration to elapse. Returns `None` if there's no
+/// pending touch, in which case the thread should block until the next request.
+fn wait_time(time: Option<time::SystemTime>) -> Option<time::Duration> {
+  let elapsed = time?.elapsed().unwrap_or_default();
+  Some(LongPressTracker::LONG_PRESS_DUR.saturating_sub(elapsed))
+}
+
+/// Check if a touch has moved too far from where it started to be a long-press.
+fn moved(start: emath::Pos2, pos: emath::Pos2) -> bool {
+  start.distance(pos) > LongPressTracker::LONG_PRESS_SLOP
+}
+
 fn check_time(time: Option<time::SystemTime>) -> bool {
   if let Some(time) = time {
     if let Ok(duration) = time::SystemTime::now().duration_since(time) {
@@ -134,3 +214,37 @@ fn check_time(time: Option<time::SystemTime>) -> bool {
   }
   false
 }
+
+mod test {
+  #[test]
+  fn test_wait_time() {
+    use super::LongPressTracker;
+    use std::time;
+
+    // Nothing pending: block instead of polling.
+    assert!(super::wait_time(None).is_none());
+
+    // Touch just started: wait for the remainder of the long-press duration.
+    let now = time::SystemTime::now();
+    let wait = super::wait_time(Some(now)).unwrap();
+    assert!(wait > time::Duration::ZERO && wait <= LongPressTracker::LONG_PRESS_DUR);
+
+    // Long-press duration already elapsed: don't wait at all.
+    let past = now - LongPressTracker::LONG_PRESS_DUR * 2;
+    assert!(super::wait_time(Some(past)) == Some(time::Duration::ZERO));
+    assert!(super::check_time(Some(past)));
+  }
+
+  #[test]
+  fn test_moved() {
+    use eframe::emath;
+
+    // Stylus jitter stays within the slop.
+    let start = emath::pos2(100.0, 100.0);
+    assert!(!super::moved(start, start));
+    assert!(!super::moved(start, emath::pos2(101.5, 99.0)));
+
+    // Dragging cancels the long-press.
+    assert!(super::moved(start, emath::pos2(120.0, 100.0)));
+  }
+}
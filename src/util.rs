@@ -1,10 +1,14 @@
 use eframe::{egui, emath, epaint};
 use gdal::{raster, spatial_ref};
-use std::{borrow, cmp, collections, ops, path};
+use serde::{Deserialize, Serialize};
+use std::{borrow, cell, cmp, collections, ops, path, process, sync::mpsc};
 
 pub const APP_NAME: &str = env!("CARGO_PKG_NAME");
 pub const APP_ICON: &[u8] = include_bytes!("../res/icon.png");
 
+/// Zip file containing a single empty file, used to check that GDAL can read zip files.
+const SELF_TEST_ZIP: &[u8] = include_bytes!("../res/self_test.zip");
+
 #[macro_export]
 macro_rules! debugln {
   ($($arg:tt)*) => (#[cfg(debug_assertions)] println!($($arg)*));
@@ -36,11 +40,14 @@ macro_rules! ok {
 /// Error message as either `&'static str` or `String`.
 pub type Error = borrow::Cow<'static, str>;
 
+/// Chart palette overrides as palette index to unmultiplied RGBA color.
+pub type ColorMap = collections::BTreeMap<u8, [u8; 4]>;
+
 pub enum ZipInfo {
   /// Chart raster data.
   Chart(Vec<path::PathBuf>),
 
-  /// NASR aeronautical data.
+  /// NASR aeronautical data. The paths are empty for an extracted CSV folder.
   Aero {
     csv: path::PathBuf,
     #[allow(unused)]
@@ -48,12 +55,21 @@ pub enum ZipInfo {
   },
 }
 
-/// Returns information about what type of FAA data (if any) is contained in a zip file.
+/// Returns information about what type of FAA data (if any) is contained in a zip file or an
+/// extracted NASR CSV folder.
 pub fn get_zip_info<P: AsRef<path::Path>>(path: P) -> Result<ZipInfo, Error> {
   _get_zip_info(path.as_ref())
 }
 
 fn _get_zip_info(path: &path::Path) -> Result<ZipInfo, Error> {
+  if path.is_dir() {
+    if is_nasr_folder(path) {
+      let (csv, shp) = (path::PathBuf::new(), path::PathBuf::new());
+      return Ok(ZipInfo::Aero { csv, shp });
+    }
+    return Err(NOT_NASR_FOLDER.into());
+  }
+
   let Some(path) = path.to_str() else {
     return Err("Invalid unicode in zip file path".into());
   };
@@ -61,7 +77,7 @@ fn _get_zip_info(path: &path::Path) -> Result<ZipInfo, Error> {
   // Concatenate the VSI prefix.
   let path = ["/vsizip/", path].concat();
 
-  match gdal::vsi::read_dir(path, true) {
+  match gdal::vsi::read_dir(&path, true) {
     Ok(files) => {
       let mut csv = path::PathBuf::new();
       let mut shp = path::PathBuf::new();
@@ -100,7 +116,10 @@ fn _get_zip_info(path: &path::Path) -> Result<ZipInfo, Error> {
       }
 
       // Both the shape folder and CSV zip must be present for aero data to be valid.
-      if !csv.as_os_str().is_empty() && !shp.as_os_str().is_empty() {
+      if !csv.as_os_str().is_empty() {
+        if shp.as_os_str().is_empty() || !has_nasr_airports(&path, &csv) {
+          return Err(NOT_NASR_ZIP.into());
+        }
         return Ok(ZipInfo::Aero { csv, shp });
       }
 
@@ -124,6 +143,232 @@ fn _get_zip_info(path: &path::Path) -> Result<ZipInfo, Error> {
   Err("Zip file does not contain usable data".into())
 }
 
+const NOT_NASR_ZIP: &str =
+  "This doesn't look like a NASR subscription zip\n(expected airport data is missing)";
+
+const NOT_NASR_FOLDER: &str = "This folder doesn't contain extracted NASR airport data";
+
+/// Check if a folder contains extracted NASR CSV airport data.
+fn is_nasr_folder(path: &path::Path) -> bool {
+  path.join("APT_BASE.csv").is_file()
+}
+
+/// Check if a NASR CSV zip contains the airport base data.
+/// - `path`: VSI path of the outer zip file
+/// - `csv`: path of the CSV zip within the outer zip file
+fn has_nasr_airports(path: &str, csv: &path::Path) -> bool {
+  let Some(csv) = csv.to_str() else {
+    return false;
+  };
+
+  let path = ["/vsizip/", path, "/", csv].concat();
+  let Ok(files) = gdal::vsi::read_dir(path, false) else {
+    return false;
+  };
+
+  files.iter().any(|file| {
+    let name = file.file_name().and_then(|name| name.to_str());
+    name.is_some_and(|name| name.eq_ignore_ascii_case("APT_BASE.csv"))
+  })
+}
+
+/// Check that GDAL has what's needed to read the FAA data. Returns the GDAL version if it does.
+pub fn gdal_self_test() -> Result<String, Error> {
+  let version = gdal::version_info("RELEASE_NAME");
+
+  // Charts are GEO-TIFF and NASR airport data is CSV.
+  for name in ["GTiff", "CSV"] {
+    if gdal::DriverManager::get_driver_by_name(name).is_err() {
+      return Err(format!("GDAL {version} is missing the {name} driver").into());
+    }
+  }
+
+  // Everything is read from inside of zip files.
+  let path = ["/vsimem/", APP_NAME, "_self_test.zip"].concat();
+  if let Err(err) = gdal::vsi::create_mem_file(&path, SELF_TEST_ZIP.to_vec()) {
+    return Err(format!("GDAL {version} self-test failed:\n{err}").into());
+  }
+
+  let files = gdal::vsi::read_dir(["/vsizip/", &path].concat(), false);
+  let _ = gdal::vsi::unlink_mem_file(&path);
+  match files {
+    Ok(files) if files.iter().any(|file| file.as_os_str() == "self_test.txt") => Ok(version),
+    _ => Err(format!("GDAL {version} is unable to read zip files").into()),
+  }
+}
+
+/// Open a folder in the system file manager, creating the folder first if it doesn't exist.
+/// - `path`: folder path
+pub fn open_folder(path: &path::Path) -> Result<(), Error> {
+  if let Err(err) = std::fs::create_dir_all(path) {
+    return Err(format!("Unable to create folder:\n{err}").into());
+  }
+
+  let program = if cfg!(target_os = "windows") {
+    "explorer"
+  } else if cfg!(target_os = "macos") {
+    "open"
+  } else {
+    "xdg-open"
+  };
+
+  match process::Command::new(program).arg(path).spawn() {
+    Ok(_) => Ok(()),
+    Err(err) => Err(format!("Unable to open folder:\n{err}").into()),
+  }
+}
+
+/// Pack files into an uncompressed zip archive.
+/// - `files`: file names paired with their contents
+pub fn zip_files(files: &[(&str, &[u8])]) -> Vec<u8> {
+  // MS-DOS date for 1980-01-01, the earliest that zip can represent.
+  const DATE: u16 = 0x21;
+  let mut zip = Vec::new();
+  let mut dir = Vec::new();
+  for (name, data) in files {
+    let offset = zip.len() as u32;
+    let crc = crc32(data);
+    let mut header = Vec::new();
+    header.extend(20u16.to_le_bytes()); // Version needed to extract.
+    header.extend(0u16.to_le_bytes()); // Flags.
+    header.extend(0u16.to_le_bytes()); // Stored (no compression).
+    header.extend(0u16.to_le_bytes()); // Time.
+    header.extend(DATE.to_le_bytes());
+    header.extend(crc.to_le_bytes());
+    header.extend((data.len() as u32).to_le_bytes()); // Compressed size.
+    header.extend((data.len() as u32).to_le_bytes()); // Uncompressed size.
+    header.extend((name.len() as u16).to_le_bytes());
+    header.extend(0u16.to_le_bytes()); // Extra field length.
+
+    // Local file header.
+    zip.extend(0x04034b50u32.to_le_bytes());
+    zip.extend(&header);
+    zip.extend(name.as_bytes());
+    zip.extend(*data);
+
+    // Central directory entry.
+    dir.extend(0x02014b50u32.to_le_bytes());
+    dir.extend(20u16.to_le_bytes()); // Version made by.
+    dir.extend(&header);
+    dir.extend(0u16.to_le_bytes()); // Comment length.
+    dir.extend(0u16.to_le_bytes()); // Disk number.
+    dir.extend(0u16.to_le_bytes()); // Internal attributes.
+    dir.extend(0u32.to_le_bytes()); // External attributes.
+    dir.extend(offset.to_le_bytes());
+    dir.extend(name.as_bytes());
+  }
+
+  // End of central directory record.
+  let offset = zip.len() as u32;
+  zip.extend(&dir);
+  zip.extend(0x06054b50u32.to_le_bytes());
+  zip.extend(0u16.to_le_bytes()); // Disk number.
+  zip.extend(0u16.to_le_bytes()); // Disk with the central directory.
+  zip.extend((files.len() as u16).to_le_bytes());
+  zip.extend((files.len() as u16).to_le_bytes());
+  zip.extend((dir.len() as u32).to_le_bytes());
+  zip.extend(offset.to_le_bytes());
+  zip.extend(0u16.to_le_bytes()); // Comment length.
+  zip
+}
+
+/// Compute the CRC-32 checksum used by zip files.
+/// - `data`: bytes to check
+fn crc32(data: &[u8]) -> u32 {
+  let mut crc = !0u32;
+  for byte in data {
+    crc ^= *byte as u32;
+    for _ in 0..8 {
+      let mask = (crc & 1).wrapping_neg();
+      crc = (crc >> 1) ^ (0xEDB88320 & mask);
+    }
+  }
+  !crc
+}
+
+/// Get the GDAL path of a chart file within a zip file.
+/// - `zip`: zip file path
+/// - `file`: chart file path within the zip file
+pub fn chart_path(zip: &path::Path, file: &path::Path) -> path::PathBuf {
+  // Concatenate the VSI prefix and the file path.
+  let path = ["/vsizip/", zip.to_str().unwrap()].concat();
+  path::Path::new(path.as_str()).join(file)
+}
+
+/// Find the charts in all of the chart zip files in a folder. Returns each zip file path along with
+/// the path of a chart file inside of it.
+pub fn find_charts(folder: &path::Path) -> Vec<(path::PathBuf, path::PathBuf)> {
+  let Ok(entries) = std::fs::read_dir(folder) else {
+    return Vec::new();
+  };
+
+  let mut paths: Vec<path::PathBuf> = entries
+    .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+    .filter(|path| {
+      path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("zip"))
+    })
+    .collect();
+
+  paths.sort_unstable();
+  let mut charts = Vec::new();
+  for path in paths {
+    if let Ok(ZipInfo::Chart(files)) = get_zip_info(&path) {
+      charts.extend(files.into_iter().map(|file| (path.clone(), file)));
+    }
+  }
+  charts
+}
+
+/// Find the most recent NASR 28 day subscription zip file in a folder. Returns the zip file path
+/// along with the path of the CSV zip inside of it. If there's no zip file but the folder contains
+/// extracted CSV data then the folder is returned with an empty CSV path.
+pub fn find_nasr_zip(folder: &path::Path) -> Option<(path::PathBuf, path::PathBuf)> {
+  let mut paths: Vec<path::PathBuf> = std::fs::read_dir(folder)
+    .ok()?
+    .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+    .filter(|path| {
+      // NASR files are named like "28DaySubscription_Effective_2024-05-16.zip".
+      let zip = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("zip"));
+      let stem = stem_str(path).map(|stem| stem.to_ascii_lowercase());
+      zip && stem.is_some_and(|stem| stem.starts_with("28daysubscription"))
+    })
+    .collect();
+
+  // The effective date is part of the name, so the last one is the most recent.
+  paths.sort_unstable();
+  while let Some(path) = paths.pop() {
+    if let Ok(ZipInfo::Aero { csv, shp: _ }) = get_zip_info(&path) {
+      return Some((path, csv));
+    }
+  }
+
+  // Fall back to extracted CSV data in the folder itself.
+  is_nasr_folder(folder).then(|| (folder.to_owned(), path::PathBuf::new()))
+}
+
+/// Find an elevation raster (DEM) in a folder.
+pub fn find_dem(folder: &path::Path) -> Option<path::PathBuf> {
+  let mut paths: Vec<path::PathBuf> = std::fs::read_dir(folder)
+    .ok()?
+    .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+    .filter(|path| {
+      // Elevation files are expected to be GEO-TIFFs named like "Seattle_DEM.tif".
+      let tif = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("tif") || ext.eq_ignore_ascii_case("tiff"));
+      let stem = stem_str(path).map(|stem| stem.to_ascii_lowercase());
+      tif && stem.is_some_and(|stem| stem.ends_with("dem"))
+    })
+    .collect();
+
+  paths.sort_unstable();
+  paths.into_iter().next()
+}
+
 pub trait ToI32 {
   fn to_i32(self) -> Option<i32>;
 }
@@ -152,53 +397,55 @@ impl ToU32 for i64 {
   }
 }
 
-#[derive(Default, Eq, PartialEq)]
+#[derive(Clone, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default)]
 pub struct WinInfo {
   pub pos: Option<Pos>,
   pub size: Option<Size>,
+  pub monitor: Option<Size>,
   pub maxed: bool,
 }
 
 impl WinInfo {
   pub fn new(info: &egui::ViewportInfo) -> Self {
     Self {
-      pos: info.inner_rect.map(|r| r.min.into()),
+      pos: info.outer_rect.map(|r| r.min.into()),
       size: info.inner_rect.map(|r| r.size().into()),
+      monitor: info.monitor_size.map(|s| s.into()),
       maxed: info.fullscreen.unwrap_or(false),
     }
   }
+}
 
-  pub fn from_value(value: Option<&serde_json::Value>) -> Self {
-    if let Some(value) = value {
-      let pos = value.get(WinInfo::POS_KEY).and_then(Pos::from_value);
-      let size = value.get(WinInfo::SIZE_KEY).and_then(Size::from_value);
-      let maxed = value
-        .get(WinInfo::MAXED_KEY)
-        .and_then(|v| v.as_bool())
-        .unwrap_or(false);
-      return Self { pos, size, maxed };
-    }
-    WinInfo::default()
-  }
-
-  pub fn to_value(&self) -> serde_json::Value {
-    let mut value = serde_json::json!({});
-
-    if let Some(pos) = &self.pos {
-      value[WinInfo::POS_KEY] = pos.to_value();
-    }
-
-    if let Some(size) = &self.size {
-      value[WinInfo::SIZE_KEY] = size.to_value();
-    }
-
-    value[WinInfo::MAXED_KEY] = serde_json::Value::Bool(self.maxed);
-    value
+/// Clamp a window position so that the window fits on a monitor. If the window is larger than the
+/// monitor then it's placed at the monitor's upper-left corner.
+/// - `pos`: window outer position
+/// - `size`: window outer size
+/// - `monitor_pos`: monitor position
+/// - `monitor_size`: monitor size
+pub fn clamp_win_pos(pos: Pos, size: Size, monitor_pos: Pos, monitor_size: Size) -> Pos {
+  let clamp = |pos: i32, size: u32, monitor_pos: i32, monitor_size: u32| {
+    let min = i64::from(monitor_pos);
+    let max = min + i64::from(monitor_size.saturating_sub(size));
+    let pos = i64::from(pos).clamp(min, max);
+    i32::try_from(pos).unwrap_or(i32::MAX)
+  };
+  Pos {
+    x: clamp(pos.x, size.w, monitor_pos.x, monitor_size.w),
+    y: clamp(pos.y, size.h, monitor_pos.y, monitor_size.h),
   }
+}
 
-  const POS_KEY: &'static str = "pos";
-  const SIZE_KEY: &'static str = "size";
-  const MAXED_KEY: &'static str = "maxed";
+/// Check if a window position is on a monitor.
+/// - `pos`: window outer position
+/// - `monitor_pos`: monitor position
+/// - `monitor_size`: monitor size
+pub fn on_monitor(pos: Pos, monitor_pos: Pos, monitor_size: Size) -> bool {
+  let (x, y) = (
+    i64::from(pos.x) - i64::from(monitor_pos.x),
+    i64::from(pos.y) - i64::from(monitor_pos.y),
+  );
+  (0..i64::from(monitor_size.w)).contains(&x) && (0..i64::from(monitor_size.h)).contains(&y)
 }
 
 pub trait Transform {
@@ -277,21 +524,28 @@ impl Bounds {
   }
 }
 
-#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+/// Position, stored as `[x, y]`.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(try_from = "[i64; 2]", into = "[i32; 2]")]
 pub struct Pos {
   pub x: i32,
   pub y: i32,
 }
 
-impl Pos {
-  pub fn from_value(value: &serde_json::Value) -> Option<Self> {
-    let x = value.get(0)?.as_i64()?.to_i32()?;
-    let y = value.get(1)?.as_i64()?.to_i32()?;
-    Some(Self { x, y })
+impl TryFrom<[i64; 2]> for Pos {
+  type Error = &'static str;
+
+  fn try_from([x, y]: [i64; 2]) -> Result<Self, Self::Error> {
+    match (x.to_i32(), y.to_i32()) {
+      (Some(x), Some(y)) => Ok(Self { x, y }),
+      _ => Err("position is out of range"),
+    }
   }
+}
 
-  pub fn to_value(self) -> serde_json::Value {
-    serde_json::json!([self.x, self.y])
+impl From<Pos> for [i32; 2] {
+  fn from(pos: Pos) -> Self {
+    [pos.x, pos.y]
   }
 }
 
@@ -328,23 +582,32 @@ impl From<Pos> for (isize, isize) {
   }
 }
 
-#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+/// Size, stored as `[w, h]`.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(try_from = "[i64; 2]", into = "[u32; 2]")]
 pub struct Size {
   pub w: u32,
   pub h: u32,
 }
 
-impl Size {
-  pub fn from_value(value: &serde_json::Value) -> Option<Self> {
-    let w = value.get(0)?.as_i64()?.to_u32()?;
-    let h = value.get(1)?.as_i64()?.to_u32()?;
-    Some(Self { w, h })
+impl TryFrom<[i64; 2]> for Size {
+  type Error = &'static str;
+
+  fn try_from([w, h]: [i64; 2]) -> Result<Self, Self::Error> {
+    match (w.to_u32(), h.to_u32()) {
+      (Some(w), Some(h)) => Ok(Self { w, h }),
+      _ => Err("size is out of range"),
+    }
   }
+}
 
-  pub fn to_value(self) -> serde_json::Value {
-    serde_json::json!([self.w, self.h])
+impl From<Size> for [u32; 2] {
+  fn from(size: Size) -> Self {
+    [size.w, size.h]
   }
+}
 
+impl Size {
   pub fn is_valid(&self) -> bool {
     self.w > 0 && self.h > 0
   }
@@ -517,6 +780,108 @@ pub fn stem_str(path: &path::Path) -> Option<&str> {
   path.file_stem()?.to_str()
 }
 
+/// Number of single character edits (insertions, deletions or substitutions) needed to change
+/// one string into another.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+  let b: Vec<char> = b.chars().collect();
+  let mut row: Vec<usize> = (0..=b.len()).collect();
+  for (i, ca) in a.chars().enumerate() {
+    let mut diag = row[0];
+    row[0] = i + 1;
+    for (j, cb) in b.iter().enumerate() {
+      let above = row[j + 1];
+      row[j + 1] = if ca == *cb {
+        diag
+      } else {
+        1 + diag.min(above).min(row[j])
+      };
+      diag = above;
+    }
+  }
+  row[b.len()]
+}
+
+/// Check if each word of a search term closely matches a word in the text. Returns the total edit
+/// distance if it does.
+/// - `term`: search term
+/// - `text`: text to match against
+pub fn fuzzy_match(term: &str, text: &str) -> Option<usize> {
+  let mut total = 0;
+  let mut any = false;
+  for word in term.split_whitespace() {
+    // Allow one edit for every four characters. Short words must match exactly.
+    let allowed = word.chars().count() / 4;
+    let dist = text
+      .split_whitespace()
+      .map(|other| edit_distance(word, other))
+      .min()?;
+    if dist > allowed {
+      return None;
+    }
+    total += dist;
+    any = true;
+  }
+  any.then_some(total)
+}
+
+/// Keyboard action for a dialog.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DlgKey {
+  None,
+
+  /// Enter was pressed to confirm the default action.
+  Confirm,
+
+  /// Escape was pressed to cancel.
+  Cancel,
+}
+
+/// Get the keyboard action for a dialog. Escape takes precedence if both keys were pressed.
+pub fn dlg_key(ctx: &egui::Context) -> DlgKey {
+  ctx.input(|state| {
+    if state.key_pressed(egui::Key::Escape) {
+      DlgKey::Cancel
+    } else if state.key_pressed(egui::Key::Enter) {
+      DlgKey::Confirm
+    } else {
+      DlgKey::None
+    }
+  })
+}
+
+/// Sending side of a channel to a worker thread that keeps track of whether the worker is still
+/// there, so that a worker exiting unexpectedly doesn't take the UI down with it.
+pub struct WorkerSender<T> {
+  tx: mpsc::Sender<T>,
+  available: cell::Cell<bool>,
+}
+
+impl<T> WorkerSender<T> {
+  pub fn new(tx: mpsc::Sender<T>) -> Self {
+    Self {
+      tx,
+      available: cell::Cell::new(true),
+    }
+  }
+
+  /// Send a request to the worker. Returns false if the worker has exited.
+  /// - `request`: request to send
+  pub fn send(&self, request: T) -> bool {
+    if self.tx.send(request).is_err() {
+      if self.available.replace(false) {
+        println!("Worker thread is unavailable");
+      }
+      return false;
+    }
+    true
+  }
+
+  /// False once a request couldn't be sent because the worker exited.
+  pub fn is_available(&self) -> bool {
+    self.available.get()
+  }
+}
+
 /// Returns the text with each word capitalized.
 pub fn title_case(text: &str) -> String {
   let mut first = true;
@@ -587,6 +952,840 @@ pub fn format_lon(dd: f64) -> Option<String> {
   None
 }
 
+/// Mean radius of the earth in meters.
+const EARTH_RADIUS: f64 = 6_371_008.8;
+
+/// Meters per nautical mile.
+pub const METERS_PER_NM: f64 = 1852.0;
+
+/// Meters per statute mile.
+pub const METERS_PER_SM: f64 = 1609.344;
+
+/// Meters per foot.
+pub const METERS_PER_FOOT: f64 = 0.3048;
+
+/// Great circle distance, in meters, between two NAD83 coordinates.
+pub fn gc_distance(from: Coord, to: Coord) -> f64 {
+  let lat1 = from.y.to_radians();
+  let lat2 = to.y.to_radians();
+  let dlat = lat2 - lat1;
+  let dlon = (to.x - from.x).to_radians();
+  let a = (dlat * 0.5).sin().powi(2) + lat1.cos() * lat2.cos() * (dlon * 0.5).sin().powi(2);
+  2.0 * EARTH_RADIUS * a.sqrt().asin()
+}
+
+/// Initial great circle bearing, in degrees true, from one NAD83 coordinate to another.
+pub fn gc_bearing(from: Coord, to: Coord) -> f64 {
+  let lat1 = from.y.to_radians();
+  let lat2 = to.y.to_radians();
+  let dlon = (to.x - from.x).to_radians();
+  let y = dlon.sin() * lat2.cos();
+  let x = lat1.cos() * lat2.sin() - lat1.sin() * lat2.cos() * dlon.cos();
+  y.atan2(x).to_degrees().rem_euclid(360.0)
+}
+
+/// Format a true bearing, along with the magnetic bearing if the magnetic variation is known.
+/// - `bearing`: bearing in degrees true
+/// - `mag_var`: magnetic variation in degrees, east is positive
+pub fn format_bearing(bearing: f64, mag_var: Option<f64>) -> String {
+  let deg = |bearing: f64| bearing.round().rem_euclid(360.0) as u32;
+  match mag_var {
+    Some(var) => format!("{:03}°T {:03}°M", deg(bearing), deg(bearing - var)),
+    None => format!("{:03}°", deg(bearing)),
+  }
+}
+
+/// Format a map scale as a ratio, like "1:500,000". The denominator is rounded to three significant
+/// digits.
+/// - `scale`: scale denominator
+pub fn format_scale(scale: f64) -> String {
+  let digits = scale.max(1.0).log10().floor() as i32;
+  let unit = 10f64.powi((digits - 2).max(0));
+  let denom = ((scale / unit).round() * unit) as u64;
+  format!("1:{}", format_count(denom))
+}
+
+/// Format a count with its digits grouped by thousands, like "12,345".
+/// - `count`: number to format
+pub fn format_count(count: u64) -> String {
+  let text = count.to_string();
+  let mut grouped = String::with_capacity(text.len() + text.len() / 3);
+  for (index, ch) in text.chars().enumerate() {
+    if index > 0 && (text.len() - index) % 3 == 0 {
+      grouped.push(',');
+    }
+    grouped.push(ch);
+  }
+  grouped
+}
+
+/// Destination NAD83 coordinate from traveling a great circle distance along an initial bearing.
+/// - `from`: starting NAD83 coordinate
+/// - `bearing`: initial bearing in degrees true
+/// - `dist`: distance in meters
+pub fn gc_destination(from: Coord, bearing: f64, dist: f64) -> Coord {
+  let lat1 = from.y.to_radians();
+  let lon1 = from.x.to_radians();
+  let brg = bearing.to_radians();
+  let ang = dist / EARTH_RADIUS;
+  let lat2 = (lat1.sin() * ang.cos() + lat1.cos() * ang.sin() * brg.cos()).asin();
+  let y = brg.sin() * ang.sin() * lat1.cos();
+  let x = ang.cos() - lat1.sin() * lat2.sin();
+  let lon2 = lon1 + y.atan2(x);
+  Coord {
+    x: (lon2.to_degrees() + 540.0).rem_euclid(360.0) - 180.0,
+    y: lat2.to_degrees(),
+  }
+}
+
+/// Area, in square meters, enclosed by a polygon of NAD83 coordinates. The edges are treated as
+/// great circle arcs and the polygon is implicitly closed.
+/// - `points`: polygon vertices
+pub fn polygon_area(points: &[Coord]) -> f64 {
+  if points.len() < 3 {
+    return 0.0;
+  }
+
+  // Sum the spherical excess of the triangles formed by each edge and the pole.
+  let mut excess = 0.0;
+  let mut prev = points[points.len() - 1];
+  for &point in points {
+    let dlon = (point.x - prev.x + 540.0).rem_euclid(360.0) - 180.0;
+    let t1 = (prev.y.to_radians() * 0.5).tan();
+    let t2 = (point.y.to_radians() * 0.5).tan();
+    let t = (dlon.to_radians() * 0.5).tan();
+    excess += 2.0 * (t * (t1 + t2)).atan2(1.0 + t1 * t2);
+    prev = point;
+  }
+
+  excess.abs() * EARTH_RADIUS * EARTH_RADIUS
+}
+
+/// Split a polygon into triangles by ear clipping. Returns the vertex indices, three per
+/// triangle, or `None` if the polygon crosses itself.
+/// - `points`: polygon vertices, in order
+pub fn triangulate(points: &[emath::Pos2]) -> Option<Vec<u32>> {
+  if points.len() < 3 {
+    return None;
+  }
+
+  let cross =
+    |a: emath::Pos2, b: emath::Pos2, c: emath::Pos2| (b - a).x * (c - a).y - (b - a).y * (c - a).x;
+
+  // Ear clipping only works on polygons whose edges don't cross.
+  let count = points.len();
+  for first in 0..count {
+    for second in first + 2..count {
+      if first == 0 && second == count - 1 {
+        // These edges share the first point.
+        continue;
+      }
+
+      let (a, b) = (points[first], points[(first + 1) % count]);
+      let (c, d) = (points[second], points[(second + 1) % count]);
+      if cross(a, b, c) * cross(a, b, d) < 0.0 && cross(c, d, a) * cross(c, d, b) < 0.0 {
+        return None;
+      }
+    }
+  }
+
+  // The sign of the area gives the winding order.
+  let mut prev = points[points.len() - 1];
+  let mut area = 0.0;
+  for &point in points {
+    area += prev.x * point.y - point.x * prev.y;
+    prev = point;
+  }
+
+  let mut remaining: Vec<usize> = (0..points.len()).collect();
+  let mut indices = Vec::with_capacity((points.len() - 2) * 3);
+  while remaining.len() > 3 {
+    let count = remaining.len();
+    let ear = (0..count).find(|&index| {
+      let a = remaining[(index + count - 1) % count];
+      let b = remaining[index];
+      let c = remaining[(index + 1) % count];
+      let (pa, pb, pc) = (points[a], points[b], points[c]);
+
+      // The corner must turn the same way as the polygon.
+      if cross(pa, pb, pc) * area <= 0.0 {
+        return false;
+      }
+
+      // No other vertex can be inside the triangle.
+      !remaining.iter().any(|&other| {
+        let p = points[other];
+        let inside = cross(pa, pb, p) * area >= 0.0
+          && cross(pb, pc, p) * area >= 0.0
+          && cross(pc, pa, p) * area >= 0.0;
+        other != a && other != b && other != c && inside
+      })
+    })?;
+
+    let a = remaining[(ear + count - 1) % count];
+    let c = remaining[(ear + 1) % count];
+    indices.extend([a as u32, remaining[ear] as u32, c as u32]);
+    remaining.remove(ear);
+  }
+
+  indices.extend(remaining.iter().map(|&index| index as u32));
+  Some(indices)
+}
+
+/// Nicely format a degrees, decimal minutes string from latitude in decimal degrees.
+pub fn format_lat_dm(dd: f64) -> Option<String> {
+  if (-90.0..=90.0).contains(&dd) {
+    let (deg, min, frac) = to_deg_dec_min(dd);
+    let sn = if dd < 0.0 { 'S' } else { 'N' };
+    return Some(format!("{deg:02}°{min:02}.{frac:02}'{sn}"));
+  }
+  None
+}
+
+/// Nicely format a degrees, decimal minutes string from longitude in decimal degrees.
+pub fn format_lon_dm(dd: f64) -> Option<String> {
+  if (-180.0..=180.0).contains(&dd) {
+    let (deg, min, frac) = to_deg_dec_min(dd);
+    let we = if dd < 0.0 { 'W' } else { 'E' };
+    return Some(format!("{deg:03}°{min:02}.{frac:02}'{we}"));
+  }
+  None
+}
+
+/// Convert a decimal degree angle to unsigned degrees, minutes and hundredths of a minute.
+fn to_deg_dec_min(dd: f64) -> (u64, u64, u64) {
+  let total = (dd.abs() * 6000.0).round() as u64;
+  let min = total % 6000;
+  (total / 6000, min / 100, min % 100)
+}
+
+/// Format a decimal degrees string from latitude in decimal degrees.
+pub fn format_lat_dd(dd: f64) -> Option<String> {
+  if (-90.0..=90.0).contains(&dd) {
+    let sn = if dd < 0.0 { 'S' } else { 'N' };
+    return Some(format!("{:08.5}°{sn}", dd.abs()));
+  }
+  None
+}
+
+/// Format a decimal degrees string from longitude in decimal degrees.
+pub fn format_lon_dd(dd: f64) -> Option<String> {
+  if (-180.0..=180.0).contains(&dd) {
+    let we = if dd < 0.0 { 'W' } else { 'E' };
+    return Some(format!("{:09.5}°{we}", dd.abs()));
+  }
+  None
+}
+
+/// Coordinate display format.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum CoordFormat {
+  /// Degrees, minutes, seconds.
+  #[default]
+  #[serde(rename = "dms")]
+  DegMinSec,
+
+  /// Degrees, decimal minutes.
+  #[serde(rename = "dm")]
+  DegMin,
+
+  /// Decimal degrees.
+  #[serde(rename = "dd")]
+  Decimal,
+}
+
+impl CoordFormat {
+  pub const ALL: [CoordFormat; 3] = [
+    CoordFormat::DegMinSec,
+    CoordFormat::DegMin,
+    CoordFormat::Decimal,
+  ];
+
+  /// Format a NAD83 coordinate as "latitude, longitude".
+  pub fn format(self, coord: Coord) -> Option<String> {
+    let (lat, lon) = match self {
+      Self::DegMinSec => (format_lat(coord.y)?, format_lon(coord.x)?),
+      Self::DegMin => (format_lat_dm(coord.y)?, format_lon_dm(coord.x)?),
+      Self::Decimal => (format_lat_dd(coord.y)?, format_lon_dd(coord.x)?),
+    };
+    Some(format!("{lat}, {lon}"))
+  }
+
+  /// Get the next format in the cycle, wrapping around after the last one.
+  pub fn next(self) -> Self {
+    let index = Self::ALL.iter().position(|fmt| *fmt == self).unwrap();
+    Self::ALL[(index + 1) % Self::ALL.len()]
+  }
+
+  /// Descriptive text for the UI.
+  pub fn text(self) -> &'static str {
+    match self {
+      Self::DegMinSec => "Deg Min Sec",
+      Self::DegMin => "Deg Min",
+      Self::Decimal => "Decimal",
+    }
+  }
+}
+
+/// Distance units.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum Units {
+  #[default]
+  #[serde(rename = "nm")]
+  Nautical,
+  #[serde(rename = "sm")]
+  Statute,
+  #[serde(rename = "km")]
+  Metric,
+}
+
+impl Units {
+  pub const ALL: [Units; 3] = [Units::Nautical, Units::Statute, Units::Metric];
+
+  /// Convert a distance in meters to these units.
+  pub fn from_meters(self, meters: f64) -> f64 {
+    match self {
+      Self::Nautical => meters / METERS_PER_NM,
+      Self::Statute => meters / METERS_PER_SM,
+      Self::Metric => meters * 0.001,
+    }
+  }
+
+  /// Convert a distance in these units to meters.
+  pub fn to_meters(self, dist: f64) -> f64 {
+    match self {
+      Self::Nautical => dist * METERS_PER_NM,
+      Self::Statute => dist * METERS_PER_SM,
+      Self::Metric => dist * 1000.0,
+    }
+  }
+
+  /// Units abbreviation.
+  pub fn abv(self) -> &'static str {
+    match self {
+      Self::Nautical => "NM",
+      Self::Statute => "SM",
+      Self::Metric => "KM",
+    }
+  }
+
+  /// Descriptive text for the UI.
+  pub fn text(self) -> &'static str {
+    match self {
+      Self::Nautical => "Nautical Miles",
+      Self::Statute => "Statute Miles",
+      Self::Metric => "Kilometers",
+    }
+  }
+}
+
+/// Color vision type used to remap chart colors.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum ColorVision {
+  #[default]
+  #[serde(rename = "normal")]
+  Normal,
+  #[serde(rename = "protanopia")]
+  Protanopia,
+  #[serde(rename = "deuteranopia")]
+  Deuteranopia,
+  #[serde(rename = "tritanopia")]
+  Tritanopia,
+}
+
+impl ColorVision {
+  pub const ALL: [ColorVision; 4] = [
+    ColorVision::Normal,
+    ColorVision::Protanopia,
+    ColorVision::Deuteranopia,
+    ColorVision::Tritanopia,
+  ];
+
+  /// Remap a color so that the differences lost to this color vision deficiency are shifted into
+  /// colors that can still be seen (daltonization).
+  /// - `rgb`: color to remap
+  pub fn remap(self, rgb: [u8; 3]) -> [u8; 3] {
+    // Simulation matrices from Machado, Oliveira and Fernandes (2009), full severity.
+    let sim = match self {
+      Self::Normal => return rgb,
+      Self::Protanopia => [
+        [0.152286, 1.052583, -0.204868],
+        [0.114503, 0.786281, 0.099216],
+        [-0.003882, -0.048116, 1.051998],
+      ],
+      Self::Deuteranopia => [
+        [0.367322, 0.860646, -0.227968],
+        [0.280085, 0.672501, 0.047413],
+        [-0.011820, 0.042940, 0.968881],
+      ],
+      Self::Tritanopia => [
+        [1.255528, -0.076749, -0.178779],
+        [-0.078411, 0.930809, 0.147602],
+        [0.004733, 0.691367, 0.303900],
+      ],
+    };
+
+    // Get the difference between the original color and the simulated color.
+    let [r, g, b] = rgb.map(|v| v as f32);
+    let [sr, sg, sb] = sim.map(|[mr, mg, mb]| mr * r + mg * g + mb * b);
+    let (er, eg, eb) = (r - sr, g - sg, b - sb);
+
+    // Shift the difference into the channels that are still distinguishable.
+    let shift = if self == Self::Tritanopia {
+      [er + 0.7 * eb, eg + 0.7 * eb, 0.0]
+    } else {
+      [0.0, 0.7 * er + eg, 0.7 * er + eb]
+    };
+
+    [r + shift[0], g + shift[1], b + shift[2]].map(|v| v.round().clamp(0.0, 255.0) as u8)
+  }
+
+  /// Descriptive text for the UI.
+  pub fn text(self) -> &'static str {
+    match self {
+      Self::Normal => "Normal",
+      Self::Protanopia => "Protanopia",
+      Self::Deuteranopia => "Deuteranopia",
+      Self::Tritanopia => "Tritanopia",
+    }
+  }
+}
+
+/// Chart features to make stand out for quick orientation.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum Emphasis {
+  #[default]
+  #[serde(rename = "off")]
+  Off,
+  #[serde(rename = "water")]
+  Water,
+  #[serde(rename = "terrain")]
+  Terrain,
+}
+
+impl Emphasis {
+  pub const ALL: [Emphasis; 3] = [Emphasis::Off, Emphasis::Water, Emphasis::Terrain];
+
+  /// Check if a palette color looks like this feature: blue for water, or the tan, brown and
+  /// olive terrain tints.
+  /// - `rgb`: palette color
+  pub fn matches(self, rgb: [u8; 3]) -> bool {
+    let [r, g, b] = rgb.map(|v| v as f32 / 255.0);
+    let max = r.max(g).max(b);
+    let chroma = max - r.min(g).min(b);
+    if max <= 0.0 || chroma / max < 0.15 {
+      // Too gray to tell.
+      return false;
+    }
+
+    let hue = if max == r {
+      60.0 * ((g - b) / chroma).rem_euclid(6.0)
+    } else if max == g {
+      60.0 * ((b - r) / chroma + 2.0)
+    } else {
+      60.0 * ((r - g) / chroma + 4.0)
+    };
+
+    match self {
+      Self::Off => false,
+      Self::Water => (180.0..=250.0).contains(&hue),
+      Self::Terrain => (20.0..=70.0).contains(&hue) && max >= 0.3,
+    }
+  }
+
+  /// Descriptive text for the UI.
+  pub fn text(self) -> &'static str {
+    match self {
+      Self::Off => "Off",
+      Self::Water => "Water",
+      Self::Terrain => "Terrain",
+    }
+  }
+}
+
+/// Boost the saturation and brightness of a color.
+/// - `rgb`: color to boost
+pub fn emphasize(rgb: [u8; 3]) -> [u8; 3] {
+  const SATURATION: f32 = 1.6;
+  const BRIGHTNESS: f32 = 1.15;
+  let [r, g, b] = rgb.map(|v| v as f32);
+  let mean = (r + g + b) / 3.0;
+  [r, g, b].map(|v| {
+    ((mean + (v - mean) * SATURATION) * BRIGHTNESS)
+      .round()
+      .clamp(0.0, 255.0) as u8
+  })
+}
+
+/// Parse a comma separated list of palette indices. Returns `None` if any of them are invalid.
+/// - `text`: index list, like "3, 17, 42"
+pub fn parse_indices(text: &str) -> Option<Vec<u8>> {
+  let items = text
+    .split(',')
+    .map(str::trim)
+    .filter(|item| !item.is_empty());
+  let mut indices: Vec<u8> = items.map(|item| item.parse().ok()).collect::<Option<_>>()?;
+  indices.sort_unstable();
+  indices.dedup();
+  Some(indices)
+}
+
+/// Format a list of palette indices for editing.
+/// - `indices`: palette indices
+pub fn format_indices(indices: &[u8]) -> String {
+  let items: Vec<String> = indices.iter().map(u8::to_string).collect();
+  items.join(", ")
+}
+
+/// What a long-press on the chart does.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum LongPressAction {
+  /// Open the context menu.
+  #[default]
+  #[serde(rename = "menu")]
+  Menu,
+
+  /// Center the chart on the pressed location.
+  #[serde(rename = "recenter")]
+  Recenter,
+}
+
+impl LongPressAction {
+  pub const ALL: [LongPressAction; 2] = [LongPressAction::Menu, LongPressAction::Recenter];
+
+  /// Descriptive text for the UI.
+  pub fn text(self) -> &'static str {
+    match self {
+      Self::Menu => "Context Menu",
+      Self::Recenter => "Recenter",
+    }
+  }
+}
+
+/// What the Escape key does once there's nothing else for it to clear.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum EscapeAction {
+  /// Close the side panel.
+  #[default]
+  #[serde(rename = "panel")]
+  Panel,
+
+  /// Close the side panel, or close the chart if the side panel is already closed.
+  #[serde(rename = "chart")]
+  Chart,
+}
+
+impl EscapeAction {
+  pub const ALL: [EscapeAction; 2] = [EscapeAction::Panel, EscapeAction::Chart];
+
+  /// Descriptive text for the UI.
+  pub fn text(self) -> &'static str {
+    match self {
+      Self::Panel => "Close Side Panel",
+      Self::Chart => "Close Panel, Then Chart",
+    }
+  }
+}
+
+/// User interface theme.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum UiTheme {
+  /// Dark in night mode, otherwise the default theme.
+  #[default]
+  #[serde(rename = "auto")]
+  Auto,
+
+  #[serde(rename = "light")]
+  Light,
+
+  #[serde(rename = "dark")]
+  Dark,
+}
+
+impl UiTheme {
+  pub const ALL: [UiTheme; 3] = [UiTheme::Auto, UiTheme::Light, UiTheme::Dark];
+
+  /// Descriptive text for the UI.
+  pub fn text(self) -> &'static str {
+    match self {
+      Self::Auto => "Follow Night Mode",
+      Self::Light => "Light",
+      Self::Dark => "Dark",
+    }
+  }
+}
+
+/// FAA chart series.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ChartSeries {
+  Sectional,
+  Terminal,
+  Flyway,
+  Helicopter,
+  EnrouteLow,
+  EnrouteHigh,
+  Area,
+}
+
+/// FAA chart file naming conventions: file stem suffixes and prefixes for each chart series.
+const CHART_SERIES_NAMES: [(&str, &str, ChartSeries); 9] = [
+  (" SEC", "", ChartSeries::Sectional),
+  (" TAC", "", ChartSeries::Terminal),
+  (" FLY", "", ChartSeries::Flyway),
+  (" HEL", "", ChartSeries::Helicopter),
+  ("", "ENR_AKL", ChartSeries::EnrouteLow),
+  ("", "ENR_AKH", ChartSeries::EnrouteHigh),
+  ("", "ENR_L", ChartSeries::EnrouteLow),
+  ("", "ENR_H", ChartSeries::EnrouteHigh),
+  ("", "ENR_A", ChartSeries::Area),
+];
+
+impl ChartSeries {
+  /// Identify the chart series from a chart's file stem.
+  /// - `stem`: chart file stem, like "Seattle SEC"
+  pub fn from_stem(stem: &str) -> Option<Self> {
+    let stem = stem.trim().to_ascii_uppercase();
+    CHART_SERIES_NAMES
+      .iter()
+      .find(|(suffix, prefix, _)| stem.ends_with(suffix) && stem.starts_with(prefix))
+      .map(|(_, _, series)| *series)
+  }
+
+  /// Descriptive text for the UI.
+  pub fn text(self) -> &'static str {
+    match self {
+      Self::Sectional => "Sectional",
+      Self::Terminal => "Terminal Area",
+      Self::Flyway => "Flyway Planning",
+      Self::Helicopter => "Helicopter Route",
+      Self::EnrouteLow => "IFR Enroute Low",
+      Self::EnrouteHigh => "IFR Enroute High",
+      Self::Area => "IFR Area",
+    }
+  }
+
+  /// Zoom that a chart of this series opens at. Charts made for detail open at full resolution
+  /// while charts made for the big picture open zoomed out.
+  pub fn open_zoom(self) -> f32 {
+    match self {
+      Self::Sectional | Self::EnrouteLow | Self::EnrouteHigh => 0.5,
+      Self::Terminal | Self::Flyway | Self::Helicopter | Self::Area => 1.0,
+    }
+  }
+}
+
+/// Get a label for a chart that includes its series, falling back to the file stem if the series
+/// isn't recognized.
+/// - `stem`: chart file stem
+pub fn chart_label(stem: &str) -> String {
+  match ChartSeries::from_stem(stem) {
+    Some(series) => format!("{stem}\n{}", series.text()),
+    None => stem.to_owned(),
+  }
+}
+
+/// When the chart scroll position is snapped to a whole pixel.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum ScrollSnap {
+  /// Snap after the chart stops moving.
+  #[default]
+  #[serde(rename = "rest")]
+  Rest,
+
+  /// Snap once kinetic scrolling slows down, which avoids a jump at the end of the motion.
+  #[serde(rename = "slow")]
+  Slow,
+
+  /// Never snap, which gives smoother motion at the cost of a slightly blurred image.
+  #[serde(rename = "off")]
+  Off,
+}
+
+impl ScrollSnap {
+  pub const ALL: [ScrollSnap; 3] = [ScrollSnap::Rest, ScrollSnap::Slow, ScrollSnap::Off];
+
+  /// Returns true if the scroll position should be snapped.
+  /// - `speed`: scroll speed in points per second
+  /// - `dragging`: true if the chart is being dragged
+  pub fn should_snap(self, speed: f32, dragging: bool) -> bool {
+    match self {
+      Self::Rest => speed == 0.0,
+      Self::Slow => speed == 0.0 || (!dragging && speed < Self::SLOW_SPEED),
+      Self::Off => false,
+    }
+  }
+
+  /// Descriptive text for the UI.
+  pub fn text(self) -> &'static str {
+    match self {
+      Self::Rest => "When Stopped",
+      Self::Slow => "When Slow",
+      Self::Off => "Never",
+    }
+  }
+
+  /// Speed, in points per second, below which kinetic scrolling is considered slow. egui's scroll
+  /// friction is 1000 points per second squared and it stops below 20 points per second, so this
+  /// leaves about 0.3 seconds (45 points) of motion after the snap.
+  const SLOW_SPEED: f32 = 300.0;
+}
+
+/// A named location that can be jumped to.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Bookmark {
+  pub name: String,
+
+  /// Longitude in decimal degrees (NAD83).
+  pub lon: f64,
+
+  /// Latitude in decimal degrees (NAD83).
+  pub lat: f64,
+}
+
+impl Bookmark {
+  pub fn new(name: String, coord: Coord) -> Self {
+    Self {
+      name,
+      lon: coord.x,
+      lat: coord.y,
+    }
+  }
+
+  /// Get the bookmark's NAD83 coordinate.
+  pub fn coord(&self) -> Coord {
+    Coord {
+      x: self.lon,
+      y: self.lat,
+    }
+  }
+}
+
+/// Zoom factor used by the zoom buttons and keys.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum ZoomStep {
+  #[serde(rename = "fine")]
+  Fine,
+
+  #[serde(rename = "medium")]
+  Medium,
+
+  #[default]
+  #[serde(rename = "coarse")]
+  Coarse,
+}
+
+impl ZoomStep {
+  pub const ALL: [ZoomStep; 3] = [ZoomStep::Fine, ZoomStep::Medium, ZoomStep::Coarse];
+
+  /// Zoom-in factor. The inverse is used for zooming out.
+  pub fn factor(self) -> f32 {
+    match self {
+      Self::Fine => 1.25,
+      Self::Medium => 1.5,
+      Self::Coarse => 2.0,
+    }
+  }
+
+  /// Descriptive text for the UI.
+  pub fn text(self) -> &'static str {
+    match self {
+      Self::Fine => "1.25×",
+      Self::Medium => "1.5×",
+      Self::Coarse => "2×",
+    }
+  }
+}
+
+/// Vertical screen position that find/goto places its result at.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum GotoAnchor {
+  #[default]
+  #[serde(rename = "center")]
+  Center,
+
+  /// Two thirds of the way down, leaving more of the area ahead visible.
+  #[serde(rename = "lower_third")]
+  LowerThird,
+
+  /// Three quarters of the way down.
+  #[serde(rename = "lower_quarter")]
+  LowerQuarter,
+}
+
+impl GotoAnchor {
+  pub const ALL: [GotoAnchor; 3] = [
+    GotoAnchor::Center,
+    GotoAnchor::LowerThird,
+    GotoAnchor::LowerQuarter,
+  ];
+
+  /// Fraction of the display height from the top.
+  pub fn fraction(self) -> f32 {
+    match self {
+      Self::Center => 0.5,
+      Self::LowerThird => 2.0 / 3.0,
+      Self::LowerQuarter => 0.75,
+    }
+  }
+
+  /// Descriptive text for the UI.
+  pub fn text(self) -> &'static str {
+    match self {
+      Self::Center => "Center",
+      Self::LowerThird => "Lower Third",
+      Self::LowerQuarter => "Lower Quarter",
+    }
+  }
+}
+
+/// Size of the airport markers drawn on the chart.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum MarkerSize {
+  /// Markers are not shown.
+  #[default]
+  #[serde(rename = "off")]
+  Off,
+
+  #[serde(rename = "small")]
+  Small,
+
+  #[serde(rename = "medium")]
+  Medium,
+
+  /// Touch friendly markers.
+  #[serde(rename = "large")]
+  Large,
+}
+
+impl MarkerSize {
+  pub const ALL: [MarkerSize; 4] = [
+    MarkerSize::Off,
+    MarkerSize::Small,
+    MarkerSize::Medium,
+    MarkerSize::Large,
+  ];
+
+  /// Marker radius in points.
+  pub fn radius(self) -> f32 {
+    match self {
+      Self::Off => 0.0,
+      Self::Small => 4.0,
+      Self::Medium => 6.0,
+      Self::Large => 10.0,
+    }
+  }
+
+  /// Descriptive text for the UI.
+  pub fn text(self) -> &'static str {
+    match self {
+      Self::Off => "Off",
+      Self::Small => "Small",
+      Self::Medium => "Medium",
+      Self::Large => "Large",
+    }
+  }
+}
+
 /// Check if a GDAL color will fit into an egui color.
 pub fn check_color(color: raster::RgbaEntry) -> bool {
   const COMP_RANGE: ops::Range<i16> = 0..256;
@@ -625,7 +1824,197 @@ pub fn inverted_color(color: &raster::RgbaEntry) -> epaint::Color32 {
   epaint::Color32::from_rgba_unmultiplied(r, g, b, color.a as u8)
 }
 
+/// Convert a color to grayscale using its luminance.
+pub fn grayscale(color: epaint::Color32) -> epaint::Color32 {
+  let [r, g, b, a] = color.to_array();
+  let y = r as f32 * 0.299 + g as f32 * 0.587 + b as f32 * 0.114;
+  let y = y.round() as u8;
+  epaint::Color32::from_rgba_premultiplied(y, y, y, a)
+}
+
+/// Sharpen an image in place using an unsharp mask with a small cross-shaped blur kernel.
+/// - `image`: image to sharpen
+/// - `amount`: sharpening strength in the 0..=1 range
+pub fn sharpen(image: &mut epaint::ColorImage, amount: f32) {
+  let [w, h] = image.size;
+  if amount <= 0.0 || w < 3 || h < 3 {
+    return;
+  }
+
+  let src = image.pixels.clone();
+  for y in 1..h - 1 {
+    for x in 1..w - 1 {
+      let idx = y * w + x;
+      let color = src[idx];
+      let alpha = color.a() as f32;
+      let near = [src[idx - w], src[idx + w], src[idx - 1], src[idx + 1]];
+      let mut rgb = [0; 3];
+      for (comp, out) in rgb.iter_mut().enumerate() {
+        let blur = near.iter().map(|c| c[comp] as f32).sum::<f32>() * 0.25;
+        let val = color[comp] as f32;
+
+        // Colors are premultiplied, so components can't exceed alpha.
+        *out = (val + amount * (val - blur)).round().clamp(0.0, alpha) as u8;
+      }
+      image.pixels[idx] =
+        epaint::Color32::from_rgba_premultiplied(rgb[0], rgb[1], rgb[2], color.a());
+    }
+  }
+}
+
 mod test {
+  #[test]
+  fn test_worker_sender() {
+    use std::sync::mpsc;
+    let (tx, rx) = mpsc::channel();
+    let sender = super::WorkerSender::new(tx);
+    assert!(sender.send(1));
+    assert!(sender.is_available());
+    assert!(rx.recv() == Ok(1));
+
+    // Sending to a worker that's gone fails without panicking.
+    drop(rx);
+    assert!(!sender.send(2));
+    assert!(!sender.is_available());
+    assert!(!sender.send(3));
+  }
+
+  #[test]
+  fn test_gdal_self_test() {
+    let version = super::gdal_self_test().unwrap();
+    assert!(!version.is_empty());
+  }
+
+  #[test]
+  fn test_zip_files() {
+    assert!(super::crc32(b"123456789") == 0xCBF43926);
+
+    // GDAL must be able to read the archive.
+    let zip = super::zip_files(&[("summary.txt", b"summary"), ("settings.json", b"{}")]);
+    let path = ["/vsimem/", super::APP_NAME, "_zip_test.zip"].concat();
+    gdal::vsi::create_mem_file(&path, zip).unwrap();
+    let files = gdal::vsi::read_dir(["/vsizip/", &path].concat(), false);
+    let _ = gdal::vsi::unlink_mem_file(&path);
+    let files = files.unwrap();
+    assert!(files.len() == 2);
+    assert!(files.iter().any(|file| file.as_os_str() == "summary.txt"));
+    assert!(files.iter().any(|file| file.as_os_str() == "settings.json"));
+  }
+
+  #[test]
+  fn test_dlg_key() {
+    use eframe::egui;
+    let dlg_key = |keys: &[egui::Key]| {
+      let ctx = egui::Context::default();
+      let events = keys.iter().map(|key| egui::Event::Key {
+        key: *key,
+        physical_key: None,
+        pressed: true,
+        repeat: false,
+        modifiers: egui::Modifiers::NONE,
+      });
+      let input = egui::RawInput {
+        events: events.collect(),
+        ..Default::default()
+      };
+
+      let mut result = super::DlgKey::None;
+      let _ = ctx.run(input, |ctx| result = super::dlg_key(ctx));
+      result
+    };
+
+    assert!(dlg_key(&[]) == super::DlgKey::None);
+    assert!(dlg_key(&[egui::Key::A]) == super::DlgKey::None);
+    assert!(dlg_key(&[egui::Key::Enter]) == super::DlgKey::Confirm);
+    assert!(dlg_key(&[egui::Key::Escape]) == super::DlgKey::Cancel);
+    assert!(dlg_key(&[egui::Key::Enter, egui::Key::Escape]) == super::DlgKey::Cancel);
+  }
+
+  #[test]
+  fn test_format_bearing() {
+    assert!(super::format_bearing(90.0, None) == "090°");
+    assert!(super::format_bearing(359.6, None) == "000°");
+    assert!(super::format_bearing(90.0, Some(14.0)) == "090°T 076°M");
+    assert!(super::format_bearing(5.0, Some(13.0)) == "005°T 352°M");
+    assert!(super::format_bearing(355.0, Some(-10.0)) == "355°T 005°M");
+  }
+
+  #[test]
+  fn test_format_scale() {
+    assert!(super::format_scale(500_000.0) == "1:500,000");
+    assert!(super::format_scale(1_234_567.0) == "1:1,230,000");
+    assert!(super::format_scale(249_640.0) == "1:250,000");
+    assert!(super::format_scale(999.4) == "1:999");
+    assert!(super::format_scale(12.0) == "1:12");
+  }
+
+  #[test]
+  fn test_format_count() {
+    assert!(super::format_count(0) == "0");
+    assert!(super::format_count(999) == "999");
+    assert!(super::format_count(1000) == "1,000");
+    assert!(super::format_count(12_345) == "12,345");
+    assert!(super::format_count(1_234_567) == "1,234,567");
+  }
+
+  #[test]
+  fn test_chart_series() {
+    use super::ChartSeries;
+
+    assert!(ChartSeries::from_stem("Seattle SEC") == Some(ChartSeries::Sectional));
+    assert!(ChartSeries::from_stem("Seattle TAC") == Some(ChartSeries::Terminal));
+    assert!(ChartSeries::from_stem("Los Angeles FLY") == Some(ChartSeries::Flyway));
+    assert!(ChartSeries::from_stem("U.S. Gulf Coast HEL") == Some(ChartSeries::Helicopter));
+    assert!(ChartSeries::from_stem("ENR_L01") == Some(ChartSeries::EnrouteLow));
+    assert!(ChartSeries::from_stem("ENR_AKH01") == Some(ChartSeries::EnrouteHigh));
+    assert!(ChartSeries::from_stem("ENR_A02") == Some(ChartSeries::Area));
+    assert!(ChartSeries::from_stem("enr_h02") == Some(ChartSeries::EnrouteHigh));
+    assert!(ChartSeries::from_stem("Grand Canyon").is_none());
+
+    assert!(super::chart_label("Seattle SEC") == "Seattle SEC\nSectional");
+    assert!(super::chart_label("Grand Canyon") == "Grand Canyon");
+  }
+
+  #[test]
+  fn test_emphasis() {
+    use super::Emphasis;
+
+    let water = [92, 160, 212];
+    let terrain = [204, 170, 110];
+    let gray = [128, 128, 128];
+    assert!(Emphasis::Water.matches(water) && !Emphasis::Terrain.matches(water));
+    assert!(Emphasis::Terrain.matches(terrain) && !Emphasis::Water.matches(terrain));
+    assert!(!Emphasis::Water.matches(gray) && !Emphasis::Terrain.matches(gray));
+    assert!(!Emphasis::Off.matches(water));
+
+    // Boosted colors move away from gray.
+    let [r, g, b] = super::emphasize(water);
+    assert!(r < water[0] && b > water[2] && g >= water[1]);
+    assert!(super::emphasize(gray) == [147, 147, 147]);
+    assert!(super::emphasize([255, 255, 255]) == [255, 255, 255]);
+
+    assert!(super::parse_indices("17, 3,,42 ,3") == Some(vec![3, 17, 42]));
+    assert!(super::parse_indices("") == Some(Vec::new()));
+    assert!(super::parse_indices("3, 256").is_none());
+    assert!(super::parse_indices("water").is_none());
+    assert!(super::format_indices(&[3, 17, 42]) == "3, 17, 42");
+  }
+
+  #[test]
+  fn test_fuzzy_match() {
+    assert!(super::edit_distance("", "") == 0);
+    assert!(super::edit_distance("KITTEN", "SITTING") == 3);
+    assert!(super::edit_distance("ABC", "") == 3);
+
+    assert!(super::fuzzy_match("MONTEREY", "MONTERY REGIONAL") == Some(1));
+    assert!(super::fuzzy_match("OAKLAND", "METROPOLITAN OAKLAND INTL") == Some(0));
+    assert!(super::fuzzy_match("LIVERMOOR", "LIVERMORE MUNI") == Some(2));
+
+    // Short words must match exactly.
+    assert!(super::fuzzy_match("SAM JOSE", "SAN JOSE INTL").is_none());
+    assert!(super::fuzzy_match("", "ANY").is_none());
+  }
+
   #[test]
   fn test_dd_lat_lon_conversion() {
     let dd = super::to_dec_deg(0.0, 59.0, 60.0).unwrap();
@@ -651,6 +2040,238 @@ mod test {
     assert!(lon == "117°08'47.00\"W");
   }
 
+  #[test]
+  fn test_coord_formats() {
+    use super::{Coord, CoordFormat};
+
+    let dd = super::to_dec_deg(34.0, 5.0, 6.9).unwrap();
+    assert!(super::format_lat_dm(dd).unwrap() == "34°05.12'N");
+    assert!(super::format_lat_dd(dd).unwrap() == "34.08525°N");
+
+    let dd = super::to_dec_deg(-117.0, 8.0, 47.0).unwrap();
+    assert!(super::format_lon_dm(dd).unwrap() == "117°08.78'W");
+    assert!(super::format_lon_dd(dd).unwrap() == "117.14639°W");
+
+    // Minutes should not round up to 60.
+    let dd = super::to_dec_deg(22.0, 59.0, 59.999).unwrap();
+    assert!(super::format_lon_dm(dd).unwrap() == "023°00.00'E");
+
+    let coord = Coord { x: 22.5, y: -26.25 };
+    let text = CoordFormat::Decimal.format(coord).unwrap();
+    assert!(text == "26.25000°S, 022.50000°E");
+
+    for fmt in CoordFormat::ALL {
+      let value = serde_json::to_value(fmt).unwrap();
+      assert!(serde_json::from_value::<CoordFormat>(value).unwrap() == fmt);
+    }
+    assert!(serde_json::to_value(CoordFormat::DegMin).unwrap() == "dm");
+
+    // Cycling visits every format and comes back around.
+    let mut fmt = CoordFormat::default();
+    for expected in CoordFormat::ALL
+      .iter()
+      .cycle()
+      .skip(1)
+      .take(CoordFormat::ALL.len())
+    {
+      fmt = fmt.next();
+      assert!(fmt == *expected);
+    }
+    assert!(fmt == CoordFormat::default());
+  }
+
+  #[test]
+  fn test_units() {
+    use super::Units;
+
+    assert!(Units::Nautical.from_meters(1852.0) == 1.0);
+    assert!(Units::Statute.from_meters(1609.344) == 1.0);
+    assert!(Units::Metric.from_meters(1000.0) == 1.0);
+    assert!(Units::Nautical.to_meters(1.0) == 1852.0);
+    assert!(Units::Statute.to_meters(1.0) == 1609.344);
+    assert!(Units::Metric.to_meters(1.0) == 1000.0);
+
+    for units in Units::ALL {
+      let value = serde_json::to_value(units).unwrap();
+      assert!(serde_json::from_value::<Units>(value).unwrap() == units);
+    }
+    assert!(serde_json::to_value(Units::Statute).unwrap() == "sm");
+    assert!(serde_json::from_value::<Units>(serde_json::json!("furlongs")).is_err());
+  }
+
+  #[test]
+  fn test_scroll_snap() {
+    use super::ScrollSnap;
+
+    assert!(ScrollSnap::Rest.should_snap(0.0, false));
+    assert!(!ScrollSnap::Rest.should_snap(10.0, false));
+    assert!(ScrollSnap::Slow.should_snap(10.0, false));
+    assert!(!ScrollSnap::Slow.should_snap(10.0, true));
+    assert!(!ScrollSnap::Slow.should_snap(500.0, false));
+    assert!(!ScrollSnap::Off.should_snap(0.0, false));
+
+    // A gentle flick is well above egui's stop speed: only Slow snaps while it's still gliding.
+    let speed = 250.0;
+    assert!(ScrollSnap::Slow.should_snap(speed, false));
+    assert!(!ScrollSnap::Rest.should_snap(speed, false));
+  }
+
+  #[test]
+  fn test_sharpen() {
+    use eframe::epaint;
+
+    // A uniform image should not change.
+    let gray = epaint::Color32::from_gray(128);
+    let mut image = epaint::ColorImage::new([4, 4], gray);
+    super::sharpen(&mut image, 1.0);
+    assert!(image.pixels.iter().all(|c| *c == gray));
+
+    // A bright pixel surrounded by darker pixels should get brighter.
+    let mut image = epaint::ColorImage::new([3, 3], gray);
+    image.pixels[4] = epaint::Color32::from_gray(160);
+    super::sharpen(&mut image, 1.0);
+    assert!(image.pixels[4].r() > 160);
+
+    // Zero strength does nothing.
+    let mut image = epaint::ColorImage::new([3, 3], gray);
+    image.pixels[4] = epaint::Color32::from_gray(160);
+    super::sharpen(&mut image, 0.0);
+    assert!(image.pixels[4].r() == 160);
+  }
+
+  #[test]
+  fn test_great_circle() {
+    use super::Coord;
+
+    // One degree of latitude along a meridian.
+    let from = Coord { x: 0.0, y: 0.0 };
+    let to = Coord { x: 0.0, y: 1.0 };
+    assert!((super::gc_distance(from, to) - 111195.08).abs() < 0.01);
+    assert!(super::gc_bearing(from, to).abs() < 1.0e-9);
+
+    // Due east along the equator.
+    let to = Coord { x: 1.0, y: 0.0 };
+    assert!((super::gc_bearing(from, to) - 90.0).abs() < 1.0e-9);
+
+    // KLAX to KJFK.
+    let lax = Coord {
+      x: -118.4081,
+      y: 33.9425,
+    };
+    let jfk = Coord {
+      x: -73.7789,
+      y: 40.6397,
+    };
+    assert!((super::gc_distance(lax, jfk) - 3974211.7).abs() < 1.0);
+    assert!((super::gc_bearing(lax, jfk) - 65.87).abs() < 0.01);
+    assert!((super::gc_bearing(jfk, lax) - 273.84).abs() < 0.01);
+
+    // Travel from KLAX towards KJFK.
+    let bearing = super::gc_bearing(lax, jfk);
+    let dist = super::gc_distance(lax, jfk);
+    let dest = super::gc_destination(lax, bearing, dist);
+    assert!((dest.x - jfk.x).abs() < 1.0e-6 && (dest.y - jfk.y).abs() < 1.0e-6);
+
+    // One degree north along a meridian.
+    let dest = super::gc_destination(from, 0.0, 111195.08);
+    assert!(dest.x.abs() < 1.0e-9 && (dest.y - 1.0).abs() < 1.0e-6);
+  }
+
+  #[test]
+  fn test_triangulate() {
+    use eframe::emath::pos2;
+
+    // Sum the areas of the triangles.
+    let area = |points: &[eframe::emath::Pos2], indices: &[u32]| -> f32 {
+      let triangles = indices.chunks(3).map(|triangle| {
+        let [a, b, c] = [0, 1, 2].map(|index| points[triangle[index] as usize]);
+        ((b - a).x * (c - a).y - (b - a).y * (c - a).x).abs() * 0.5
+      });
+      triangles.sum()
+    };
+
+    // Not enough points.
+    assert!(super::triangulate(&[pos2(0.0, 0.0), pos2(1.0, 0.0)]).is_none());
+
+    // Convex in either winding order, even with a straight corner.
+    let mut square = [
+      pos2(0.0, 0.0),
+      pos2(1.0, 0.0),
+      pos2(2.0, 0.0),
+      pos2(2.0, 2.0),
+      pos2(0.0, 2.0),
+    ];
+    let indices = super::triangulate(&square).unwrap();
+    assert!(indices.len() == 9);
+    assert!(area(&square, &indices) == 4.0);
+    square.reverse();
+    let indices = super::triangulate(&square).unwrap();
+    assert!(area(&square, &indices) == 4.0);
+
+    // Concave.
+    let ell = [
+      pos2(0.0, 0.0),
+      pos2(2.0, 0.0),
+      pos2(2.0, 1.0),
+      pos2(1.0, 1.0),
+      pos2(1.0, 2.0),
+      pos2(0.0, 2.0),
+    ];
+    let indices = super::triangulate(&ell).unwrap();
+    assert!(indices.len() == 12);
+    assert!(area(&ell, &indices) == 3.0);
+
+    // Crosses itself.
+    let star = [
+      pos2(0.0, -10.0),
+      pos2(5.9, 8.1),
+      pos2(-9.5, -3.1),
+      pos2(9.5, -3.1),
+      pos2(-5.9, 8.1),
+    ];
+    assert!(super::triangulate(&star).is_none());
+  }
+
+  #[test]
+  fn test_polygon_area() {
+    use super::Coord;
+    use std::f64::consts::PI;
+
+    // Not enough points to enclose anything.
+    let line = [Coord { x: 0.0, y: 0.0 }, Coord { x: 1.0, y: 0.0 }];
+    assert!(super::polygon_area(&line) == 0.0);
+
+    // One eighth of the sphere, in either winding order.
+    let mut octant = [
+      Coord { x: 0.0, y: 0.0 },
+      Coord { x: 90.0, y: 0.0 },
+      Coord { x: 0.0, y: 90.0 },
+    ];
+    let expected = 0.5 * PI * super::EARTH_RADIUS * super::EARTH_RADIUS;
+    assert!((super::polygon_area(&octant) / expected - 1.0).abs() < 1.0e-9);
+    octant.reverse();
+    assert!((super::polygon_area(&octant) / expected - 1.0).abs() < 1.0e-9);
+
+    // A small one degree square at the equator is nearly planar.
+    let square = [
+      Coord { x: 0.0, y: 0.0 },
+      Coord { x: 1.0, y: 0.0 },
+      Coord { x: 1.0, y: 1.0 },
+      Coord { x: 0.0, y: 1.0 },
+    ];
+    let side = 111195.08;
+    assert!((super::polygon_area(&square) / (side * side) - 1.0).abs() < 1.0e-3);
+
+    // Crossing the antimeridian.
+    let square = [
+      Coord { x: 179.5, y: 0.0 },
+      Coord { x: -179.5, y: 0.0 },
+      Coord { x: -179.5, y: 1.0 },
+      Coord { x: 179.5, y: 1.0 },
+    ];
+    assert!((super::polygon_area(&square) / (side * side) - 1.0).abs() < 1.0e-3);
+  }
+
   #[test]
   fn test_title_case() {
     assert!(super::title_case("title case text") == "Title Case Text");
@@ -701,4 +2322,90 @@ mod test {
     assert!(val.to_i32().is_none());
     assert!(val.to_u32().is_none());
   }
+
+  #[test]
+  fn test_clamp_win_pos() {
+    use super::{Pos, Size};
+
+    let origin = Pos { x: 0, y: 0 };
+    let monitor = Size { w: 1920, h: 1080 };
+    let size = Size { w: 800, h: 600 };
+
+    // Already on the monitor.
+    let pos = Pos { x: 100, y: 200 };
+    assert!(super::clamp_win_pos(pos, size, origin, monitor) == pos);
+
+    // Off to the right of the monitor.
+    let pos = Pos { x: 2500, y: 200 };
+    let clamped = super::clamp_win_pos(pos, size, origin, monitor);
+    assert!(clamped == Pos { x: 1120, y: 200 });
+
+    // Off to the upper-left of the monitor.
+    let pos = Pos { x: -900, y: -50 };
+    assert!(super::clamp_win_pos(pos, size, origin, monitor) == origin);
+
+    // On a monitor to the left of the origin.
+    let left = Pos { x: -1920, y: 0 };
+    let pos = Pos { x: -1000, y: 200 };
+    assert!(super::clamp_win_pos(pos, size, left, monitor) == pos);
+    let pos = Pos { x: -500, y: 900 };
+    let clamped = super::clamp_win_pos(pos, size, left, monitor);
+    assert!(clamped == Pos { x: -800, y: 480 });
+    let pos = Pos { x: 100, y: 200 };
+    let clamped = super::clamp_win_pos(pos, size, left, monitor);
+    assert!(clamped == Pos { x: -800, y: 200 });
+
+    // Larger than the monitor.
+    let pos = Pos { x: 10, y: 10 };
+    let size = Size { w: 2000, h: 1200 };
+    assert!(super::clamp_win_pos(pos, size, origin, monitor) == origin);
+    let clamped = super::clamp_win_pos(pos, size, left, monitor);
+    assert!(clamped == left);
+  }
+
+  #[test]
+  fn test_on_monitor() {
+    use super::{Pos, Size};
+
+    let origin = Pos { x: 0, y: 0 };
+    let monitor = Size { w: 1920, h: 1080 };
+    assert!(super::on_monitor(Pos { x: 100, y: 200 }, origin, monitor));
+    assert!(super::on_monitor(Pos { x: 1919, y: 1079 }, origin, monitor));
+
+    // Positions that may be on another monitor.
+    assert!(!super::on_monitor(
+      Pos { x: -1000, y: 200 },
+      origin,
+      monitor
+    ));
+    assert!(!super::on_monitor(Pos { x: 2500, y: 200 }, origin, monitor));
+    assert!(!super::on_monitor(Pos { x: 100, y: 1080 }, origin, monitor));
+
+    // A monitor to the left of the origin.
+    let left = Pos { x: -1920, y: 0 };
+    assert!(super::on_monitor(Pos { x: -1000, y: 200 }, left, monitor));
+    assert!(!super::on_monitor(Pos { x: 100, y: 200 }, left, monitor));
+  }
+
+  #[test]
+  fn test_color_vision() {
+    use super::ColorVision;
+
+    let red = [255, 0, 0];
+    assert!(ColorVision::Normal.remap(red) == red);
+
+    for vision in ColorVision::ALL {
+      // Grays are not affected.
+      for gray in [0, 64, 128, 255] {
+        assert!(vision.remap([gray; 3]) == [gray; 3]);
+      }
+    }
+
+    // Red is shifted towards blue for protanopia and deuteranopia.
+    assert!(ColorVision::Protanopia.remap(red)[2] > 0);
+    assert!(ColorVision::Deuteranopia.remap(red)[2] > 0);
+
+    // Blue is shifted towards red for tritanopia.
+    assert!(ColorVision::Tritanopia.remap([0, 0, 255])[0] > 0);
+  }
 }